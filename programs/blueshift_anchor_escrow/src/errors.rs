@@ -12,4 +12,126 @@ pub enum EscrowError {
     InvalidMintB,
     #[msg("Invalid maker balance")]
     InsufficientFunds,
+    #[msg("Offer expired")]
+    OfferExpired,
+    #[msg("Refund too early")]
+    RefundTooEarly,
+    #[msg("Invalid fill amount")]
+    InvalidFillAmount,
+    #[msg("Fee too high")]
+    FeeTooHigh,
+    #[msg("Unauthorized taker")]
+    UnauthorizedTaker,
+    #[msg("Slippage exceeded")]
+    SlippageExceeded,
+    #[msg("Mint b is not the native mint")]
+    NotNativeMint,
+    #[msg("Escrow not active")]
+    EscrowNotActive,
+    #[msg("Token account is frozen")]
+    AccountFrozen,
+    #[msg("Fee calculation overflow")]
+    FeeCalculationOverflow,
+    #[msg("Invalid auction parameters")]
+    InvalidAuctionParams,
+    #[msg("Invalid account group")]
+    InvalidAccountGroup,
+    #[msg("Escrow still open")]
+    EscrowStillOpen,
+    #[msg("Fill below minimum")]
+    FillBelowMinimum,
+    #[msg("Fill above maximum")]
+    FillAboveMaximum,
+    #[msg("Unauthorized")]
+    Unauthorized,
+    #[msg("Program paused")]
+    ProgramPaused,
+    #[msg("Math overflow")]
+    MathOverflow,
+    #[msg("Maker cannot take own escrow")]
+    MakerCannotTake,
+    #[msg("Vault still holds tokens")]
+    VaultNotEmpty,
+    #[msg("Invalid bump")]
+    InvalidBump,
+    #[msg("Delegate approval revoked")]
+    DelegateRevoked,
+    #[msg("Mint decimals mismatch")]
+    DecimalsMismatch,
+    #[msg("Escrow is not partially filled")]
+    NotPartiallyFilled,
+    #[msg("Vault is empty")]
+    VaultEmpty,
+    #[msg("Token account is not the canonical associated token account")]
+    NonCanonicalAta,
+    #[msg("Basket escrows must use the basket instructions")]
+    BasketMismatch,
+    #[msg("Reentrancy detected")]
+    ReentrancyDetected,
+    #[msg("Mint is non-transferable")]
+    NonTransferableMint,
+    #[msg("Offer has not expired")]
+    OfferNotExpired,
+    #[msg("Maker token account does not exist")]
+    MakerAtaMissing,
+    #[msg("Seed already in use by an open escrow")]
+    SeedAlreadyInUse,
+    #[msg("Escrow below minimum size")]
+    BelowMinimumSize,
+    #[msg("Duplicate token account")]
+    DuplicateTokenAccount,
+    #[msg("Vault authority is not the escrow")]
+    InvalidVaultAuthority,
+    #[msg("Token pair not allowed")]
+    PairNotAllowed,
+    #[msg("Vault balance lower than expected")]
+    VaultBalanceMismatch,
+    #[msg("Permanent delegate mint not allowed")]
+    PermanentDelegateNotAllowed,
+    #[msg("Expiry too far in the future")]
+    ExpiryTooFar,
+    #[msg("Escrow exists with different terms")]
+    EscrowExistsWithDifferentTerms,
+    #[msg("Reveal too early")]
+    RevealTooEarly,
+    #[msg("Commitment does not match")]
+    InvalidCommitment,
+    #[msg("Invalid split")]
+    InvalidSplit,
+    #[msg("Funder is not an approved delegate")]
+    InvalidDelegate,
+    #[msg("Too many open escrows for this maker")]
+    TooManyOpenEscrows,
+    #[msg("Maker stats account required")]
+    MakerStatsRequired,
+    #[msg("Invalid escrow account")]
+    InvalidEscrowAccount,
+    #[msg("Cranker bounty exceeds reclaimable rent")]
+    BountyTooHigh,
+    #[msg("Transaction expired")]
+    TransactionExpired,
+    #[msg("Take memo required")]
+    MemoRequired,
+    #[msg("Take memo too long")]
+    MemoTooLong,
+    #[msg("Transfer would leave the source account below rent exemption")]
+    WouldBreakRentExemption,
+    #[msg("Invalid ratio price")]
+    InvalidPrice,
+    #[msg("Pre-created maker Token B account must belong to the proceeds recipient")]
+    MakerAtaRecipientMismatch,
+    #[msg("Seed does not match the maker's next auto seed")]
+    AutoSeedMismatch,
+    #[msg("Not enough unreserved deposit")]
+    InsufficientAvailable,
+    #[msg("Invalid reservation expiry")]
+    InvalidReservationExpiry,
+    #[msg("Reservation has expired")]
+    ReservationExpired,
+    #[msg("Reservation has not expired yet")]
+    ReservationNotExpired,
+    #[msg("Taker does not meet the gate token requirement")]
+    GateRequirementNotMet,
+    #[msg("Not enough lamports to pay rent for a new token account")]
+    InsufficientLamportsForRent,
 }
\ No newline at end of file
@@ -0,0 +1,53 @@
+use anchor_lang::prelude::*;
+
+/// Escrow 创建事件
+///
+/// make 指令在初始化 Escrow 并存入 Token A 之后发出，
+/// 供 indexer / 前端订阅以构建订单簿
+#[event]
+pub struct EscrowCreated {
+    pub escrow: Pubkey,
+    pub maker: Pubkey,
+    pub mint_a: Pubkey,
+    pub mint_b: Pubkey,
+    pub deposit: u64,
+    pub receive: u64,
+    pub seed: u64,
+    pub created_at: i64,
+    /// make 时附带的附言（0 填充的 UTF-8）
+    pub memo: [u8; 32],
+}
+
+/// Escrow 成交事件（支持部分成交）
+///
+/// remaining_deposit 为本次成交后 escrow 剩余的 Token A 数量，
+/// 为 0 表示已全部成交且 escrow 已关闭
+#[event]
+pub struct EscrowTaken {
+    pub escrow: Pubkey,
+    pub taker: Pubkey,
+    pub amount_a: u64,
+    pub amount_b: u64,
+    pub remaining_deposit: u64,
+    /// take 时附带的附言（其他成交指令为空）
+    pub memo: Vec<u8>,
+}
+
+/// Escrow 退款事件（maker 撤回报价）
+#[event]
+pub struct EscrowRefunded {
+    pub escrow: Pubkey,
+    pub maker: Pubkey,
+    pub amount_refunded: u64,
+}
+
+/// Escrow 部分退款事件（maker 撤回部分 Token A，escrow 保持挂单）
+///
+/// remaining_deposit 为 0 表示已全部撤回且 escrow 已关闭
+#[event]
+pub struct EscrowPartiallyRefunded {
+    pub escrow: Pubkey,
+    pub maker: Pubkey,
+    pub amount_refunded: u64,
+    pub remaining_deposit: u64,
+}
@@ -0,0 +1,65 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::Mint;
+
+use crate::state::{Config, PairRegistry};
+use crate::errors::EscrowError;
+
+/// AddPair 指令：
+///
+/// 配置管理员登记允许的交易对（mint_a -> mint_b，有方向），
+/// config.restrict_pairs 为 true 时 make 只接受已登记的交易对
+#[derive(Accounts)]
+pub struct AddPair<'info> {
+    /// 配置管理员，同时支付 PairRegistry 账户租金
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    /// 全局 Config PDA
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        has_one = authority @ EscrowError::Unauthorized,
+    )]
+    pub config: Account<'info, Config>,
+
+    /// 交易对白名单 PDA
+    ///
+    /// PDA seeds:
+    /// - "pair"
+    /// - mint_a
+    /// - mint_b
+    #[account(
+        init,
+        payer = authority,
+        space = PairRegistry::INIT_SPACE + PairRegistry::DISCRIMINATOR.len(),
+        seeds = [b"pair", mint_a.key().as_ref(), mint_b.key().as_ref()],
+        bump,
+    )]
+    pub pair_registry: Account<'info, PairRegistry>,
+
+    /// maker 存入的 Token A 的 mint
+    pub mint_a: InterfaceAccount<'info, Mint>,
+
+    /// maker 希望换取的 Token B 的 mint
+    pub mint_b: InterfaceAccount<'info, Mint>,
+
+    /// System Program（创建 PairRegistry PDA）
+    pub system_program: Program<'info, System>,
+}
+
+/// AddPair 指令入口
+pub fn handler(ctx: Context<AddPair>) -> Result<()> {
+    require_keys_neq!(
+        ctx.accounts.mint_a.key(),
+        ctx.accounts.mint_b.key(),
+        EscrowError::InvalidMintA
+    );
+
+    ctx.accounts.pair_registry.set_inner(PairRegistry {
+        mint_a: ctx.accounts.mint_a.key(),
+        mint_b: ctx.accounts.mint_b.key(),
+        bump: ctx.bumps.pair_registry,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,606 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::{get_associated_token_address_with_program_id, AssociatedToken},
+    token_interface::{
+        Mint,
+        TokenAccount,
+        TokenInterface,
+        TransferChecked,
+        CloseAccount,
+    },
+};
+
+use crate::state::{Config, Escrow, EscrowStatus};
+use crate::errors::EscrowError;
+use crate::events::EscrowTaken;
+use crate::instructions::take::{
+    gross_up_transfer_fee, quote_fill, sweep_and_close_vault, transfer_checked_with_hook,
+};
+
+/// 每个 escrow 在 remaining_accounts 中占用的账户数
+pub const BATCH_TAKE_GROUP_LEN: usize = 4;
+
+/// BatchTake 指令：
+///
+/// 在一笔交易中原子地全部成交多个 escrow（mint_a / mint_b 必须相同），
+/// 任意一个 escrow 成交失败则整笔交易回滚。先校验全部 escrow 并报价，
+/// 再依次转账，校验失败时不会发生任何 CPI。
+///
+/// group 中不含 MakerStats，config 限制挂单数（max_open_per_maker > 0）时不可用，
+/// 需逐个使用 take 成交，否则 maker 的未关闭计数无法扣减
+///
+/// remaining_accounts 前 escrow_count * 4 个账户按每个 escrow 4 个依次排列：
+/// 1. maker（mut，接收 Token B 与 lamports）
+/// 2. escrow（mut）
+/// 3. vault（mut，escrow 持有的 mint_a ATA）
+/// 4. maker_ata_b（mut，maker 的 mint_b ATA，必须已存在）
+///
+/// 其后为 mint_a / mint_b 的 transfer hook 所需账户（与 take 相同）
+#[derive(Accounts)]
+pub struct BatchTake<'info> {
+    /// 接受报价的用户（支付 Token B）
+    #[account(mut)]
+    pub taker: Signer<'info>,
+
+    /// 全局配置（协议费率与 treasury）
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+    )]
+    pub config: Box<Account<'info, Config>>,
+
+    /// CHECK: 仅作为 treasury ATA 的 authority，地址由 config.treasury 约束
+    #[account(address = config.treasury)]
+    pub treasury: UncheckedAccount<'info>,
+
+    /// ===== Token Mints =====
+
+    /// Token A（所有 escrow 共同的 mint_a）
+    #[account(
+        mint::token_program = token_program
+    )]
+    pub mint_a: Box<InterfaceAccount<'info, Mint>>,
+
+    /// Token B（每个 escrow 的 mint_b 或 mint_b_alt 之一）
+    #[account(
+        mint::token_program = token_program
+    )]
+    pub mint_b: Box<InterfaceAccount<'info, Mint>>,
+
+    /// ===== Token Accounts =====
+
+    /// Taker 的 Token A ATA（接收所有 vault 的 Token A）
+    #[account(
+        init_if_needed,
+        payer = taker,
+        associated_token::mint = mint_a,
+        associated_token::authority = taker,
+        associated_token::token_program = token_program
+    )]
+    pub taker_ata_a: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Taker 的 Token B ATA（支付给各 maker）
+    #[account(
+        mut,
+        associated_token::mint = mint_b,
+        associated_token::authority = taker,
+        associated_token::token_program = token_program
+    )]
+    pub taker_ata_b: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Treasury 的 Token B ATA（接收协议费）
+    #[account(
+        init_if_needed,
+        payer = taker,
+        associated_token::mint = mint_b,
+        associated_token::authority = treasury,
+        associated_token::token_program = token_program
+    )]
+    pub treasury_ata_b: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// ===== Programs =====
+
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+/// 一个 escrow 的成交报价（校验阶段产生，执行阶段使用）
+struct BatchFill<'info> {
+    group: &'info [AccountInfo<'info>],
+    escrow: Account<'info, Escrow>,
+    alt: bool,
+    fill_amount: u64,
+    amount_b: u64,
+    fee: u64,
+    maker_gross: u64,
+}
+
+impl<'info> BatchTake<'info> {
+    /// 将 Token B 从 taker 转给 to
+    fn transfer_from_taker(
+        &self,
+        to: AccountInfo<'info>,
+        amount: u64,
+        remaining: &[AccountInfo<'info>],
+    ) -> Result<()> {
+        transfer_checked_with_hook(
+            CpiContext::new(
+                self.token_program.to_account_info(),
+                TransferChecked {
+                    from: self.taker_ata_b.to_account_info(),
+                    to,
+                    mint: self.mint_b.to_account_info(),
+                    authority: self.taker.to_account_info(),
+                },
+            )
+            .with_remaining_accounts(remaining.to_vec()),
+            amount,
+            self.mint_b.decimals,
+        )
+    }
+
+    /// 校验一个 escrow（等价于 Take 的账户约束与 quote_fill）并按全部成交报价，不执行任何 CPI
+    fn quote_one(&self, group: &'info [AccountInfo<'info>]) -> Result<BatchFill<'info>> {
+        let [maker, escrow_info, vault_info, maker_ata_b] = group else {
+            return err!(EscrowError::InvalidAccountGroup);
+        };
+
+        // =======================
+        // 校验 escrow 账户
+        // =======================
+        let escrow = Account::<Escrow>::try_from(escrow_info)?;
+
+        let seed_bytes = escrow.seed.to_le_bytes();
+        let pda = Pubkey::create_program_address(&escrow.signer_seeds(&seed_bytes), &crate::ID)
+            .map_err(|_| error!(EscrowError::InvalidAccountGroup))?;
+        require_keys_eq!(pda, escrow.key(), EscrowError::InvalidAccountGroup);
+
+        require_keys_eq!(escrow.maker, maker.key(), EscrowError::InvalidMaker);
+        // 批量成交时关闭账户的 lamports 只能返还给 group 内的 maker，
+        // 设置了 rent_collector / vault_rent_payer / proceeds_recipient 的 escrow
+        // 需使用 take 单独成交
+        require_keys_eq!(
+            escrow.rent_destination(maker.key()),
+            maker.key(),
+            EscrowError::InvalidAccountGroup
+        );
+        require_keys_eq!(
+            escrow.vault_rent_destination(maker.key()),
+            maker.key(),
+            EscrowError::InvalidAccountGroup
+        );
+        require_keys_eq!(
+            escrow.proceeds_destination(),
+            maker.key(),
+            EscrowError::InvalidAccountGroup
+        );
+        require_keys_eq!(escrow.mint_a, self.mint_a.key(), EscrowError::InvalidMintA);
+        let alt = escrow.check_mint_b(&self.mint_b.key())?;
+        require!(!escrow.is_basket(), EscrowError::BasketMismatch);
+        require!(
+            self.mint_a.decimals == escrow.mint_a_decimals
+                && escrow.mint_b_decimals_match(&self.mint_b.key(), self.mint_b.decimals),
+            EscrowError::DecimalsMismatch
+        );
+
+        // =======================
+        // 成交条件与报价（全部成交，不接受门槛账户）
+        // =======================
+        let fill_amount = escrow.deposit;
+        let amount_b = quote_fill(
+            &escrow,
+            &self.config,
+            self.taker.key,
+            None,
+            fill_amount,
+            u64::MAX,
+            alt,
+        )?;
+
+        // =======================
+        // 校验 token 账户
+        // =======================
+        require_keys_neq!(
+            self.taker_ata_b.key(),
+            maker_ata_b.key(),
+            EscrowError::DuplicateTokenAccount
+        );
+
+        // vault 与 maker_ata_b 必须是规范 ATA
+        let token_program = self.token_program.key();
+        require_keys_eq!(
+            vault_info.key(),
+            get_associated_token_address_with_program_id(
+                &escrow.key(),
+                &escrow.mint_a,
+                &token_program,
+            ),
+            EscrowError::InvalidAccountGroup
+        );
+        require_keys_eq!(
+            maker_ata_b.key(),
+            get_associated_token_address_with_program_id(
+                maker.key,
+                &self.mint_b.key(),
+                &token_program,
+            ),
+            EscrowError::InvalidAccountGroup
+        );
+
+        let vault = InterfaceAccount::<TokenAccount>::try_from(vault_info)?;
+        let maker_token_b = InterfaceAccount::<TokenAccount>::try_from(maker_ata_b)?;
+        require_gt!(vault.amount, 0, EscrowError::VaultEmpty);
+        require!(
+            vault.amount >= fill_amount,
+            EscrowError::VaultBalanceMismatch
+        );
+        require!(
+            !vault.is_frozen() && !maker_token_b.is_frozen(),
+            EscrowError::AccountFrozen
+        );
+
+        let fee = self.config.fee_for(&escrow, amount_b)?;
+        let maker_net = amount_b
+            .checked_sub(fee)
+            .ok_or(error!(EscrowError::MathOverflow))?;
+        let maker_gross = gross_up_transfer_fee(&self.mint_b.to_account_info(), maker_net)?;
+
+        Ok(BatchFill {
+            group,
+            escrow,
+            alt,
+            fill_amount,
+            amount_b,
+            fee,
+            maker_gross,
+        })
+    }
+
+    /// 执行一个已报价的成交：转账、关闭 vault 与 escrow
+    fn take_one(&self, fill: BatchFill<'info>, remaining: &[AccountInfo<'info>]) -> Result<()> {
+        let BatchFill {
+            group,
+            mut escrow,
+            alt,
+            fill_amount,
+            amount_b,
+            fee,
+            maker_gross,
+        } = fill;
+        let [maker, escrow_info, vault_info, maker_ata_b] = group else {
+            return err!(EscrowError::InvalidAccountGroup);
+        };
+
+        // 与 take 相同：在任何 CPI 之前写入 in_progress，防止 transfer hook 回调重入
+        escrow.in_progress = true;
+        escrow.exit(&crate::ID)?;
+
+        // 1️⃣ taker -> maker（Token B，扣除协议费）+ taker -> treasury（协议费）
+        self.transfer_from_taker(maker_ata_b.clone(), maker_gross, remaining)?;
+        if fee > 0 {
+            require!(
+                !self.treasury_ata_b.is_frozen(),
+                EscrowError::AccountFrozen
+            );
+            self.transfer_from_taker(self.treasury_ata_b.to_account_info(), fee, remaining)?;
+        }
+
+        // 2️⃣ vault -> taker（Token A），并关闭 vault
+        let seed_bytes = escrow.seed.to_le_bytes();
+        let seeds = escrow.signer_seeds(&seed_bytes);
+        let signer_seeds: [&[&[u8]]; 1] = [&seeds];
+
+        transfer_checked_with_hook(
+            CpiContext::new_with_signer(
+                self.token_program.to_account_info(),
+                TransferChecked {
+                    from: vault_info.clone(),
+                    to: self.taker_ata_a.to_account_info(),
+                    mint: self.mint_a.to_account_info(),
+                    authority: escrow_info.clone(),
+                },
+                &signer_seeds,
+            )
+            .with_remaining_accounts(remaining.to_vec()),
+            fill_amount,
+            self.mint_a.decimals,
+        )?;
+
+        // 额外转入 vault 的 Token A 随最后一笔一并转给 taker
+        sweep_and_close_vault(
+            CpiContext::new_with_signer(
+                self.token_program.to_account_info(),
+                CloseAccount {
+                    account: vault_info.clone(),
+                    authority: escrow_info.clone(),
+                    destination: maker.clone(),
+                },
+                &signer_seeds,
+            )
+            .with_remaining_accounts(remaining.to_vec()),
+            self.mint_a.to_account_info(),
+            self.mint_a.decimals,
+            self.taker_ata_a.to_account_info(),
+        )?;
+
+        // 3️⃣ 关闭 escrow，lamports 返还给 maker
+        escrow.in_progress = false;
+        escrow.apply_fill(fill_amount, amount_b, alt)?;
+        escrow.status = EscrowStatus::Filled;
+        escrow.close(maker.clone())?;
+
+        emit!(EscrowTaken {
+            escrow: escrow.key(),
+            taker: self.taker.key(),
+            amount_a: fill_amount,
+            amount_b,
+            remaining_deposit: escrow.deposit,
+            memo: Vec::new(),
+        });
+
+        Ok(())
+    }
+}
+
+/// BatchTake 指令入口
+///
+/// 参数说明：
+/// - escrow_count: 成交的 escrow 数量（remaining_accounts 中 group 的个数）
+/// - max_total_receive: taker 愿意为全部 escrow 支付的 Token B 总上限（u64::MAX 表示不检查）
+pub fn handler<'info>(
+    ctx: Context<'_, '_, 'info, 'info, BatchTake<'info>>,
+    escrow_count: u8,
+    max_total_receive: u64,
+) -> Result<()> {
+    let groups_len = usize::from(escrow_count) * BATCH_TAKE_GROUP_LEN;
+    require!(
+        escrow_count > 0 && ctx.remaining_accounts.len() >= groups_len,
+        EscrowError::InvalidAccountGroup
+    );
+    let (groups, hook_accounts) = ctx.remaining_accounts.split_at(groups_len);
+
+    // 不接受附言，config.require_take_memo 时只能通过 take 成交
+    ctx.accounts.config.check_take_memo(&[])?;
+
+    require!(
+        !ctx.accounts.config.tracks_open_escrows(),
+        EscrowError::MakerStatsRequired
+    );
+
+    require_keys_neq!(
+        ctx.accounts.mint_a.key(),
+        ctx.accounts.mint_b.key(),
+        EscrowError::InvalidMintA
+    );
+
+    require!(
+        !ctx.accounts.taker_ata_a.is_frozen() && !ctx.accounts.taker_ata_b.is_frozen(),
+        EscrowError::AccountFrozen
+    );
+
+    // 1️⃣ 校验全部 escrow 并报价，任一失败时不会发生任何转账
+    let mut fills = Vec::with_capacity(usize::from(escrow_count));
+    let mut total_receive: u64 = 0;
+    let mut taker_cost: u64 = 0;
+    for group in groups.chunks_exact(BATCH_TAKE_GROUP_LEN) {
+        let fill = ctx.accounts.quote_one(group)?;
+
+        // 同一 escrow 出现两次时第二次成交的是过时的报价
+        require!(
+            fills
+                .iter()
+                .all(|other: &BatchFill| other.escrow.key() != fill.escrow.key()),
+            EscrowError::InvalidAccountGroup
+        );
+
+        total_receive = total_receive
+            .checked_add(fill.amount_b)
+            .ok_or(error!(EscrowError::MathOverflow))?;
+        taker_cost = fill
+            .maker_gross
+            .checked_add(fill.fee)
+            .and_then(|cost| cost.checked_add(taker_cost))
+            .ok_or(error!(EscrowError::MathOverflow))?;
+        fills.push(fill);
+    }
+
+    // 任一 escrow 价格高于预期时整笔回滚
+    require!(
+        total_receive <= max_total_receive,
+        EscrowError::SlippageExceeded
+    );
+
+    // taker 余额不足时在转出任何 Token B 之前拒绝
+    require!(
+        ctx.accounts.taker_ata_b.amount >= taker_cost,
+        EscrowError::InsufficientFunds
+    );
+
+    // 2️⃣ 依次成交
+    for fill in fills {
+        ctx.accounts.take_one(fill, hook_accounts)?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use anchor_spl::token::spl_token;
+
+    use super::*;
+    use crate::test_utils::*;
+
+    const BATCH_TAKE: u8 = 8;
+
+    struct Batch {
+        taker: Pubkey,
+        config: Config,
+        mint_a: TestAccount,
+        mint_b: TestAccount,
+        taker_b: u64,
+        groups: Vec<TestAccount>,
+    }
+
+    impl Batch {
+        fn new() -> Self {
+            let (mint_a, mint_b) = setup_mints(6, 6);
+
+            Self {
+                taker: Pubkey::new_unique(),
+                config: default_config(),
+                mint_a,
+                mint_b,
+                taker_b: 1_000,
+                groups: Vec::new(),
+            }
+        }
+
+        /// 添加一个 escrow（maker 的 vault 存有 deposit，maker_ata_b 已存在）
+        fn add(&mut self, escrow: &Escrow) {
+            self.add_at(escrow_account(escrow).key, escrow);
+        }
+
+        /// 添加一个位于 key 地址的 escrow
+        fn add_at(&mut self, key: Pubkey, escrow: &Escrow) {
+            self.groups.extend([
+                TestAccount::wallet(escrow.maker, 0).writable(),
+                program_account(key, escrow).writable(),
+                fund_ata(&key, &self.mint_a, escrow.deposit).writable(),
+                fund_ata(&escrow.maker, &self.mint_b, 0).writable(),
+            ]);
+        }
+
+        fn escrow(&self, seed: u64, deposit: u64, receive: u64) -> Escrow {
+            make_escrow(&Pubkey::new_unique(), seed, &self.mint_a, &self.mint_b, deposit, receive)
+        }
+
+        fn run(&self, escrow_count: u8) -> (ProgramResult, Vec<AccountInfo<'static>>) {
+            let mut accounts = vec![
+                TestAccount::wallet(self.taker, 1_000_000_000).signer(),
+                config_account(&self.config),
+                TestAccount::wallet(self.config.treasury, 0),
+                self.mint_a.clone(),
+                self.mint_b.clone(),
+                fund_ata(&self.taker, &self.mint_a, 0).writable(),
+                fund_ata(&self.taker, &self.mint_b, self.taker_b).writable(),
+                fund_ata(&self.config.treasury, &self.mint_b, 0).writable(),
+                TestAccount::program(anchor_spl::associated_token::ID),
+                TestAccount::program(spl_token::ID),
+                TestAccount::program(anchor_lang::system_program::ID),
+            ];
+            accounts.extend(self.groups.iter().cloned());
+
+            run(&ix_data(BATCH_TAKE, (escrow_count, u64::MAX)), accounts)
+        }
+    }
+
+    /// 第二个 escrow 校验失败时整笔拒绝，第一个 escrow 未被修改
+    #[test]
+    fn invalid_group_rejects_whole_batch() {
+        set_clock(1_000, 1);
+        let mut batch = Batch::new();
+        let first = batch.escrow(1, 100, 50);
+        let mut second = batch.escrow(2, 100, 50);
+        second.expiry = 999;
+        batch.add(&first);
+        batch.add(&second);
+
+        let (result, infos) = batch.run(2);
+
+        assert_error(result, EscrowError::OfferExpired);
+        let stored = Escrow::try_deserialize(&mut &infos[12].data.borrow()[..]).unwrap();
+        assert!(!stored.in_progress);
+        assert_eq!(stored.deposit, 100);
+    }
+
+    #[test]
+    fn escrow_mid_fill_is_rejected() {
+        set_clock(1_000, 1);
+        let mut batch = Batch::new();
+        let mut escrow = batch.escrow(1, 100, 50);
+        escrow.in_progress = true;
+        batch.add(&escrow);
+
+        assert_error(batch.run(1).0, EscrowError::ReentrancyDetected);
+    }
+
+    #[cfg(feature = "strict-pda")]
+    #[test]
+    fn non_canonical_bump_is_rejected() {
+        set_clock(1_000, 1);
+        let mut batch = Batch::new();
+        let mut escrow = batch.escrow(1, 100, 50);
+
+        // 以非规范 bump 推导出的（同样有效的）PDA 地址存放 escrow
+        let seed_bytes = escrow.seed.to_le_bytes();
+        let key = (0..escrow.bump)
+            .rev()
+            .find_map(|bump| {
+                escrow.bump = bump;
+                Pubkey::create_program_address(&escrow.signer_seeds(&seed_bytes), &crate::ID).ok()
+            })
+            .unwrap();
+        batch.add_at(key, &escrow);
+
+        assert_error(batch.run(1).0, EscrowError::InvalidBump);
+    }
+
+    #[test]
+    fn decimals_must_match_escrow() {
+        set_clock(1_000, 1);
+        let mut batch = Batch::new();
+        let mut escrow = batch.escrow(1, 100, 50);
+        escrow.mint_a_decimals = 9;
+        batch.add(&escrow);
+
+        assert_error(batch.run(1).0, EscrowError::DecimalsMismatch);
+    }
+
+    #[test]
+    fn drained_vault_is_rejected() {
+        set_clock(1_000, 1);
+        let mut batch = Batch::new();
+        let escrow = batch.escrow(1, 100, 50);
+        batch.add(&escrow);
+        batch.groups[2] = fund_ata(&batch.groups[1].key, &batch.mint_a, 60).writable();
+
+        assert_error(batch.run(1).0, EscrowError::VaultBalanceMismatch);
+    }
+
+    #[test]
+    fn taker_must_cover_whole_batch() {
+        set_clock(1_000, 1);
+        let mut batch = Batch::new();
+        let first = batch.escrow(1, 100, 50);
+        let second = batch.escrow(2, 100, 50);
+        batch.add(&first);
+        batch.add(&second);
+        batch.taker_b = 99;
+
+        assert_error(batch.run(2).0, EscrowError::InsufficientFunds);
+    }
+
+    #[test]
+    fn same_escrow_twice_is_rejected() {
+        set_clock(1_000, 1);
+        let mut batch = Batch::new();
+        let escrow = batch.escrow(1, 100, 50);
+        batch.add(&escrow);
+        batch.add(&escrow);
+
+        assert_error(batch.run(2).0, EscrowError::InvalidAccountGroup);
+    }
+
+    #[test]
+    fn escrow_count_must_fit_remaining_accounts() {
+        set_clock(1_000, 1);
+        let mut batch = Batch::new();
+        let escrow = batch.escrow(1, 100, 50);
+        batch.add(&escrow);
+
+        assert_error(batch.run(2).0, EscrowError::InvalidAccountGroup);
+        assert_error(batch.run(0).0, EscrowError::InvalidAccountGroup);
+    }
+}
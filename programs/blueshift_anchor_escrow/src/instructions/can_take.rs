@@ -0,0 +1,154 @@
+use anchor_lang::prelude::*;
+
+use crate::state::{Config, Escrow};
+
+/// can_take 的结果（通过 return data 返回），按 take 的校验顺序给出第一个失败原因
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub enum TakeStatus {
+    /// 可以成交
+    Ok,
+    /// escrow 已全部成交或已撤回（EscrowNotActive）
+    NotActive,
+    /// 正在成交中（ReentrancyDetected）
+    InProgress,
+    /// 协议已暂停（ProgramPaused）
+    ProgramPaused,
+    /// 报价已过期（OfferExpired）
+    Expired,
+    /// taker 即 maker（MakerCannotTake）
+    MakerCannotTake,
+    /// OTC 报价且 taker 不是指定的 taker（UnauthorizedTaker）
+    UnauthorizedTaker,
+    /// 没有剩余可成交的 Token A（VaultEmpty）
+    NothingToFill,
+}
+
+/// CanTake 指令：
+///
+/// 只读指令，不修改任何账户。UI 通过 simulateTransaction 解码 return data，
+/// 判断 taker 能否成交以及不能成交的原因，无需在客户端复制 take 的校验逻辑。
+///
+/// 只检查与 taker 身份和 escrow 状态相关的条件；余额、滑点与账户冻结等
+/// 取决于具体交易参数的条件仍由 take 校验
+#[derive(Accounts)]
+pub struct CanTake<'info> {
+    /// Escrow 状态账户
+    pub escrow: Account<'info, Escrow>,
+
+    /// 全局配置（可选，传入时检查暂停状态）
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+    )]
+    pub config: Option<Account<'info, Config>>,
+}
+
+/// CanTake 指令入口
+///
+/// 参数说明：
+/// - taker: 准备成交的用户
+pub fn handler(ctx: Context<CanTake>, taker: Pubkey) -> Result<TakeStatus> {
+    let escrow = &ctx.accounts.escrow;
+    let now = Clock::get()?.unix_timestamp;
+
+    let paused = ctx
+        .accounts
+        .config
+        .as_ref()
+        .is_some_and(|config| config.paused);
+
+    let status = if !escrow.is_active() {
+        TakeStatus::NotActive
+    } else if escrow.in_progress {
+        TakeStatus::InProgress
+    } else if paused {
+        TakeStatus::ProgramPaused
+    } else if escrow.is_expired(now) {
+        TakeStatus::Expired
+    } else if taker == escrow.maker {
+        TakeStatus::MakerCannotTake
+    } else if !escrow.is_taker_allowed(&taker) {
+        TakeStatus::UnauthorizedTaker
+    } else if escrow.available() == 0 {
+        TakeStatus::NothingToFill
+    } else {
+        TakeStatus::Ok
+    };
+
+    Ok(status)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::EscrowStatus;
+    use crate::test_utils::*;
+
+    fn can_take(escrow: &Escrow, taker: Pubkey) -> TakeStatus {
+        can_take_with(escrow, taker, none())
+    }
+
+    fn can_take_with(escrow: &Escrow, taker: Pubkey, config: TestAccount) -> TakeStatus {
+        set_clock(1_000, 1);
+        let (mut accounts, bumps) =
+            load_accounts::<CanTake>(vec![escrow_account(escrow), config], &[]).unwrap();
+
+        handler(Context::new(&crate::ID, &mut accounts, &[], bumps), taker).unwrap()
+    }
+
+    fn escrow() -> Escrow {
+        let (mint_a, mint_b) = setup_mints(6, 9);
+
+        make_escrow(&Pubkey::new_unique(), 7, &mint_a, &mint_b, 100, 50)
+    }
+
+    #[test]
+    fn open_escrow_can_be_taken() {
+        assert!(can_take(&escrow(), Pubkey::new_unique()) == TakeStatus::Ok);
+    }
+
+    #[test]
+    fn maker_cannot_take_own_escrow() {
+        let escrow = escrow();
+
+        assert!(can_take(&escrow, escrow.maker) == TakeStatus::MakerCannotTake);
+    }
+
+    #[test]
+    fn reports_first_failing_check() {
+        let mut escrow = escrow();
+        escrow.expiry = 999;
+        assert!(can_take(&escrow, Pubkey::new_unique()) == TakeStatus::Expired);
+
+        escrow.expiry = 0;
+        escrow.allowed_taker = Pubkey::new_unique();
+        assert!(can_take(&escrow, Pubkey::new_unique()) == TakeStatus::UnauthorizedTaker);
+
+        escrow.allowed_taker = Pubkey::default();
+        escrow.reserved = escrow.deposit;
+        assert!(can_take(&escrow, Pubkey::new_unique()) == TakeStatus::NothingToFill);
+    }
+
+    #[test]
+    fn reports_escrow_and_program_state() {
+        let mut escrow = escrow();
+        let taker = Pubkey::new_unique();
+
+        escrow.status = EscrowStatus::Filled;
+        assert!(can_take(&escrow, taker) == TakeStatus::NotActive);
+
+        escrow.status = EscrowStatus::PartiallyFilled;
+        escrow.in_progress = true;
+        assert!(can_take(&escrow, taker) == TakeStatus::InProgress);
+
+        escrow.in_progress = false;
+        let paused = Config {
+            paused: true,
+            ..default_config()
+        };
+        let status = can_take_with(&escrow, taker, config_account(&paused));
+        assert!(status == TakeStatus::ProgramPaused);
+        let config = config_account(&default_config());
+        assert!(can_take_with(&escrow, taker, config) == TakeStatus::Ok);
+    }
+}
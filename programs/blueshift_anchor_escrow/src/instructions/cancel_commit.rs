@@ -0,0 +1,84 @@
+use anchor_lang::prelude::*;
+
+use crate::state::TakeCommit;
+
+/// CancelCommit 指令：
+///
+/// taker 放弃 commit_take 提交的承诺，关闭 TakeCommit（租金返还给 taker）。
+/// 不需要传入 escrow：escrow 已成交、撤回或关闭后，未 reveal 的承诺同样可以关闭
+#[derive(Accounts)]
+pub struct CancelCommit<'info> {
+    /// 提交承诺的 taker，接收返还的租金
+    #[account(mut)]
+    pub taker: Signer<'info>,
+
+    /// 要关闭的承诺 PDA（seeds 中的 escrow 取自账户数据）
+    #[account(
+        mut,
+        close = taker,
+        seeds = [b"commit", take_commit.escrow.as_ref(), taker.key().as_ref()],
+        bump = take_commit.bump,
+    )]
+    pub take_commit: Account<'info, TakeCommit>,
+}
+
+/// CancelCommit 指令入口（关闭由 close 约束完成）
+pub fn handler(_ctx: Context<CancelCommit>) -> Result<()> {
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::*;
+
+    const CANCEL_COMMIT: u8 = 44;
+
+    /// taker 对 escrow 提交的承诺 PDA
+    fn commit_account(escrow: &Pubkey, taker: &Pubkey) -> TestAccount {
+        let (key, bump) = Pubkey::find_program_address(
+            &[TakeCommit::SEED_PREFIX, escrow.as_ref(), taker.as_ref()],
+            &crate::ID,
+        );
+        let commit = TakeCommit {
+            escrow: *escrow,
+            taker: *taker,
+            commitment: [7; 32],
+            slot: 10,
+            bump,
+        };
+
+        program_account(key, &commit)
+    }
+
+    #[test]
+    fn taker_closes_commit_of_missing_escrow() {
+        set_clock(1_000, 12);
+        let taker = Pubkey::new_unique();
+        // escrow 已关闭：地址上没有任何账户，也无需传入
+        let commit = commit_account(&Pubkey::new_unique(), &taker);
+        let rent = commit.lamports;
+
+        let (result, infos) = run(
+            &[CANCEL_COMMIT],
+            vec![TestAccount::wallet(taker, 0).signer(), commit.writable()],
+        );
+
+        assert_eq!(result, Ok(()));
+        assert_eq!(infos[0].lamports(), rent);
+        assert_eq!(infos[1].lamports(), 0);
+    }
+
+    #[test]
+    fn only_committing_taker_can_cancel() {
+        set_clock(1_000, 12);
+        let commit = commit_account(&Pubkey::new_unique(), &Pubkey::new_unique());
+
+        let (result, _) = run(
+            &[CANCEL_COMMIT],
+            vec![TestAccount::wallet(Pubkey::new_unique(), 0).signer(), commit.writable()],
+        );
+
+        assert_error(result, ErrorCode::ConstraintSeeds);
+    }
+}
@@ -0,0 +1,89 @@
+use anchor_lang::prelude::*;
+
+use crate::state::EscrowStatus;
+use crate::errors::EscrowError;
+use crate::instructions::refund::{self, Refund};
+
+/// CancelRemainder 指令入口
+///
+/// 撤回已部分成交 escrow 的剩余部分：
+/// 账户与流程与 refund 相同（退回剩余 Token A、关闭 vault 与 escrow、发出 EscrowRefunded），
+/// 但要求 escrow 处于 PartiallyFilled，便于统计区分「从未成交的撤单」与「部分成交后的撤单」
+pub fn handler<'info>(ctx: Context<'_, '_, 'info, 'info, Refund<'info>>) -> Result<()> {
+    require!(
+        ctx.accounts.escrow.status == EscrowStatus::PartiallyFilled,
+        EscrowError::NotPartiallyFilled
+    );
+
+    refund::handler(ctx)
+}
+
+#[cfg(test)]
+mod tests {
+    use anchor_spl::associated_token;
+    use anchor_spl::token::spl_token;
+
+    use super::*;
+    use crate::state::Escrow;
+    use crate::test_utils::*;
+
+    const CANCEL_REMAINDER: u8 = 16;
+
+    fn accounts(escrow: &Escrow, mint_a: &TestAccount) -> Vec<TestAccount> {
+        let escrow_account = escrow_account(escrow);
+
+        vec![
+            TestAccount::wallet(escrow.maker, 1_000_000_000).signer(),
+            escrow_account.clone().writable(),
+            TestAccount::wallet(escrow.maker, 1_000_000_000).writable(),
+            no_config(),
+            none(),
+            TestAccount::wallet(Pubkey::new_unique(), 0).writable(),
+            mint_a.clone(),
+            fund_ata(&escrow_account.key, mint_a, escrow.deposit).writable(),
+            fund_ata(&escrow.maker, mint_a, 0).writable(),
+            TestAccount::program(associated_token::ID),
+            TestAccount::program(spl_token::ID),
+            TestAccount::program(anchor_lang::system_program::ID),
+        ]
+    }
+
+    /// make 100 Token A 换 50 Token B，之后被 take 了 40 个 Token A
+    fn partially_filled() -> (Escrow, TestAccount) {
+        let (mint_a, mint_b) = setup_mints(6, 6);
+        let mut escrow = make_escrow(&Pubkey::new_unique(), 3, &mint_a, &mint_b, 100, 50);
+        escrow.apply_fill(40, 20, false).unwrap();
+        escrow.status = EscrowStatus::PartiallyFilled;
+
+        (escrow, mint_a)
+    }
+
+    #[test]
+    fn requires_partially_filled_escrow() {
+        set_clock(1_000, 1);
+        let (mut escrow, mint_a) = partially_filled();
+        escrow.status = EscrowStatus::Active;
+
+        let (result, _) = run(&[CANCEL_REMAINDER], accounts(&escrow, &mint_a));
+
+        assert_error(result, EscrowError::NotPartiallyFilled);
+    }
+
+    #[test]
+    fn cancels_remainder_through_refund_flow() {
+        set_clock(1_000, 1);
+        let (mut escrow, mint_a) = partially_filled();
+        assert_eq!((escrow.deposit, escrow.receive), (60, 30));
+
+        // 与 refund 共用校验：持有期内不能撤回
+        escrow.min_hold_until = 2_000;
+        let (result, _) = run(&[CANCEL_REMAINDER], accounts(&escrow, &mint_a));
+        assert_error(result, EscrowError::RefundTooEarly);
+
+        // 持有期结束后退回剩余的 60 个 Token A（之后的转账需要 CPI，宿主机上无法执行）
+        escrow.min_hold_until = 0;
+        let (mut refund, _) =
+            load_accounts::<Refund>(accounts(&escrow, &mint_a), &[CANCEL_REMAINDER]).unwrap();
+        assert_eq!(refund.flow().prepare_refund().unwrap(), 60);
+    }
+}
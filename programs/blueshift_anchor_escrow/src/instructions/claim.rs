@@ -0,0 +1,115 @@
+use anchor_lang::prelude::*;
+
+use crate::state::Reservation;
+use crate::errors::EscrowError;
+use crate::instructions::take::*;
+
+/// Claim 指令：
+///
+/// 成交 reserve 锁定的份额：按普通 take 流程成交 reservation.amount 个 Token A
+/// （价格按成交时的条款计算），然后关闭 Reservation（租金返还给 taker）
+#[derive(Accounts)]
+pub struct Claim<'info> {
+    /// 与 take 完全相同的账户
+    pub take: Take<'info>,
+
+    /// reserve 创建的预留 PDA
+    #[account(
+        mut,
+        close = taker,
+        seeds = [b"reservation", take.escrow.key().as_ref(), take.taker.key().as_ref()],
+        bump = reservation.bump,
+    )]
+    pub reservation: Account<'info, Reservation>,
+
+    /// CHECK: 接收关闭 Reservation 返还的 lamports，必须是 take.taker
+    #[account(
+        mut,
+        address = take.taker.key(),
+    )]
+    pub taker: UncheckedAccount<'info>,
+}
+
+impl<'info> Claim<'info> {
+    /// 解除预留锁定并按当前条款报价，返回 (fill_amount, amount_b)
+    pub fn quote_claim(&mut self, max_receive: u64) -> Result<(u64, u64)> {
+        require!(
+            !self.reservation.is_expired(Clock::get()?.unix_timestamp),
+            EscrowError::ReservationExpired
+        );
+        let fill_amount = self.reservation.amount;
+
+        // 先解除锁定，使本次成交可以动用这部分存入
+        let take = &mut self.take;
+        take.escrow.reserved = take
+            .escrow
+            .reserved
+            .checked_sub(fill_amount)
+            .ok_or(error!(EscrowError::MathOverflow))?;
+
+        take.ensure_atas(false)?;
+
+        Ok((fill_amount, take.quote_fill(fill_amount, max_receive)?))
+    }
+}
+
+/// Claim 指令入口
+///
+/// 参数说明：
+/// - max_receive: 见 Take::quote_fill
+///
+/// transfer hook 所需账户通过 remaining_accounts 传入
+pub fn handler<'info>(
+    ctx: Context<'_, '_, 'info, 'info, Claim<'info>>,
+    max_receive: u64,
+) -> Result<()> {
+    let (fill_amount, amount_b) = ctx.accounts.quote_claim(max_receive)?;
+
+    ctx.accounts
+        .take
+        .execute_fill(fill_amount, amount_b, &[], ctx.remaining_accounts)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::*;
+
+    const CLAIM: u8 = 41;
+
+    /// taker 预留了 40 / 100 Token A 的 escrow 对应的 Claim 账户（按结构体字段顺序）
+    fn claim_accounts(taker: &Pubkey, expires_at: i64) -> Vec<TestAccount> {
+        let (mint_a, mint_b) = setup_mints(6, 6);
+        let mut escrow = make_escrow(&Pubkey::new_unique(), 1, &mint_a, &mint_b, 100, 50);
+        escrow.reserved = 40;
+        let mut accounts =
+            take_accounts(taker, &escrow, &default_config(), &mint_a, &mint_b, 1_000).into_vec();
+        accounts.push(reservation_account(&escrow, taker, 40, expires_at).writable());
+        accounts.push(TestAccount::wallet(*taker, 0).writable());
+
+        accounts
+    }
+
+    #[test]
+    fn claim_fills_the_reserved_amount() {
+        set_clock(1_000, 1);
+        let taker = Pubkey::new_unique();
+        let data = ix_data(CLAIM, u64::MAX);
+        let (mut claim, _) = load_accounts::<Claim>(claim_accounts(&taker, 1_500), &data).unwrap();
+
+        // 成交数量取自预留，锁定在成交前解除
+        assert_eq!(claim.quote_claim(20).unwrap(), (40, 20));
+        assert_eq!(claim.take.escrow.reserved, 0);
+        assert_eq!(claim.take.escrow.available(), 100);
+    }
+
+    #[test]
+    fn expired_reservation_cannot_be_claimed() {
+        set_clock(1_500, 1);
+        let taker = Pubkey::new_unique();
+
+        let result = run(&ix_data(CLAIM, u64::MAX), claim_accounts(&taker, 1_500)).0;
+
+        assert_error(result, EscrowError::ReservationExpired);
+    }
+}
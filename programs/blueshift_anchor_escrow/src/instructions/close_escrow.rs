@@ -0,0 +1,247 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::get_associated_token_address_with_program_id,
+    token_interface::{Mint, TokenAccount, TokenInterface},
+};
+
+use crate::instructions::make::load_config;
+use crate::state::{Escrow, EscrowStatus, MakerStats};
+use crate::errors::EscrowError;
+
+/// CloseEscrow 指令：
+///
+/// vault 已被清空或关闭（例如外部操作）时，refund 无法完成转账与关闭 vault，
+/// maker 可通过本指令仅关闭 escrow PDA 取回租金，不触碰 vault
+///
+/// vault 地址由 mint_a 所属的 token program 推导，且已存在的 vault 必须归该 token program 所有，
+/// 防止传入另一个 token program 推导出空地址，绕过仍有余额的 vault 后再 sweep_stranded 取走
+#[derive(Accounts)]
+pub struct CloseEscrow<'info> {
+    /// Maker: 创建 escrow 的人
+    #[account(mut)]
+    pub maker: Signer<'info>,
+
+    /// Escrow PDA
+    /// close = rent_collector 表示关闭后 lamports 返还给 rent_collector（默认 maker）
+    #[account(
+        mut,
+        close = rent_collector,
+        seeds = [b"escrow", escrow.creator.as_ref(), escrow.seed.to_le_bytes().as_ref()],
+        bump = escrow.bump,
+        has_one = maker @ EscrowError::InvalidMaker,
+        has_one = mint_a @ EscrowError::InvalidMintA,
+        constraint = !escrow.is_basket() @ EscrowError::BasketMismatch,
+    )]
+    pub escrow: Box<Account<'info, Escrow>>,
+
+    /// CHECK: 仅接收关闭 vault / escrow 返还的 lamports，
+    /// 地址为 escrow.rent_collector（未设置时为 maker）
+    #[account(
+        mut,
+        address = escrow.rent_destination(escrow.maker),
+    )]
+    pub rent_collector: UncheckedAccount<'info>,
+
+    /// CHECK: 全局 Config PDA（与 make 相同，未初始化时为空账户），仅读取挂单数上限
+    #[account(
+        seeds = [b"config"],
+        bump,
+    )]
+    pub config: UncheckedAccount<'info>,
+
+    /// maker 统计 PDA（可选，传入时扣减未关闭的 escrow 数量；config 限制挂单数时必须传入）
+    #[account(
+        mut,
+        seeds = [b"stats", maker.key().as_ref()],
+        bump = maker_stats.bump,
+    )]
+    pub maker_stats: Option<Box<Account<'info, MakerStats>>>,
+
+    /// Token A（其所属的 token program 决定 vault 地址）
+    #[account(mint::token_program = token_program)]
+    pub mint_a: Box<InterfaceAccount<'info, Mint>>,
+
+    /// CHECK: escrow 的 vault 地址（可能已不存在），仅读取余额，
+    /// 地址由 ATA 推导约束，所有者在 vault_balance 中校验
+    #[account(
+        address = get_associated_token_address_with_program_id(
+            &escrow.key(),
+            &escrow.mint_a,
+            &token_program.key(),
+        ),
+    )]
+    pub vault: UncheckedAccount<'info>,
+
+    /// Token Program（mint_a 所属的 token program，用于推导 vault 地址）
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+impl<'info> CloseEscrow<'info> {
+    /// vault 中剩余的 Token A 数量（账户不存在时视为 0）
+    ///
+    /// 地址与 token program 已由账户约束校验；账户存在时必须归 token_program 所有
+    fn vault_balance(&self) -> Result<u64> {
+        if self.vault.data_is_empty() && self.vault.owner == &System::id() {
+            return Ok(0);
+        }
+        require_keys_eq!(
+            *self.vault.owner,
+            self.token_program.key(),
+            ErrorCode::AccountOwnedByWrongProgram
+        );
+
+        let data = self.vault.try_borrow_data()?;
+        let vault = TokenAccount::try_deserialize(&mut &data[..])?;
+
+        Ok(vault.amount)
+    }
+}
+
+/// CloseEscrow 指令入口
+pub fn handler(ctx: Context<CloseEscrow>) -> Result<()> {
+    // vault 仍有 Token A 时必须走 refund，避免资金被锁死
+    require_eq!(
+        ctx.accounts.vault_balance()?,
+        0,
+        EscrowError::VaultNotEmpty
+    );
+
+    let tracks_open = load_config(&ctx.accounts.config)?
+        .is_some_and(|config| config.tracks_open_escrows());
+    require!(
+        ctx.accounts.maker_stats.is_some() || !tracks_open,
+        EscrowError::MakerStatsRequired
+    );
+    if let Some(stats) = ctx.accounts.maker_stats.as_mut() {
+        stats.record_closed();
+    }
+
+    // escrow 会因 close = rent_collector 在指令结束时关闭
+    ctx.accounts.escrow.status = EscrowStatus::Cancelled;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use anchor_spl::token::spl_token;
+    use anchor_spl::token_2022::spl_token_2022;
+
+    use super::*;
+    use crate::state::Config;
+    use crate::test_utils::*;
+
+    const CLOSE_ESCROW: u8 = 13;
+
+    fn setup() -> (Escrow, TestAccount, Pubkey) {
+        let (mint_a, mint_b) = setup_mints(6, 6);
+        let mut escrow = make_escrow(&Pubkey::new_unique(), 1, &mint_a, &mint_b, 100, 50);
+        escrow.rent_collector = Pubkey::new_unique();
+        let key = escrow_account(&escrow).key;
+
+        (escrow, mint_a, key)
+    }
+
+    fn accounts(
+        escrow: &Escrow,
+        config: TestAccount,
+        mint_a: &TestAccount,
+        vault: TestAccount,
+        token_program: Pubkey,
+    ) -> Vec<TestAccount> {
+        vec![
+            TestAccount::wallet(escrow.maker, 1_000_000_000).signer(),
+            escrow_account(escrow).writable(),
+            TestAccount::wallet(escrow.rent_collector, 0).writable(),
+            config,
+            none(),
+            mint_a.clone(),
+            vault,
+            TestAccount::program(token_program),
+        ]
+    }
+
+    fn missing_vault(
+        escrow_key: &Pubkey,
+        mint_a: &TestAccount,
+        token_program: &Pubkey,
+    ) -> TestAccount {
+        let key =
+            get_associated_token_address_with_program_id(escrow_key, &mint_a.key, token_program);
+
+        TestAccount::wallet(key, 0)
+    }
+
+    #[test]
+    fn closes_escrow_when_vault_is_gone() {
+        let (escrow, mint_a, key) = setup();
+        let vault = missing_vault(&key, &mint_a, &spl_token::ID);
+
+        let (result, infos) = run(
+            &[CLOSE_ESCROW],
+            accounts(&escrow, no_config(), &mint_a, vault, spl_token::ID),
+        );
+
+        assert_eq!(result, Ok(()));
+        assert_eq!(infos[1].lamports(), 0);
+        assert!(infos[2].lamports() > 0);
+    }
+
+    #[test]
+    fn funded_vault_blocks_close() {
+        let (escrow, mint_a, key) = setup();
+        let vault = fund_ata(&key, &mint_a, 5);
+
+        let (result, _) = run(
+            &[CLOSE_ESCROW],
+            accounts(&escrow, no_config(), &mint_a, vault, spl_token::ID),
+        );
+
+        assert_error(result, EscrowError::VaultNotEmpty);
+    }
+
+    #[test]
+    fn other_token_program_cannot_bypass_funded_vault() {
+        // 真实 vault（SPL Token）仍有余额，改传 Token-2022 推导出的空地址
+        let (escrow, mint_a, key) = setup();
+        let vault = missing_vault(&key, &mint_a, &spl_token_2022::ID);
+
+        let (result, _) = run(
+            &[CLOSE_ESCROW],
+            accounts(&escrow, no_config(), &mint_a, vault, spl_token_2022::ID),
+        );
+
+        assert_error(result, ErrorCode::ConstraintMintTokenProgram);
+    }
+
+    #[test]
+    fn vault_must_be_owned_by_token_program() {
+        let (escrow, mint_a, key) = setup();
+        let mut vault = fund_ata(&key, &mint_a, 0);
+        vault.owner = Pubkey::new_unique();
+
+        let (result, _) = run(
+            &[CLOSE_ESCROW],
+            accounts(&escrow, no_config(), &mint_a, vault, spl_token::ID),
+        );
+
+        assert_error(result, ErrorCode::AccountOwnedByWrongProgram);
+    }
+
+    #[test]
+    fn maker_stats_required_when_open_escrows_are_tracked() {
+        let (escrow, mint_a, key) = setup();
+        let vault = missing_vault(&key, &mint_a, &spl_token::ID);
+        let config = Config {
+            max_open_per_maker: 5,
+            ..default_config()
+        };
+
+        let (result, _) = run(
+            &[CLOSE_ESCROW],
+            accounts(&escrow, config_account(&config), &mint_a, vault, spl_token::ID),
+        );
+
+        assert_error(result, EscrowError::MakerStatsRequired);
+    }
+}
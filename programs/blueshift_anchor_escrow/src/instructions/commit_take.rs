@@ -0,0 +1,59 @@
+use anchor_lang::prelude::*;
+
+use crate::state::{Escrow, TakeCommit};
+use crate::errors::EscrowError;
+
+/// CommitTake 指令：
+///
+/// 两步成交的第一步：taker 先提交
+/// commitment = sha256(taker || escrow || fill_amount || max_receive || nonce)（nonce 为 32 字节随机数），
+/// 至少 TakeCommit::MIN_REVEAL_SLOT_GAP 个 slot 之后再通过 reveal_take 公开 nonce 并按提交的参数成交。
+/// 提交时不暴露成交数量，搜索者无法据此抢跑；reveal 时也无法更换 escrow 或成交参数
+///
+/// 放弃成交（或 escrow 已关闭）时 taker 可通过 cancel_commit 关闭承诺、取回租金
+#[derive(Accounts)]
+pub struct CommitTake<'info> {
+    /// 准备成交的 taker，支付 TakeCommit 账户租金
+    #[account(mut)]
+    pub taker: Signer<'info>,
+
+    /// Escrow 状态账户
+    #[account(
+        constraint = escrow.is_active() @ EscrowError::EscrowNotActive,
+    )]
+    pub escrow: Box<Account<'info, Escrow>>,
+
+    /// take 承诺 PDA
+    ///
+    /// PDA seeds:
+    /// - "commit"
+    /// - escrow 公钥
+    /// - taker 公钥
+    #[account(
+        init,
+        payer = taker,
+        space = TakeCommit::INIT_SPACE + TakeCommit::DISCRIMINATOR.len(),
+        seeds = [b"commit", escrow.key().as_ref(), taker.key().as_ref()],
+        bump,
+    )]
+    pub take_commit: Account<'info, TakeCommit>,
+
+    /// System Program（创建 TakeCommit PDA）
+    pub system_program: Program<'info, System>,
+}
+
+/// CommitTake 指令入口
+///
+/// 参数说明：
+/// - commitment: sha256(taker || escrow || fill_amount || max_receive || nonce)，见 TakeCommit::commitment_for
+pub fn handler(ctx: Context<CommitTake>, commitment: [u8; 32]) -> Result<()> {
+    ctx.accounts.take_commit.set_inner(TakeCommit {
+        escrow: ctx.accounts.escrow.key(),
+        taker: ctx.accounts.taker.key(),
+        commitment,
+        slot: Clock::get()?.slot,
+        bump: ctx.bumps.take_commit,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,347 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::{get_associated_token_address_with_program_id, AssociatedToken},
+    token_interface::{
+        close_account,
+        CloseAccount,
+        Mint,
+        TokenAccount,
+        TokenInterface,
+        TransferChecked,
+    },
+};
+
+use crate::state::{transfer_lamports_rent_safe, Escrow, EscrowStatus, MakerStats};
+use crate::errors::EscrowError;
+use crate::events::EscrowRefunded;
+use crate::instructions::make::load_config;
+use crate::instructions::take::transfer_checked_with_hook;
+
+/// CrankRefund 指令：
+///
+/// 任何人都可以对已过期的 escrow 发起退款：
+/// - Token A 退回 maker 的 ATA（不存在时由 cranker 付费创建）
+/// - vault 与 escrow 的租金返还给 rent_collector（默认 maker）；
+///   config.cranker_bounty_lamports > 0 时从 vault 租金中先支付给 cranker 作为奖励
+/// - 不收取撤单费
+#[derive(Accounts)]
+pub struct CrankRefund<'info> {
+    /// 发起退款的任意用户
+    #[account(mut)]
+    pub cranker: Signer<'info>,
+
+    /// 创建 escrow 的用户（接收退回的 Token A）
+    #[account(mut)]
+    pub maker: SystemAccount<'info>,
+
+    /// Escrow PDA
+    /// close = rent_collector 表示关闭后 lamports 返还给 rent_collector（默认 maker）
+    #[account(
+        mut,
+        close = rent_collector,
+        seeds = [b"escrow", escrow.creator.as_ref(), escrow.seed.to_le_bytes().as_ref()],
+        bump = escrow.bump,
+        constraint = escrow.has_canonical_bump() @ EscrowError::InvalidBump,
+        has_one = maker @ EscrowError::InvalidMaker,
+        has_one = mint_a @ EscrowError::InvalidMintA,
+        constraint = !escrow.is_basket() @ EscrowError::BasketMismatch,
+        constraint = escrow.is_active() @ EscrowError::EscrowNotActive,
+        constraint = !escrow.in_progress @ EscrowError::ReentrancyDetected,
+    )]
+    pub escrow: Box<Account<'info, Escrow>>,
+
+    /// CHECK: 仅接收关闭 vault / escrow 返还的 lamports，
+    /// 地址为 escrow.rent_collector（未设置时为 maker）
+    #[account(
+        mut,
+        address = escrow.rent_destination(escrow.maker),
+    )]
+    pub rent_collector: UncheckedAccount<'info>,
+
+    /// CHECK: 全局 Config PDA（与 make 相同，未初始化时为空账户），仅读取挂单数上限与 cranker 奖励
+    #[account(
+        seeds = [b"config"],
+        bump,
+    )]
+    pub config: UncheckedAccount<'info>,
+
+    /// maker 统计 PDA（可选，传入时累加 total_refunded；config 限制挂单数时必须传入）
+    #[account(
+        mut,
+        seeds = [b"stats", maker.key().as_ref()],
+        bump = maker_stats.bump,
+    )]
+    pub maker_stats: Option<Box<Account<'info, MakerStats>>>,
+
+    /// Token A 的 mint
+    #[account(
+        mint::token_program = token_program,
+        constraint = mint_a.decimals == escrow.mint_a_decimals @ EscrowError::DecimalsMismatch,
+    )]
+    pub mint_a: Box<InterfaceAccount<'info, Mint>>,
+
+    /// Vault：escrow PDA 持有的 Token A
+    #[account(
+        mut,
+        associated_token::mint = mint_a,
+        associated_token::authority = escrow,
+        associated_token::token_program = token_program
+    )]
+    pub vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Maker 的 Token A ATA（接收退款）
+    #[account(
+        init_if_needed,
+        payer = cranker,
+        associated_token::mint = mint_a,
+        associated_token::authority = maker,
+        associated_token::token_program = token_program,
+        address = get_associated_token_address_with_program_id(
+            &maker.key(),
+            &mint_a.key(),
+            &token_program.key(),
+        ) @ EscrowError::NonCanonicalAta,
+    )]
+    pub maker_ata_a: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Programs
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> CrankRefund<'info> {
+    /// 从 Vault 中把所有 Token A 退回 maker，并关闭 Vault
+    ///
+    /// bounty > 0 时 vault 租金先进入 cranker，再由 cranker 把 rest（bounty 之外的部分）转给 rent_collector
+    fn refund_and_close_vault(
+        &mut self,
+        (bounty, rest): (u64, u64),
+        remaining: &[AccountInfo<'info>],
+    ) -> Result<()> {
+        let seed_bytes = self.escrow.seed.to_le_bytes();
+        let seeds = self.escrow.signer_seeds(&seed_bytes);
+        let signer_seeds: [&[&[u8]]; 1] = [&seeds];
+
+        transfer_checked_with_hook(
+            CpiContext::new_with_signer(
+                self.token_program.to_account_info(),
+                TransferChecked {
+                    from: self.vault.to_account_info(),
+                    to: self.maker_ata_a.to_account_info(),
+                    mint: self.mint_a.to_account_info(),
+                    authority: self.escrow.to_account_info(),
+                },
+                &signer_seeds,
+            )
+            .with_remaining_accounts(remaining.to_vec()),
+            self.vault.amount,
+            self.mint_a.decimals,
+        )?;
+
+        let destination = if bounty > 0 {
+            self.cranker.to_account_info()
+        } else {
+            self.rent_collector.to_account_info()
+        };
+
+        close_account(CpiContext::new_with_signer(
+            self.token_program.to_account_info(),
+            CloseAccount {
+                account: self.vault.to_account_info(),
+                authority: self.escrow.to_account_info(),
+                destination,
+            },
+            &signer_seeds,
+        ))?;
+
+        if bounty > 0 && rest > 0 {
+            transfer_lamports_rent_safe(
+                &self.system_program,
+                self.cranker.to_account_info(),
+                self.rent_collector.to_account_info(),
+                rest,
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+/// vault 租金 vault_rent 的分配：(cranker 所得 bounty, rent_collector 所得的其余部分)
+pub fn split_vault_rent(vault_rent: u64, bounty: u64) -> Result<(u64, u64)> {
+    require!(bounty <= vault_rent, EscrowError::BountyTooHigh);
+
+    Ok((bounty, vault_rent - bounty))
+}
+
+/// CrankRefund 指令入口
+///
+/// transfer hook 所需账户通过 remaining_accounts 传入
+pub fn handler<'info>(ctx: Context<'_, '_, 'info, 'info, CrankRefund<'info>>) -> Result<()> {
+    // 只有设置了 expiry 且已过期的报价才能被他人撤回
+    let now = Clock::get()?.unix_timestamp;
+    require!(
+        ctx.accounts.escrow.is_expired(now),
+        EscrowError::OfferNotExpired
+    );
+
+    // 过期的预留需先通过 release_reservation 释放
+    require!(
+        ctx.accounts.escrow.reserved == 0,
+        EscrowError::InsufficientAvailable
+    );
+
+    // 承诺持有期对 crank 同样有效
+    require!(
+        !ctx.accounts.escrow.is_held(now),
+        EscrowError::RefundTooEarly
+    );
+
+    require!(
+        !ctx.accounts.vault.is_frozen() && !ctx.accounts.maker_ata_a.is_frozen(),
+        EscrowError::AccountFrozen
+    );
+
+    let amount_refunded = ctx.accounts.vault.amount;
+
+    let config = load_config(&ctx.accounts.config)?;
+    let bounty = config
+        .as_ref()
+        .map_or(0, |config| config.cranker_bounty_lamports);
+
+    // 转出 Token A 不改变 vault 的 lamports，在 CPI 之前即可确定租金分配
+    let vault_rent = ctx.accounts.vault.to_account_info().lamports();
+    let split = split_vault_rent(vault_rent, bounty)?;
+
+    ctx.accounts.refund_and_close_vault(split, ctx.remaining_accounts)?;
+
+    let tracks_open = config.is_some_and(|config| config.tracks_open_escrows());
+    require!(
+        ctx.accounts.maker_stats.is_some() || !tracks_open,
+        EscrowError::MakerStatsRequired
+    );
+    if let Some(stats) = ctx.accounts.maker_stats.as_mut() {
+        stats.record_refunded()?;
+    }
+
+    // escrow 会因 close = rent_collector 在指令结束时关闭
+    ctx.accounts.escrow.status = EscrowStatus::Cancelled;
+
+    emit!(EscrowRefunded {
+        escrow: ctx.accounts.escrow.key(),
+        maker: ctx.accounts.maker.key(),
+        amount_refunded,
+    });
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use anchor_spl::associated_token;
+    use anchor_spl::token::spl_token;
+
+    use super::*;
+    use crate::state::Config;
+    use crate::test_utils::*;
+
+    const CRANK_REFUND: u8 = 21;
+
+    /// 由随机的第三方 cranker 发起 crank_refund（config 未初始化）
+    fn crank(escrow: &Escrow, mint_a: &TestAccount, rent_collector: Pubkey) -> ProgramResult {
+        crank_with(escrow, mint_a, rent_collector, no_config())
+    }
+
+    fn crank_with(
+        escrow: &Escrow,
+        mint_a: &TestAccount,
+        rent_collector: Pubkey,
+        config: TestAccount,
+    ) -> ProgramResult {
+        let escrow_account = escrow_account(escrow);
+
+        let (result, _) = run(
+            &[CRANK_REFUND],
+            vec![
+                TestAccount::wallet(Pubkey::new_unique(), 1_000_000_000).signer(),
+                TestAccount::wallet(escrow.maker, 0).writable(),
+                escrow_account.clone().writable(),
+                TestAccount::wallet(rent_collector, 0).writable(),
+                config,
+                none(),
+                mint_a.clone(),
+                fund_ata(&escrow_account.key, mint_a, escrow.deposit).writable(),
+                fund_ata(&escrow.maker, mint_a, 0).writable(),
+                TestAccount::program(associated_token::ID),
+                TestAccount::program(spl_token::ID),
+                TestAccount::program(anchor_lang::system_program::ID),
+            ],
+        );
+
+        result
+    }
+
+    fn expiring(expiry: i64) -> (Escrow, TestAccount) {
+        let (mint_a, mint_b) = setup_mints(6, 6);
+        let mut escrow = make_escrow(&Pubkey::new_unique(), 7, &mint_a, &mint_b, 100, 50);
+        escrow.expiry = expiry;
+
+        (escrow, mint_a)
+    }
+
+    #[test]
+    fn offer_must_be_expired() {
+        set_clock(1_000, 1);
+
+        let (escrow, mint_a) = expiring(1_000);
+        assert_error(crank(&escrow, &mint_a, escrow.maker), EscrowError::OfferNotExpired);
+
+        // 永不过期的报价不能被他人撤回
+        let (escrow, mint_a) = expiring(0);
+        assert_error(crank(&escrow, &mint_a, escrow.maker), EscrowError::OfferNotExpired);
+    }
+
+    #[test]
+    fn third_party_cranks_expired_offer() {
+        set_clock(1_000, 1);
+        let (mut escrow, mint_a) = expiring(999);
+
+        // 租金只能返还给 maker，不能给 cranker
+        assert_error(
+            crank(&escrow, &mint_a, Pubkey::new_unique()),
+            ErrorCode::ConstraintAddress,
+        );
+
+        // 第三方通过过期校验，之后与 refund 相同仍受持有期约束
+        escrow.min_hold_until = 2_000;
+        assert_error(crank(&escrow, &mint_a, escrow.maker), EscrowError::RefundTooEarly);
+    }
+
+    #[test]
+    fn cranker_receives_bounty_from_vault_rent() {
+        let vault_rent = Rent::default().minimum_balance(165);
+
+        // cranker 恰好分得 bounty，其余租金归 rent_collector
+        let (bounty, rest) = split_vault_rent(vault_rent, 5_000).unwrap();
+        assert_eq!((bounty, rest), (5_000, vault_rent - 5_000));
+        assert_eq!(split_vault_rent(vault_rent, 0).unwrap(), (0, vault_rent));
+        assert_eq!(split_vault_rent(vault_rent, vault_rent).unwrap(), (vault_rent, 0));
+        assert_err(split_vault_rent(vault_rent, vault_rent + 1), EscrowError::BountyTooHigh);
+    }
+
+    #[test]
+    fn bounty_above_vault_rent_is_rejected() {
+        set_clock(1_000, 1);
+        let (escrow, mint_a) = expiring(999);
+        let config = Config {
+            cranker_bounty_lamports: Rent::default().minimum_balance(165) + 1,
+            ..default_config()
+        };
+
+        assert_error(
+            crank_with(&escrow, &mint_a, escrow.maker, config_account(&config)),
+            EscrowError::BountyTooHigh,
+        );
+    }
+}
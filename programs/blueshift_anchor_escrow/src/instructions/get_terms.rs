@@ -0,0 +1,77 @@
+use anchor_lang::prelude::*;
+
+use crate::state::{Escrow, EscrowStatus};
+
+/// 当前交易条款（通过 return data 返回）
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct EscrowTerms {
+    /// 剩余存入的 Token A 数量
+    pub deposit: u64,
+    /// 剩余存入对应的 Token B 总价（荷兰拍已按当前时间插值）
+    pub receive: u64,
+    /// 报价过期时间（0 表示永不过期）
+    pub expiry: i64,
+    /// 生命周期状态
+    pub status: EscrowStatus,
+}
+
+/// GetTerms 指令：
+///
+/// 只读指令，不修改任何账户。客户端通过 simulateTransaction 解码 return data，
+/// 无需依赖 Escrow 的链上布局
+#[derive(Accounts)]
+pub struct GetTerms<'info> {
+    /// Escrow 状态账户
+    pub escrow: Account<'info, Escrow>,
+}
+
+/// GetTerms 指令入口
+pub fn handler(ctx: Context<GetTerms>) -> Result<EscrowTerms> {
+    let escrow = &ctx.accounts.escrow;
+    let now = Clock::get()?.unix_timestamp;
+
+    Ok(EscrowTerms {
+        deposit: escrow.deposit,
+        receive: escrow.current_receive(now)?,
+        expiry: escrow.expiry,
+        status: escrow.status,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::*;
+
+    /// 调用 handler 并按 Anchor 写入 return data 的方式序列化后再解码
+    /// （宿主机上 set_return_data 不保存数据）
+    fn get_terms(escrow: &Escrow) -> EscrowTerms {
+        let (mut accounts, bumps) =
+            load_accounts::<GetTerms>(vec![escrow_account(escrow)], &[]).unwrap();
+        let terms = handler(Context::new(&crate::ID, &mut accounts, &[], bumps)).unwrap();
+        let return_data = terms.try_to_vec().unwrap();
+
+        EscrowTerms::try_from_slice(&return_data).unwrap()
+    }
+
+    #[test]
+    fn returns_current_terms() {
+        set_clock(1_500, 1);
+        let (mint_a, mint_b) = setup_mints(6, 6);
+        let mut escrow = make_escrow(&Pubkey::new_unique(), 9, &mint_a, &mint_b, 100, 50);
+        escrow.expiry = 3_000;
+        escrow.status = EscrowStatus::PartiallyFilled;
+
+        let terms = get_terms(&escrow);
+        assert_eq!((terms.deposit, terms.receive, terms.expiry), (100, 50, 3_000));
+        assert!(terms.status == EscrowStatus::PartiallyFilled);
+
+        // 荷兰拍返回按当前时间插值后的总价
+        escrow.is_auction = true;
+        escrow.start_receive = 80;
+        escrow.end_receive = 40;
+        escrow.auction_start = 1_000;
+        escrow.auction_end = 2_000;
+        assert_eq!(get_terms(&escrow).receive, 60);
+    }
+}
@@ -0,0 +1,107 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::bpf_loader_upgradeable;
+
+use crate::state::Config;
+use crate::errors::EscrowError;
+
+/// InitializeConfig 指令参数
+///
+/// 字段顺序即 Borsh 编码顺序，新增字段只能追加在末尾
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct InitializeConfigArgs {
+    /// 协议费率（基点，上限 Config::MAX_FEE_BPS）
+    pub fee_bps: u16,
+    /// 接收协议费的钱包
+    pub treasury: Pubkey,
+    /// refund 时 maker 支付给 treasury 的撤单费（0 表示免费）
+    pub cancel_fee_lamports: u64,
+    /// make 时最少存入的 Token A 数量（0 表示不限制）
+    pub min_deposit: u64,
+    /// make 时最低的 Token B 报价（0 表示不限制）
+    pub min_receive: u64,
+    /// 协议费中分给推荐人的费率（基点，不超过 fee_bps）
+    pub referral_bps: u16,
+    /// 为 true 时 make 只接受 PairRegistry 中登记的交易对
+    pub restrict_pairs: bool,
+    /// make 时 expiry 距当前时间的最大秒数（0 表示不限制）
+    pub max_ttl_seconds: i64,
+    /// 每个 maker 同时未关闭的 escrow 数量上限（0 表示不限制）
+    pub max_open_per_maker: u64,
+    /// escrow 每挂单一天协议费率减少的基点（0 表示无折扣）
+    pub discount_bps_per_day: u16,
+    /// 挂单时长折扣的上限（基点）
+    pub max_discount_bps: u16,
+    /// crank_refund 时从 vault 租金中奖励给 cranker 的 lamports（0 表示无奖励）
+    pub cranker_bounty_lamports: u64,
+    /// 为 true 时 take 必须附带非空附言（合规审计）
+    pub require_take_memo: bool,
+}
+
+/// InitializeConfig 指令：
+///
+/// 创建全局 Config PDA，设置协议费率、treasury 与挂单规则。
+/// 只有本程序的升级权限持有者可以调用，防止部署后被他人抢先初始化成为管理员
+#[derive(Accounts)]
+pub struct InitializeConfig<'info> {
+    /// 配置管理员（必须是本程序的 upgrade authority），同时支付 Config 账户租金
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    /// 本程序的 ProgramData 账户（BPF Upgradeable Loader 按程序 ID 推导），
+    /// 在创建 Config 之前校验 upgrade_authority_address
+    #[account(
+        seeds = [crate::ID.as_ref()],
+        bump,
+        seeds::program = bpf_loader_upgradeable::ID,
+        constraint = program_data.upgrade_authority_address == Some(authority.key())
+            @ EscrowError::Unauthorized,
+    )]
+    pub program_data: Account<'info, ProgramData>,
+
+    /// 全局 Config PDA
+    ///
+    /// PDA seeds:
+    /// - "config"
+    #[account(
+        init,
+        payer = authority,
+        space = Config::INIT_SPACE + Config::DISCRIMINATOR.len(),
+        seeds = [b"config"],
+        bump,
+    )]
+    pub config: Account<'info, Config>,
+
+    /// System Program（创建 Config PDA）
+    pub system_program: Program<'info, System>,
+}
+
+/// InitializeConfig 指令入口
+///
+/// 参数说明见 InitializeConfigArgs
+pub fn handler(ctx: Context<InitializeConfig>, args: InitializeConfigArgs) -> Result<()> {
+    require!(args.fee_bps <= Config::MAX_FEE_BPS, EscrowError::FeeTooHigh);
+    require!(args.referral_bps <= args.fee_bps, EscrowError::FeeTooHigh);
+    require!(args.max_ttl_seconds >= 0, EscrowError::InvalidAmount);
+
+    ctx.accounts.config.set_inner(Config {
+        authority: ctx.accounts.authority.key(),
+        fee_bps: args.fee_bps,
+        treasury: args.treasury,
+        paused: false,
+        cancel_fee_lamports: args.cancel_fee_lamports,
+        min_deposit: args.min_deposit,
+        min_receive: args.min_receive,
+        referral_bps: args.referral_bps,
+        restrict_pairs: args.restrict_pairs,
+        max_ttl_seconds: args.max_ttl_seconds,
+        max_open_per_maker: args.max_open_per_maker,
+        discount_bps_per_day: args.discount_bps_per_day,
+        max_discount_bps: args.max_discount_bps,
+        cranker_bounty_lamports: args.cranker_bounty_lamports,
+        require_take_memo: args.require_take_memo,
+        bump: ctx.bumps.config,
+    });
+
+    Ok(())
+}
+
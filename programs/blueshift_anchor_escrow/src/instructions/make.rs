@@ -2,6 +2,11 @@ use anchor_lang::prelude::*;
 use anchor_spl::{
     associated_token::AssociatedToken,
     token_interface::{
+        spl_token_2022::extension::{
+            non_transferable::NonTransferable,
+            permanent_delegate::PermanentDelegate,
+        },
+        get_mint_extension_data,
         Mint,
         TokenAccount,
         TokenInterface,
@@ -10,16 +15,315 @@ use anchor_spl::{
     },
 };
 
-use crate::state::Escrow;
+use crate::state::{Config, Escrow, EscrowStatus, MakerStats, PairRegistry};
 use crate::errors::EscrowError;
+use crate::events::EscrowCreated;
+
+/// Make 指令参数
+///
+/// 字段顺序即 Borsh 编码顺序，新增字段只能追加在末尾
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct MakeArgs {
+    /// 用于区分不同 escrow 的随机数（PDA seed）
+    pub seed: u64,
+    /// maker 希望收到的 Token B 数量
+    pub receive: u64,
+    /// maker 存入的 Token A 数量
+    pub amount: u64,
+    /// 报价过期时间（unix 时间戳，0 表示永不过期）
+    pub expiry: i64,
+    /// 承诺持有期截止时间，在此之前不能 refund（0 表示不限制）
+    pub min_hold_until: i64,
+    /// 指定唯一可成交的 taker（Pubkey::default() 表示任何人都可成交）
+    pub allowed_taker: Pubkey,
+    /// 是否为荷兰拍（价格随时间线性下降）；为 false 时以下字段被忽略
+    pub is_auction: bool,
+    /// 荷兰拍起始总价（Token B）
+    pub start_receive: u64,
+    /// 荷兰拍最低总价（Token B）
+    pub end_receive: u64,
+    /// 荷兰拍开始时间（unix 时间戳）
+    pub auction_start: i64,
+    /// 荷兰拍结束时间（unix 时间戳），之后价格保持 end_receive
+    pub auction_end: i64,
+    /// 单次成交的最小 Token A 数量（吃掉全部剩余时不受限制）
+    pub min_fill: u64,
+    /// 单次成交的最大 Token A 数量（0 表示不限制）
+    pub max_fill: u64,
+    /// 备选 Token B mint，taker 可任选其一支付（Pubkey::default() 表示不启用）
+    pub mint_b_alt: Pubkey,
+    /// 以备选 mint 支付时 maker 希望收到的数量（固定价，不参与荷兰拍）
+    pub receive_alt: u64,
+    /// 关闭账户时租金的接收方（例如代付租金的运营方，Pubkey::default() 表示返还给 maker）
+    pub rent_collector: Pubkey,
+    /// 附言（例如 "OTC deal ref #123"），定长 32 字节，不足部分以 0 填充；
+    /// 仅 UTF-8 内容有意义，合约不做校验
+    pub memo: [u8; 32],
+    /// 本 escrow 专属协议费率（bps），Escrow::FEE_BPS_USE_GLOBAL（u16::MAX）表示使用全局费率
+    pub fee_bps_override: u16,
+    /// take 全部成交关闭 vault 时租金的接收方，可与 Token B 收款方（maker）不同
+    /// （Pubkey::default() 表示与 escrow 租金相同，即 rent_collector 或 maker）
+    pub vault_rent_payer: Pubkey,
+    /// 接收 Token B 的钱包，之后可通过 set_recipient 修改（Pubkey::default() 表示 maker）
+    pub proceeds_recipient: Pubkey,
+    /// 部分成交时 Token B 数量是否向上取整（默认 false 向下取整，零头由 maker 承担）
+    pub round_up_receive: bool,
+    /// 是否为比例报价（按单价成交，receive 仅用于展示）；为 false 时以下字段被忽略
+    pub is_ratio: bool,
+    /// 比例报价单价的分子：每 price_den 个 Token A 需支付 price_num 个 Token B
+    pub price_num: u64,
+    /// 比例报价单价的分母（必须大于 0）
+    pub price_den: u64,
+    /// 持币门槛 mint：只有持有该 token 的 taker 才能成交（Pubkey::default() 表示不限制）
+    pub gate_mint: Pubkey,
+    /// taker 至少需持有的 gate_mint 数量（启用门槛时必须大于 0）
+    pub gate_min_balance: u64,
+}
+
+/// make 结果（通过 return data 返回），客户端模拟交易即可拿到 escrow 地址，无需自行推导 PDA
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct MadeEscrow {
+    /// 新建的 escrow PDA
+    pub escrow: Pubkey,
+    /// escrow PDA 的 bump
+    pub bump: u8,
+}
+
+impl MakeArgs {
+    /// 校验交易条款（与具体账户余额无关的部分）
+    pub fn validate(&self, mint_a: Pubkey, mint_b: Pubkey) -> Result<()> {
+        // 不允许 0 数量的交易
+        require_gt!(self.receive, 0, EscrowError::InvalidAmount);
+        require_gt!(self.amount, 0, EscrowError::InvalidAmount);
+
+        // 荷兰拍：价格只能随时间下降，且最低价不能为 0
+        if self.is_auction {
+            require!(
+                self.auction_end > self.auction_start
+                    && self.start_receive >= self.end_receive
+                    && self.end_receive > 0,
+                EscrowError::InvalidAuctionParams
+            );
+        }
+
+        // 比例报价：单价不能为 0 或分母为 0，且不能与荷兰拍同时启用
+        if self.is_ratio {
+            require!(
+                self.price_num > 0 && self.price_den > 0 && !self.is_auction,
+                EscrowError::InvalidPrice
+            );
+        }
+
+        // 持币门槛数量为 0 等于不限制，要求显式关闭
+        if self.gate_mint != Pubkey::default() {
+            require_gt!(self.gate_min_balance, 0, EscrowError::InvalidAmount);
+        }
+
+        // 专属费率同样受全局上限约束
+        require!(
+            self.fee_bps_override == Escrow::FEE_BPS_USE_GLOBAL
+                || self.fee_bps_override <= Config::MAX_FEE_BPS,
+            EscrowError::FeeTooHigh
+        );
+
+        // 成交上限不能低于下限
+        require!(
+            self.max_fill == 0 || self.max_fill >= self.min_fill,
+            EscrowError::InvalidAmount
+        );
+
+        // （可选但推荐）防止 A 和 B 是同一个 mint
+        require_keys_neq!(mint_a, mint_b, EscrowError::InvalidMintA);
+
+        // 备选 mint 必须是不同于 mint_a / mint_b 的第三种 token，且报价不为 0
+        if self.mint_b_alt != Pubkey::default() {
+            require_gt!(self.receive_alt, 0, EscrowError::InvalidAmount);
+            if self.mint_b_alt == mint_a || self.mint_b_alt == mint_b {
+                msg!(
+                    "InvalidMintB: mint_b_alt {} must differ from mint_a {} and mint_b {}",
+                    self.mint_b_alt,
+                    mint_a,
+                    mint_b
+                );
+                return err!(EscrowError::InvalidMintB);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// 按参数构造新的 Escrow 状态（status = Active）
+    pub fn to_escrow(
+        &self,
+        maker: Pubkey,
+        mint_a: &InterfaceAccount<Mint>,
+        mint_b: &InterfaceAccount<Mint>,
+        created_at: i64,
+        bump: u8,
+    ) -> Escrow {
+        Escrow {
+            maker,
+            seed: self.seed,
+            mint_a: mint_a.key(),
+            mint_b: mint_b.key(),
+            receive: self.receive,
+            deposit: self.amount,
+            expiry: self.expiry,
+            min_hold_until: self.min_hold_until,
+            allowed_taker: self.allowed_taker,
+            status: EscrowStatus::Active,
+            created_at,
+            is_auction: self.is_auction,
+            start_receive: self.start_receive,
+            end_receive: self.end_receive,
+            auction_start: self.auction_start,
+            auction_end: self.auction_end,
+            min_fill: self.min_fill,
+            max_fill: self.max_fill,
+            mint_b_alt: self.mint_b_alt,
+            receive_alt: self.receive_alt,
+            rent_collector: self.rent_collector,
+            is_delegated: false,
+            mint_a_decimals: mint_a.decimals,
+            mint_b_decimals: mint_b.decimals,
+            memo: self.memo,
+            creator: maker,
+            mint_a2: Pubkey::default(),
+            deposit2: 0,
+            in_progress: false,
+            fee_bps_override: self.fee_bps_override,
+            vault_rent_payer: self.vault_rent_payer,
+            proceeds_recipient: self.proceeds_recipient,
+            round_up_receive: self.round_up_receive,
+            is_ratio: self.is_ratio,
+            price_num: self.price_num,
+            price_den: self.price_den,
+            maker_ata_b_ready: false,
+            reserved: 0,
+            gate_mint: self.gate_mint,
+            gate_min_balance: self.gate_min_balance,
+            version: Escrow::CURRENT_VERSION,
+            bump,
+        }
+    }
+}
+
+/// 拒绝启用了 Token-2022 NonTransferable（灵魂绑定）扩展的 mint
+///
+/// 此类 token 无法 transfer，存入后 take / refund 都会失败，资金永久卡在 vault
+pub fn require_transferable(mint: &AccountInfo) -> Result<()> {
+    require!(
+        get_mint_extension_data::<NonTransferable>(mint).is_err(),
+        EscrowError::NonTransferableMint
+    );
+
+    Ok(())
+}
+
+/// 拒绝启用了 Token-2022 PermanentDelegate 扩展的 mint（用于存入的 Token A）
+///
+/// 永久代理人可以不经 vault authority（escrow PDA）签名转走 vault 中的资金
+pub fn require_no_permanent_delegate(mint: &AccountInfo) -> Result<()> {
+    require!(
+        get_mint_extension_data::<PermanentDelegate>(mint).is_err(),
+        EscrowError::PermanentDelegateNotAllowed
+    );
+
+    Ok(())
+}
+
+/// maker 的 Token A 账户必须足额且未被冻结（冻结的账户无法转出，提前给出明确错误）
+pub fn require_depositable(maker_ata_a: &TokenAccount, amount: u64) -> Result<()> {
+    require!(maker_ata_a.amount >= amount, EscrowError::InsufficientFunds);
+    require!(!maker_ata_a.is_frozen(), EscrowError::AccountFrozen);
+
+    Ok(())
+}
+
+/// make 时预先创建了 maker 的 Token B ATA：标记 escrow.maker_ata_b_ready
+///
+/// 预先创建的是 maker 的 ATA，因此 take 时的收款人（proceeds_destination）必须就是 maker
+pub fn mark_maker_ata_b_ready(escrow: &mut Escrow, maker: &Pubkey) -> Result<()> {
+    require_keys_eq!(
+        escrow.proceeds_destination(),
+        *maker,
+        EscrowError::MakerAtaRecipientMismatch
+    );
+    escrow.maker_ata_b_ready = true;
+
+    Ok(())
+}
+
+/// 读取全局配置，config 尚未初始化（空账户）时返回 None
+pub fn load_config(config: &AccountInfo) -> Result<Option<Config>> {
+    if config.data_is_empty() {
+        return Ok(None);
+    }
+    require_keys_eq!(*config.owner, crate::ID, ErrorCode::AccountOwnedByWrongProgram);
+
+    let data = config.try_borrow_data()?;
+    Ok(Some(Config::try_deserialize(&mut &data[..])?))
+}
+
+/// 运营方规则：最小挂单规模、交易对白名单、最长存活时间与 maker 挂单数上限
+/// （未初始化 config 时不校验）
+///
+/// pair_registered 为交易对白名单 PDA 是否已传入（seeds 约束保证传入的就是该交易对的 PDA）；
+/// open_escrows 为创建本 escrow 之前 maker 未关闭的 escrow 数量；
+/// 所有 make 变体都必须调用，否则 maker 可以换用其他变体绕过限制
+pub fn check_market_rules(
+    config: &AccountInfo,
+    pair_registered: bool,
+    open_escrows: u64,
+    args: &MakeArgs,
+) -> Result<()> {
+    let Some(config) = load_config(config)? else {
+        return Ok(());
+    };
+
+    // 最小挂单规模（荷兰拍按最低价 end_receive 计算）
+    let floor_receive = if args.is_auction {
+        args.end_receive
+    } else {
+        args.receive
+    };
+    require!(
+        config.meets_min_size(args.amount, floor_receive),
+        EscrowError::BelowMinimumSize
+    );
+
+    // 白名单模式下交易对必须已登记
+    require!(
+        !config.restrict_pairs || pair_registered,
+        EscrowError::PairNotAllowed
+    );
+
+    // 限制报价存活时间，避免长期挂单的陈旧流动性
+    let now = Clock::get()?.unix_timestamp;
+    require!(
+        config.within_max_ttl(args.expiry, now),
+        EscrowError::ExpiryTooFar
+    );
+
+    // 限制单个 maker 的挂单数量，避免状态膨胀
+    require!(
+        config.within_open_limit(open_escrows),
+        EscrowError::TooManyOpenEscrows
+    );
+
+    Ok(())
+}
 
 /// Make 指令：
 ///
 /// 1. 创建 Escrow PDA，保存交易条款
 /// 2. 创建 Vault（Escrow 拥有的 mint_a ATA）
 /// 3. 将 maker 的 Token A 转入 Vault
+///
+/// 安全：mint_a 不能带 PermanentDelegate 扩展——永久代理人可绕过 escrow PDA
+/// 直接转走 vault 中的 Token A，taker 付款后可能拿不到资金
 #[derive(Accounts)]
-#[instruction(seed: u64)]
+#[instruction(args: MakeArgs)]
 pub struct Make<'info> {
     /// 创建者（maker），决定交易条款并存入 Token A
     #[account(mut)]
@@ -31,15 +335,44 @@ pub struct Make<'info> {
     /// - "escrow"
     /// - maker 公钥
     /// - 用户提供的 seed（支持同一 maker 创建多个 escrow）
+    ///
+    /// seed 已被未关闭的 escrow 占用时，init 会在 handler 之前以
+    /// "account already in use" 失败；需要明确错误请使用 make_checked
     #[account(
         init,
         payer = maker,
         space = Escrow::INIT_SPACE + Escrow::DISCRIMINATOR.len(),
-        seeds = [b"escrow", maker.key().as_ref(), seed.to_le_bytes().as_ref()],
+        seeds = [b"escrow", maker.key().as_ref(), args.seed.to_le_bytes().as_ref()],
         bump,
     )]
     pub escrow: Account<'info, Escrow>,
 
+    /// maker 统计 PDA（首次 make 时创建）
+    #[account(
+        init_if_needed,
+        payer = maker,
+        space = MakerStats::INIT_SPACE + MakerStats::DISCRIMINATOR.len(),
+        seeds = [b"stats", maker.key().as_ref()],
+        bump,
+    )]
+    pub maker_stats: Account<'info, MakerStats>,
+
+    /// CHECK: 全局 Config PDA，地址由 seeds 约束。
+    /// 未初始化 config 的部署中为空账户，此时跳过最小规模与交易对白名单校验；
+    /// 必须传入固定地址而非 Option，maker 无法通过省略 config 绕过限制
+    #[account(
+        seeds = [b"config"],
+        bump,
+    )]
+    pub config: UncheckedAccount<'info>,
+
+    /// 交易对白名单（config.restrict_pairs 为 true 时必须传入）
+    #[account(
+        seeds = [b"pair", mint_a.key().as_ref(), mint_b.key().as_ref()],
+        bump = pair_registry.bump,
+    )]
+    pub pair_registry: Option<Box<Account<'info, PairRegistry>>>,
+
     // =======================
     // Token Mint Accounts
     // =======================
@@ -94,11 +427,25 @@ pub struct Make<'info> {
     )]
     pub vault: InterfaceAccount<'info, TokenAccount>,
 
+    /// Maker 的 Token B ATA（可选）
+    ///
+    /// 传入时由 maker 付费创建（已存在则跳过），escrow 标记 maker_ata_b_ready，
+    /// 之后 take 不再为 maker 代付租金；仅支持 proceeds_recipient 为 maker 的 escrow
+    #[account(
+        init_if_needed,
+        payer = maker,
+        associated_token::mint = mint_b,
+        associated_token::authority = maker,
+        associated_token::token_program = token_program
+    )]
+    pub maker_ata_b: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+
     // =======================
     // Programs
     // =======================
 
-    /// Associated Token Program（创建 ATA 使用）
+    /// Associated Token Program（创建 ATA 使用，显式约束地址）
+    #[account(address = anchor_spl::associated_token::ID)]
     pub associated_token_program: Program<'info, AssociatedToken>,
 
     /// Token Program（SPL Token 或 Token-2022）
@@ -115,27 +462,33 @@ impl<'info> Make<'info> {
     /// 初始化 Escrow 账户，保存所有交易条款
     ///
     /// 参数说明：
-    /// - seed: PDA 使用的随机种子
-    /// - receive: maker 希望收到的 Token B 数量
+    /// - args: make 指令参数（交易条款）
     /// - bump: Escrow PDA 的 bump，用于后续签名
-    pub fn populate_escrow(
-        &mut self,
-        seed: u64,
-        receive: u64,
-        bump: u8,
-    ) -> Result<()> {
-        self.escrow.set_inner(Escrow {
-            seed,
-            maker: self.maker.key(),
-            mint_a: self.mint_a.key(),
-            mint_b: self.mint_b.key(),
-            receive,
+    pub fn populate_escrow(&mut self, args: &MakeArgs, bump: u8) -> Result<()> {
+        let created_at = Clock::get()?.unix_timestamp;
+
+        self.escrow.set_inner(args.to_escrow(
+            self.maker.key(),
+            &self.mint_a,
+            &self.mint_b,
+            created_at,
             bump,
-        });
+        ));
 
         Ok(())
     }
 
+    /// 累加 maker 创建 / 未关闭的 escrow 数量（首次创建时写入 maker 与 bump）
+    pub fn record_created(&mut self, bump: u8) -> Result<()> {
+        let stats = &mut self.maker_stats;
+        if stats.maker == Pubkey::default() {
+            stats.maker = self.maker.key();
+            stats.bump = bump;
+        }
+
+        stats.record_created()
+    }
+
     /// 将 maker 的 Token A 存入 Vault
     ///
     /// 使用 transfer_checked：
@@ -143,18 +496,21 @@ impl<'info> Make<'info> {
     /// - 校验 decimals
     /// - 防止精度错误
     pub fn deposit_tokens(&self, amount: u64) -> Result<()> {
-        transfer_checked(
-            CpiContext::new(
-                self.token_program.to_account_info(),
-                TransferChecked {
-                    from: self.maker_ata_a.to_account_info(),
-                    mint: self.mint_a.to_account_info(),
-                    to: self.vault.to_account_info(),
-                    authority: self.maker.to_account_info(),
-                },
-            ),
-            amount,
-            self.mint_a.decimals,
+        cu_profile!(
+            "make: deposit",
+            transfer_checked(
+                CpiContext::new(
+                    self.token_program.to_account_info(),
+                    TransferChecked {
+                        from: self.maker_ata_a.to_account_info(),
+                        mint: self.mint_a.to_account_info(),
+                        to: self.vault.to_account_info(),
+                        authority: self.maker.to_account_info(),
+                    },
+                ),
+                amount,
+                self.mint_a.decimals,
+            )
         )?;
 
         Ok(())
@@ -163,48 +519,405 @@ impl<'info> Make<'info> {
 
 /// Make 指令入口
 ///
-/// 参数说明：
-/// - seed: 用于区分不同 escrow 的随机数
-/// - receive: maker 希望收到的 Token B 数量
-/// - amount: maker 存入的 Token A 数量
-pub fn handler(
-    ctx: Context<Make>,
-    seed: u64,
-    receive: u64,
-    amount: u64,
-) -> Result<()> {
+/// 参数说明见 MakeArgs
+pub fn handler(ctx: Context<Make>, args: MakeArgs) -> Result<MadeEscrow> {
     // =======================
     // 参数校验
     // =======================
 
-    // 不允许 0 数量的交易
-    require_gt!(receive, 0, EscrowError::InvalidAmount);
-    require_gt!(amount, 0, EscrowError::InvalidAmount);
+    args.validate(ctx.accounts.mint_a.key(), ctx.accounts.mint_b.key())?;
+    require_transferable(&ctx.accounts.mint_a.to_account_info())?;
+    require_transferable(&ctx.accounts.mint_b.to_account_info())?;
+    require_no_permanent_delegate(&ctx.accounts.mint_a.to_account_info())?;
 
-    // （可选但推荐）防止 A 和 B 是同一个 mint
-    require_keys_neq!(
-        ctx.accounts.mint_a.key(),
-        ctx.accounts.mint_b.key(),
-        EscrowError::InvalidMintA
-    );
+    check_market_rules(
+        &ctx.accounts.config,
+        ctx.accounts.pair_registry.is_some(),
+        ctx.accounts.maker_stats.open_escrows,
+        &args,
+    )?;
 
-    // （可选）提前校验 maker 余额是否足够
-    require!(
-        ctx.accounts.maker_ata_a.amount >= amount,
-        EscrowError::InsufficientFunds
-    );
+    // 提前校验 maker 余额是否足够、账户是否被冻结
+    require_depositable(&ctx.accounts.maker_ata_a, args.amount)?;
 
     // =======================
     // 初始化 Escrow
     // =======================
-    ctx.accounts
-        .populate_escrow(seed, receive, ctx.bumps.escrow)?;
+    ctx.accounts.populate_escrow(&args, ctx.bumps.escrow)?;
+
+    // 预先创建的 maker_ata_b 必须就是 take 时的收款 ATA
+    if ctx.accounts.maker_ata_b.is_some() {
+        mark_maker_ata_b_ready(&mut ctx.accounts.escrow, ctx.accounts.maker.key)?;
+    }
+    msg!(
+        "make: escrow={} bump={} receive={} mint_b_decimals={}",
+        ctx.accounts.escrow.key(),
+        ctx.bumps.escrow,
+        args.receive,
+        ctx.accounts.mint_b.decimals
+    );
 
     // =======================
     // 存入 Token A
     // =======================
-    ctx.accounts.deposit_tokens(amount)?;
+    ctx.accounts.deposit_tokens(args.amount)?;
 
-    Ok(())
+    // =======================
+    // 更新 maker 统计
+    // =======================
+    ctx.accounts.record_created(ctx.bumps.maker_stats)?;
+
+    // =======================
+    // 发出创建事件
+    // =======================
+    emit!(EscrowCreated {
+        escrow: ctx.accounts.escrow.key(),
+        maker: ctx.accounts.maker.key(),
+        mint_a: ctx.accounts.mint_a.key(),
+        mint_b: ctx.accounts.mint_b.key(),
+        deposit: args.amount,
+        receive: args.receive,
+        seed: args.seed,
+        created_at: ctx.accounts.escrow.created_at,
+        memo: args.memo,
+    });
+
+    Ok(MadeEscrow {
+        escrow: ctx.accounts.escrow.key(),
+        bump: ctx.bumps.escrow,
+    })
 }
 
+#[cfg(test)]
+mod tests {
+    use anchor_spl::token_2022::spl_token_2022::{
+        self,
+        extension::{BaseStateWithExtensionsMut, ExtensionType, StateWithExtensionsMut},
+        state::Mint as Token2022Mint,
+    };
+
+    use super::*;
+    use crate::state::escrow_pda;
+    use crate::test_utils::*;
+
+    const MAKE: u8 = 0;
+
+    #[test]
+    fn deposit_requires_funded_unfrozen_ata() {
+        let maker = Pubkey::new_unique();
+        let (mint_a, _) = setup_mints(6, 6);
+        let ata = |account| load_interface_account::<TokenAccount>(account);
+
+        assert!(require_depositable(&ata(fund_ata(&maker, &mint_a, 100)), 100).is_ok());
+        assert_err(
+            require_depositable(&ata(fund_ata(&maker, &mint_a, 99)), 100),
+            EscrowError::InsufficientFunds,
+        );
+        assert_err(
+            require_depositable(&ata(freeze(fund_ata(&maker, &mint_a, 100))), 100),
+            EscrowError::AccountFrozen,
+        );
+    }
+
+    #[test]
+    fn memo_round_trips() {
+        let (mint_a, mint_b) = setup_mints(6, 6);
+        let mut memo = [0u8; 32];
+        memo[..17].copy_from_slice(b"OTC deal ref #123");
+        let args = MakeArgs { memo, ..make_args() };
+
+        let escrow = args.to_escrow(
+            Pubkey::new_unique(),
+            &load_interface_account::<Mint>(mint_a),
+            &load_interface_account::<Mint>(mint_b),
+            1_000,
+            255,
+        );
+        let account = escrow_account(&escrow);
+        let stored = Escrow::try_deserialize(&mut &account.data[..]).unwrap();
+
+        assert_eq!(stored.memo, memo);
+        assert_eq!(account.data.len(), Escrow::DISCRIMINATOR.len() + Escrow::INIT_SPACE);
+    }
+
+    #[test]
+    fn round_up_receive_is_set_at_make() {
+        let maker = Pubkey::new_unique();
+        let (mint_a, mint_b) = setup_mints(6, 6);
+        let floor = made_escrow(&maker, &mint_a, &mint_b);
+        assert!(!floor.round_up_receive);
+
+        let args = MakeArgs { round_up_receive: true, ..make_args() };
+        let escrow = args.to_escrow(
+            maker,
+            &load_interface_account::<Mint>(mint_a),
+            &load_interface_account::<Mint>(mint_b),
+            1_000,
+            255,
+        );
+
+        // 1 * 50 / 100 = 0.5
+        assert!(escrow.round_up_receive);
+        assert_eq!(floor.receive_for(1, 1_000, false).unwrap(), 0);
+        assert_eq!(escrow.receive_for(1, 1_000, false).unwrap(), 1);
+    }
+
+    #[test]
+    fn pre_created_maker_ata_requires_maker_as_recipient() {
+        let maker = Pubkey::new_unique();
+        let (mint_a, mint_b) = setup_mints(6, 6);
+
+        // 未传入 maker_ata_b 时保持惰性创建（由 taker 代付）
+        let mut escrow = made_escrow(&maker, &mint_a, &mint_b);
+        assert!(!escrow.maker_ata_b_ready);
+        mark_maker_ata_b_ready(&mut escrow, &maker).unwrap();
+        assert!(escrow.maker_ata_b_ready);
+
+        let mut redirected = made_escrow(&maker, &mint_a, &mint_b);
+        redirected.proceeds_recipient = Pubkey::new_unique();
+        assert_err(
+            mark_maker_ata_b_ready(&mut redirected, &maker),
+            EscrowError::MakerAtaRecipientMismatch,
+        );
+        assert!(!redirected.maker_ata_b_ready);
+    }
+
+    /// 启用了 NonTransferable（灵魂绑定）扩展的 Token-2022 mint
+    fn non_transferable_mint() -> AccountInfo<'static> {
+        let len = ExtensionType::try_calculate_account_len::<Token2022Mint>(&[
+            ExtensionType::NonTransferable,
+        ])
+        .unwrap();
+        let mut data = vec![0u8; len];
+        let mut state =
+            StateWithExtensionsMut::<Token2022Mint>::unpack_uninitialized(&mut data).unwrap();
+        state.init_extension::<NonTransferable>(true).unwrap();
+        state.base.decimals = 6;
+        state.base.is_initialized = true;
+        state.pack_base();
+        state.init_account_type().unwrap();
+
+        TestAccount::new(Pubkey::new_unique(), spl_token_2022::ID, data).into_info()
+    }
+
+    #[test]
+    fn non_transferable_mint_is_rejected() {
+        assert!(require_transferable(&mint_account(6).into_info()).is_ok());
+        assert_err(
+            require_transferable(&non_transferable_mint()),
+            EscrowError::NonTransferableMint,
+        );
+    }
+
+    #[test]
+    fn ratio_price_must_be_valid() {
+        let (mint_a, mint_b) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let ratio = |price_num, price_den| MakeArgs {
+            is_ratio: true,
+            price_num,
+            price_den,
+            ..make_args()
+        };
+
+        assert!(ratio(2, 1).validate(mint_a, mint_b).is_ok());
+        assert_err(ratio(2, 0).validate(mint_a, mint_b), EscrowError::InvalidPrice);
+        assert_err(ratio(0, 1).validate(mint_a, mint_b), EscrowError::InvalidPrice);
+        let auction = MakeArgs {
+            is_auction: true,
+            start_receive: 60,
+            end_receive: 40,
+            auction_start: 1_000,
+            auction_end: 2_000,
+            ..ratio(2, 1)
+        };
+        assert_err(auction.validate(mint_a, mint_b), EscrowError::InvalidPrice);
+        // 未启用比例报价时忽略单价字段
+        assert!(MakeArgs { is_ratio: false, ..ratio(0, 0) }.validate(mint_a, mint_b).is_ok());
+    }
+
+    #[test]
+    fn gate_requires_positive_minimum_balance() {
+        let (mint_a, mint_b) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let gated = |gate_min_balance| MakeArgs {
+            gate_mint: Pubkey::new_unique(),
+            gate_min_balance,
+            ..make_args()
+        };
+
+        assert!(gated(1).validate(mint_a, mint_b).is_ok());
+        assert_err(gated(0).validate(mint_a, mint_b), EscrowError::InvalidAmount);
+        // 默认 gate_mint 表示不限制
+        assert!(make_args().validate(mint_a, mint_b).is_ok());
+    }
+
+    #[test]
+    fn fee_override_is_capped() {
+        let (mint_a, mint_b) = (Pubkey::new_unique(), Pubkey::new_unique());
+
+        assert!(make_args().validate(mint_a, mint_b).is_ok());
+        for fee_bps_override in [0, Config::MAX_FEE_BPS] {
+            let args = MakeArgs { fee_bps_override, ..make_args() };
+            assert!(args.validate(mint_a, mint_b).is_ok());
+        }
+        let args = MakeArgs {
+            fee_bps_override: Config::MAX_FEE_BPS + 1,
+            ..make_args()
+        };
+        assert_err(args.validate(mint_a, mint_b), EscrowError::FeeTooHigh);
+    }
+
+    #[test]
+    fn enforces_minimum_size() {
+        set_clock(1_000, 1);
+        let config = Config {
+            min_deposit: 100,
+            min_receive: 50,
+            ..default_config()
+        };
+        let check = |config: TestAccount, amount, receive| {
+            let args = MakeArgs { amount, receive, ..make_args() };
+            check_market_rules(&config.into_info(), false, 0, &args)
+        };
+
+        // 恰好达到与超过门槛
+        assert!(check(config_account(&config), 100, 50).is_ok());
+        assert!(check(config_account(&config), 101, 51).is_ok());
+
+        // 任一数量低于门槛
+        assert_err(check(config_account(&config), 99, 50), EscrowError::BelowMinimumSize);
+        assert_err(check(config_account(&config), 100, 49), EscrowError::BelowMinimumSize);
+
+        // 未初始化 config 时不校验
+        assert!(check(no_config(), 1, 1).is_ok());
+    }
+
+    #[test]
+    fn auction_minimum_uses_floor_price() {
+        set_clock(1_000, 1);
+        let config = Config {
+            min_receive: 50,
+            ..default_config()
+        };
+        let args = MakeArgs {
+            is_auction: true,
+            start_receive: 80,
+            end_receive: 40,
+            ..make_args()
+        };
+
+        assert_err(
+            check_market_rules(&config_account(&config).into_info(), false, 0, &args),
+            EscrowError::BelowMinimumSize,
+        );
+    }
+
+    #[test]
+    fn stores_both_mint_decimals() {
+        let (mint_a, mint_b) = setup_mints(6, 9);
+        let escrow = make_args().to_escrow(
+            Pubkey::new_unique(),
+            &load_interface_account::<Mint>(mint_a),
+            &load_interface_account::<Mint>(mint_b),
+            1_000,
+            255,
+        );
+        let account = escrow_account(&escrow);
+        let stored = Escrow::try_deserialize(&mut &account.data[..]).unwrap();
+
+        // take 按存储的精度校验传入的 mint，不依赖调用方
+        assert_eq!((stored.mint_a_decimals, stored.mint_b_decimals), (6, 9));
+    }
+
+    #[test]
+    fn permanent_delegate_mint_is_rejected() {
+        assert!(require_no_permanent_delegate(&mint_account(6).into_info()).is_ok());
+        assert_err(
+            require_no_permanent_delegate(
+                &permanent_delegate_mint(&Pubkey::new_unique()).into_info(),
+            ),
+            EscrowError::PermanentDelegateNotAllowed,
+        );
+    }
+
+    #[test]
+    fn made_escrow_decodes_to_escrow_pda() {
+        let maker = Pubkey::new_unique();
+        let (escrow, bump) = escrow_pda(&maker, make_args().seed, &crate::ID);
+
+        // 按 Anchor 写入 return data 的方式序列化（宿主机上 handler 无法执行到返回处）
+        let return_data = MadeEscrow { escrow, bump }.try_to_vec().unwrap();
+        assert_eq!(return_data.len(), 33);
+
+        let made = MadeEscrow::try_from_slice(&return_data).unwrap();
+        assert_eq!(made.escrow, escrow);
+        assert_eq!(made.bump, bump);
+        assert_eq!(
+            Pubkey::create_program_address(
+                &[b"escrow", maker.as_ref(), &make_args().seed.to_le_bytes(), &[made.bump]],
+                &crate::ID,
+            ),
+            Ok(made.escrow)
+        );
+    }
+
+    #[test]
+    fn expiry_beyond_max_ttl_is_rejected() {
+        set_clock(1_000, 1);
+        let config = Config {
+            max_ttl_seconds: 3_600,
+            ..default_config()
+        };
+        let check = |expiry| {
+            let args = MakeArgs { expiry, ..make_args() };
+            check_market_rules(&config_account(&config).into_info(), false, 0, &args)
+        };
+
+        assert!(check(4_600).is_ok());
+        assert_err(check(4_601), EscrowError::ExpiryTooFar);
+        assert_err(check(0), EscrowError::ExpiryTooFar);
+    }
+
+    #[test]
+    fn associated_token_program_is_pinned() {
+        set_clock(1_000, 1);
+        let (mint_a, mint_b) = setup_mints(6, 6);
+        let maker = Pubkey::new_unique();
+        let args = make_args();
+        // escrow 与 vault 尚未创建：init 在全部账户反序列化之后才执行 CPI
+        let (escrow, _) = escrow_pda(&maker, args.seed, &crate::ID);
+        let vault = fund_ata(&escrow, &mint_a, 0);
+        let accounts = vec![
+            TestAccount::wallet(maker, 1_000_000_000).signer(),
+            TestAccount::wallet(escrow, 0).writable(),
+            maker_stats_account(&maker, 0).writable(),
+            no_config(),
+            none(),
+            mint_a.clone(),
+            mint_b,
+            fund_ata(&maker, &mint_a, 1_000).writable(),
+            TestAccount::wallet(vault.key, 0).writable(),
+            none(),
+            TestAccount::program(Pubkey::new_unique()),
+            TestAccount::program(mint_a.owner),
+            TestAccount::program(anchor_lang::system_program::ID),
+        ];
+
+        assert_error(run(&ix_data(MAKE, args), accounts).0, ErrorCode::InvalidProgramId);
+    }
+
+    #[test]
+    fn make_beyond_open_cap_is_rejected() {
+        set_clock(1_000, 1);
+        let config = Config {
+            max_open_per_maker: 3,
+            ..default_config()
+        };
+        let check = |open_escrows| {
+            let config = config_account(&config).into_info();
+            check_market_rules(&config, false, open_escrows, &make_args())
+        };
+
+        assert!(check(2).is_ok());
+        assert_err(check(3), EscrowError::TooManyOpenEscrows);
+    }
+}
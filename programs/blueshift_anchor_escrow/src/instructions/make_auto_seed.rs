@@ -0,0 +1,64 @@
+use anchor_lang::prelude::*;
+
+use crate::state::auto_seed;
+use crate::errors::EscrowError;
+use crate::instructions::make::{self, Make, MakeArgs};
+
+/// MakeAutoSeed 指令入口
+///
+/// 账户与 make 相同，seed 由程序按 maker 的创建计数推导，客户端无需自行管理 seed：
+/// - 期望 seed = auto_seed(maker, maker_stats.total_created)（新 maker 的计数为 0）
+/// - args.seed 必须等于期望 seed（escrow PDA 需在交易中预先给出，因此仍由客户端传入），
+///   否则返回 AutoSeedMismatch
+/// - 创建成功后计数递增，同一 maker 下一次自动 seed 不会与之前的重复
+///
+/// seed 通过 return data 返回
+///
+/// 同一 maker 并发发送多笔 make_auto_seed 时，只有第一笔能成功，其余因 seed 过期失败，
+/// 需重新读取 MakerStats 后重试
+pub fn handler(ctx: Context<Make>, args: MakeArgs) -> Result<u64> {
+    let seed = auto_seed(
+        &ctx.accounts.maker.key(),
+        ctx.accounts.maker_stats.total_created,
+    );
+    require_eq!(args.seed, seed, EscrowError::AutoSeedMismatch);
+
+    make::handler(ctx, args)?;
+
+    Ok(seed)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::*;
+    use crate::state::{escrow_pda, MakerStats};
+
+    #[test]
+    fn consecutive_auto_seeds_do_not_collide() {
+        let maker = Pubkey::new_unique();
+        let mut stats = MakerStats {
+            maker,
+            total_created: 0,
+            total_taken: 0,
+            total_refunded: 0,
+            open_escrows: 0,
+            bump: 255,
+        };
+
+        // 同一 slot 内连续两次 make：第一次成功后计数递增，第二次得到新的 seed
+        let first = auto_seed(&maker, stats.total_created);
+        stats.record_created().unwrap();
+        let second = auto_seed(&maker, stats.total_created);
+
+        assert_ne!(first, second);
+        assert_ne!(escrow_pda(&maker, first, &crate::ID), escrow_pda(&maker, second, &crate::ID));
+        assert_eq!(first, auto_seed(&maker, 0));
+
+        let seeds = (0..1_000).map(|nonce| auto_seed(&maker, nonce)).collect::<HashSet<_>>();
+        assert_eq!(seeds.len(), 1_000);
+        // 不同 maker 的计数都从 0 开始，seed 同样不同
+        assert_ne!(auto_seed(&Pubkey::new_unique(), 0), first);
+    }
+}
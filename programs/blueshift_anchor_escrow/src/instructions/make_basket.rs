@@ -0,0 +1,276 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_interface::{
+        Mint,
+        TokenAccount,
+        TokenInterface,
+        transfer_checked,
+        TransferChecked,
+    },
+};
+
+use crate::state::{Escrow, MakerStats, PairRegistry};
+use crate::errors::EscrowError;
+use crate::events::EscrowCreated;
+use crate::instructions::make::{
+    check_market_rules,
+    require_no_permanent_delegate,
+    require_transferable,
+    MakeArgs,
+};
+
+/// MakeBasket 指令：
+///
+/// 与 make 相同，但 maker 同时存入两种 Token A（例如 10 TokenX + 5 TokenY 换 100 USDC）：
+/// - mint_a / args.amount 存入 vault
+/// - mint_a2 / amount2 存入 vault2（escrow 拥有的 mint_a2 ATA）
+///
+/// 组合报价只能通过 take_basket 整单成交、通过 refund_basket 撤回；
+/// 不支持荷兰拍、备选 mint_b 与部分成交
+#[derive(Accounts)]
+#[instruction(args: MakeArgs)]
+pub struct MakeBasket<'info> {
+    /// 创建者（maker），存入两种 Token A
+    #[account(mut)]
+    pub maker: Signer<'info>,
+
+    /// Escrow PDA（seeds 与 make 相同）
+    #[account(
+        init,
+        payer = maker,
+        space = Escrow::INIT_SPACE + Escrow::DISCRIMINATOR.len(),
+        seeds = [b"escrow", maker.key().as_ref(), args.seed.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub escrow: Box<Account<'info, Escrow>>,
+
+    /// maker 统计 PDA（首次 make 时创建）
+    #[account(
+        init_if_needed,
+        payer = maker,
+        space = MakerStats::INIT_SPACE + MakerStats::DISCRIMINATOR.len(),
+        seeds = [b"stats", maker.key().as_ref()],
+        bump,
+    )]
+    pub maker_stats: Box<Account<'info, MakerStats>>,
+
+    /// CHECK: 全局 Config PDA（与 make 相同，未初始化时为空账户）
+    #[account(
+        seeds = [b"config"],
+        bump,
+    )]
+    pub config: UncheckedAccount<'info>,
+
+    /// mint_a -> mint_b 的交易对白名单（config.restrict_pairs 为 true 时必须传入）
+    #[account(
+        seeds = [b"pair", mint_a.key().as_ref(), mint_b.key().as_ref()],
+        bump = pair_registry.bump,
+    )]
+    pub pair_registry: Option<Box<Account<'info, PairRegistry>>>,
+
+    /// mint_a2 -> mint_b 的交易对白名单（config.restrict_pairs 为 true 时必须传入）
+    #[account(
+        seeds = [b"pair", mint_a2.key().as_ref(), mint_b.key().as_ref()],
+        bump = pair_registry2.bump,
+    )]
+    pub pair_registry2: Option<Box<Account<'info, PairRegistry>>>,
+
+    // =======================
+    // Token Mint Accounts
+    // =======================
+
+    /// 第一种 Token A 的 mint
+    #[account(
+        mint::token_program = token_program
+    )]
+    pub mint_a: Box<InterfaceAccount<'info, Mint>>,
+
+    /// 第二种 Token A 的 mint
+    #[account(
+        mint::token_program = token_program
+    )]
+    pub mint_a2: Box<InterfaceAccount<'info, Mint>>,
+
+    /// Maker 希望换取的 Token B 的 mint
+    #[account(
+        mint::token_program = token_program
+    )]
+    pub mint_b: Box<InterfaceAccount<'info, Mint>>,
+
+    // =======================
+    // Token Accounts
+    // =======================
+
+    /// Maker 的 mint_a ATA
+    #[account(
+        mut,
+        associated_token::mint = mint_a,
+        associated_token::authority = maker,
+        associated_token::token_program = token_program
+    )]
+    pub maker_ata_a: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Maker 的 mint_a2 ATA
+    #[account(
+        mut,
+        associated_token::mint = mint_a2,
+        associated_token::authority = maker,
+        associated_token::token_program = token_program
+    )]
+    pub maker_ata_a2: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Vault：Escrow 拥有的 mint_a ATA
+    #[account(
+        init,
+        payer = maker,
+        associated_token::mint = mint_a,
+        associated_token::authority = escrow,
+        associated_token::token_program = token_program
+    )]
+    pub vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Vault2：Escrow 拥有的 mint_a2 ATA
+    #[account(
+        init,
+        payer = maker,
+        associated_token::mint = mint_a2,
+        associated_token::authority = escrow,
+        associated_token::token_program = token_program
+    )]
+    pub vault2: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    // =======================
+    // Programs
+    // =======================
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> MakeBasket<'info> {
+    /// 将 maker 的 Token A 存入对应 vault
+    fn deposit(
+        &self,
+        from: &InterfaceAccount<'info, TokenAccount>,
+        mint: &InterfaceAccount<'info, Mint>,
+        to: &InterfaceAccount<'info, TokenAccount>,
+        amount: u64,
+    ) -> Result<()> {
+        transfer_checked(
+            CpiContext::new(
+                self.token_program.to_account_info(),
+                TransferChecked {
+                    from: from.to_account_info(),
+                    mint: mint.to_account_info(),
+                    to: to.to_account_info(),
+                    authority: self.maker.to_account_info(),
+                },
+            ),
+            amount,
+            mint.decimals,
+        )?;
+
+        Ok(())
+    }
+
+    /// 累加 maker 创建 / 未关闭的 escrow 数量（首次创建时写入 maker 与 bump）
+    fn record_created(&mut self, bump: u8) -> Result<()> {
+        let stats = &mut self.maker_stats;
+        if stats.maker == Pubkey::default() {
+            stats.maker = self.maker.key();
+            stats.bump = bump;
+        }
+
+        stats.record_created()
+    }
+}
+
+/// MakeBasket 指令入口
+///
+/// 参数说明：
+/// - args: 交易条款，args.amount 为 mint_a 的存入数量
+/// - amount2: mint_a2 的存入数量
+pub fn handler(ctx: Context<MakeBasket>, args: MakeArgs, amount2: u64) -> Result<()> {
+    // =======================
+    // 参数校验
+    // =======================
+    args.validate(ctx.accounts.mint_a.key(), ctx.accounts.mint_b.key())?;
+    require_gt!(amount2, 0, EscrowError::InvalidAmount);
+    require_transferable(&ctx.accounts.mint_a.to_account_info())?;
+    require_transferable(&ctx.accounts.mint_a2.to_account_info())?;
+    require_transferable(&ctx.accounts.mint_b.to_account_info())?;
+    require_no_permanent_delegate(&ctx.accounts.mint_a.to_account_info())?;
+    require_no_permanent_delegate(&ctx.accounts.mint_a2.to_account_info())?;
+    check_market_rules(
+        &ctx.accounts.config,
+        ctx.accounts.pair_registry.is_some()
+            && ctx.accounts.pair_registry2.is_some(),
+        ctx.accounts.maker_stats.open_escrows,
+        &args,
+    )?;
+
+    // 组合报价只支持固定价格整单成交
+    require!(!args.is_auction, EscrowError::InvalidAuctionParams);
+    require_keys_eq!(args.mint_b_alt, Pubkey::default(), EscrowError::InvalidMintB);
+
+    // 两种 Token A 互不相同，且都不同于 mint_b
+    let mint_a2 = ctx.accounts.mint_a2.key();
+    if mint_a2 == ctx.accounts.mint_a.key() || mint_a2 == ctx.accounts.mint_b.key() {
+        msg!(
+            "InvalidMintA: mint_a2 {} must differ from mint_a {} and mint_b {}",
+            mint_a2,
+            ctx.accounts.mint_a.key(),
+            ctx.accounts.mint_b.key()
+        );
+        return err!(EscrowError::InvalidMintA);
+    }
+
+    require!(
+        ctx.accounts.maker_ata_a.amount >= args.amount
+            && ctx.accounts.maker_ata_a2.amount >= amount2,
+        EscrowError::InsufficientFunds
+    );
+    require!(
+        !ctx.accounts.maker_ata_a.is_frozen() && !ctx.accounts.maker_ata_a2.is_frozen(),
+        EscrowError::AccountFrozen
+    );
+
+    // =======================
+    // 初始化 Escrow
+    // =======================
+    let created_at = Clock::get()?.unix_timestamp;
+    let mut escrow = args.to_escrow(
+        ctx.accounts.maker.key(),
+        &ctx.accounts.mint_a,
+        &ctx.accounts.mint_b,
+        created_at,
+        ctx.bumps.escrow,
+    );
+    escrow.mint_a2 = mint_a2;
+    escrow.deposit2 = amount2;
+    ctx.accounts.escrow.set_inner(escrow);
+
+    // =======================
+    // 存入两种 Token A
+    // =======================
+    let accounts = &ctx.accounts;
+    accounts.deposit(&accounts.maker_ata_a, &accounts.mint_a, &accounts.vault, args.amount)?;
+    accounts.deposit(&accounts.maker_ata_a2, &accounts.mint_a2, &accounts.vault2, amount2)?;
+
+    ctx.accounts.record_created(ctx.bumps.maker_stats)?;
+
+    emit!(EscrowCreated {
+        escrow: ctx.accounts.escrow.key(),
+        maker: ctx.accounts.maker.key(),
+        mint_a: ctx.accounts.mint_a.key(),
+        mint_b: ctx.accounts.mint_b.key(),
+        deposit: args.amount,
+        receive: args.receive,
+        seed: args.seed,
+        created_at,
+        memo: args.memo,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,232 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_interface::{
+        Mint,
+        TokenAccount,
+        TokenInterface,
+        transfer_checked,
+        TransferChecked,
+    },
+};
+
+use crate::state::{Escrow, MakerStats, PairRegistry};
+use crate::errors::EscrowError;
+use crate::events::EscrowCreated;
+use crate::instructions::make::{
+    check_market_rules,
+    require_depositable,
+    require_no_permanent_delegate,
+    require_transferable,
+    MakeArgs,
+};
+
+/// MakeChecked 指令：
+///
+/// 与 make 完全相同，但 seed 已被仍未关闭的 escrow 占用时返回明确的 SeedAlreadyInUse，
+/// 而不是 make 中 `init` 在 handler 之前抛出的通用 "account already in use" 错误。
+///
+/// escrow / vault 使用 init_if_needed，在 handler 中通过 discriminator
+/// 判断 escrow 是否为本指令新建的账户
+#[derive(Accounts)]
+#[instruction(args: MakeArgs)]
+pub struct MakeChecked<'info> {
+    /// 创建者（maker），决定交易条款并存入 Token A
+    #[account(mut)]
+    pub maker: Signer<'info>,
+
+    /// Escrow PDA（seeds 与 make 相同）
+    #[account(
+        init_if_needed,
+        payer = maker,
+        space = Escrow::INIT_SPACE + Escrow::DISCRIMINATOR.len(),
+        seeds = [b"escrow", maker.key().as_ref(), args.seed.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub escrow: Box<Account<'info, Escrow>>,
+
+    /// maker 统计 PDA（首次 make 时创建）
+    #[account(
+        init_if_needed,
+        payer = maker,
+        space = MakerStats::INIT_SPACE + MakerStats::DISCRIMINATOR.len(),
+        seeds = [b"stats", maker.key().as_ref()],
+        bump,
+    )]
+    pub maker_stats: Box<Account<'info, MakerStats>>,
+
+    /// CHECK: 全局 Config PDA（与 make 相同，未初始化时为空账户）
+    #[account(
+        seeds = [b"config"],
+        bump,
+    )]
+    pub config: UncheckedAccount<'info>,
+
+    /// 交易对白名单（config.restrict_pairs 为 true 时必须传入）
+    #[account(
+        seeds = [b"pair", mint_a.key().as_ref(), mint_b.key().as_ref()],
+        bump = pair_registry.bump,
+    )]
+    pub pair_registry: Option<Box<Account<'info, PairRegistry>>>,
+
+    /// Token A 的 mint
+    #[account(
+        mint::token_program = token_program
+    )]
+    pub mint_a: Box<InterfaceAccount<'info, Mint>>,
+
+    /// Token B 的 mint
+    #[account(
+        mint::token_program = token_program
+    )]
+    pub mint_b: Box<InterfaceAccount<'info, Mint>>,
+
+    /// Maker 的 Token A ATA
+    #[account(
+        mut,
+        associated_token::mint = mint_a,
+        associated_token::authority = maker,
+        associated_token::token_program = token_program
+    )]
+    pub maker_ata_a: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Vault：Escrow 拥有的 Token A ATA
+    #[account(
+        init_if_needed,
+        payer = maker,
+        associated_token::mint = mint_a,
+        associated_token::authority = escrow,
+        associated_token::token_program = token_program
+    )]
+    pub vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Programs
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> MakeChecked<'info> {
+    /// escrow 是否为本指令新建（新建账户在指令结束前尚未写入 discriminator）
+    pub fn is_fresh_escrow(&self) -> Result<bool> {
+        let info = self.escrow.to_account_info();
+        let data = info.try_borrow_data()?;
+
+        Ok(!data.starts_with(Escrow::DISCRIMINATOR))
+    }
+
+    /// 将 maker 的 Token A 存入 Vault
+    pub fn deposit_tokens(&self, amount: u64) -> Result<()> {
+        transfer_checked(
+            CpiContext::new(
+                self.token_program.to_account_info(),
+                TransferChecked {
+                    from: self.maker_ata_a.to_account_info(),
+                    mint: self.mint_a.to_account_info(),
+                    to: self.vault.to_account_info(),
+                    authority: self.maker.to_account_info(),
+                },
+            ),
+            amount,
+            self.mint_a.decimals,
+        )?;
+
+        Ok(())
+    }
+
+    /// 累加 maker 创建 / 未关闭的 escrow 数量（首次创建时写入 maker 与 bump）
+    fn record_created(&mut self, bump: u8) -> Result<()> {
+        let stats = &mut self.maker_stats;
+        if stats.maker == Pubkey::default() {
+            stats.maker = self.maker.key();
+            stats.bump = bump;
+        }
+
+        stats.record_created()
+    }
+}
+
+/// MakeChecked 指令入口
+///
+/// 参数说明见 MakeArgs
+pub fn handler(ctx: Context<MakeChecked>, args: MakeArgs) -> Result<()> {
+    // 同一 maker + seed 的 escrow 仍未关闭
+    require!(
+        ctx.accounts.is_fresh_escrow()?,
+        EscrowError::SeedAlreadyInUse
+    );
+
+    create_escrow(ctx, args.amount, args)
+}
+
+/// 校验条款并写入新 escrow，从 maker 转入 deposit 个 Token A
+///
+/// escrow.deposit 始终为 args.amount；deposit 小于 args.amount 时
+/// 差额必须已在 vault 中（见 make_resume）
+pub fn create_escrow(ctx: Context<MakeChecked>, deposit: u64, args: MakeArgs) -> Result<()> {
+    args.validate(ctx.accounts.mint_a.key(), ctx.accounts.mint_b.key())?;
+    require_transferable(&ctx.accounts.mint_a.to_account_info())?;
+    require_transferable(&ctx.accounts.mint_b.to_account_info())?;
+    require_no_permanent_delegate(&ctx.accounts.mint_a.to_account_info())?;
+    check_market_rules(
+        &ctx.accounts.config,
+        ctx.accounts.pair_registry.is_some(),
+        ctx.accounts.maker_stats.open_escrows,
+        &args,
+    )?;
+
+    require_depositable(&ctx.accounts.maker_ata_a, deposit)?;
+
+    let created_at = Clock::get()?.unix_timestamp;
+    let escrow = args.to_escrow(
+        ctx.accounts.maker.key(),
+        &ctx.accounts.mint_a,
+        &ctx.accounts.mint_b,
+        created_at,
+        ctx.bumps.escrow,
+    );
+    ctx.accounts.escrow.set_inner(escrow);
+
+    ctx.accounts.deposit_tokens(deposit)?;
+
+    ctx.accounts.record_created(ctx.bumps.maker_stats)?;
+
+    emit!(EscrowCreated {
+        escrow: ctx.accounts.escrow.key(),
+        maker: ctx.accounts.maker.key(),
+        mint_a: ctx.accounts.mint_a.key(),
+        mint_b: ctx.accounts.mint_b.key(),
+        deposit: args.amount,
+        receive: args.receive,
+        seed: args.seed,
+        created_at,
+        memo: args.memo,
+    });
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::*;
+
+    const MAKE_CHECKED: u8 = 23;
+
+    #[test]
+    fn live_seed_is_rejected() {
+        set_clock(1_000, 1);
+        let (mint_a, mint_b) = setup_mints(6, 6);
+        let maker = Pubkey::new_unique();
+        let args = make_args();
+        // 同一 maker + seed 的 escrow 仍未关闭
+        let live = make_escrow(&maker, args.seed, &mint_a, &mint_b, 100, 50);
+
+        let (result, _) = run(
+            &ix_data(MAKE_CHECKED, args),
+            make_checked_accounts(&maker, &live, &mint_a, &mint_b),
+        );
+
+        assert_error(result, EscrowError::SeedAlreadyInUse);
+    }
+}
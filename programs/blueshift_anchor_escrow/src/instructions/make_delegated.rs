@@ -0,0 +1,175 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{
+    approve_checked,
+    ApproveChecked,
+    Mint,
+    TokenAccount,
+    TokenInterface,
+};
+
+use crate::state::{Escrow, MakerStats, PairRegistry};
+use crate::events::EscrowCreated;
+use crate::instructions::make::{
+    check_market_rules,
+    require_depositable,
+    require_no_permanent_delegate,
+    require_transferable,
+    MakeArgs,
+};
+
+/// MakeDelegated 指令：
+///
+/// 与 make 相同的交易条款，但不创建 vault、不转移 Token A：
+/// maker 将 escrow PDA 设为 maker_ata_a 的 delegate（额度 = amount），
+/// 成交时由 take_delegated 通过 delegate 权限直接从 maker_ata_a 划转。
+///
+/// ⚠️ 托管保证较弱：
+/// - Token A 仍在 maker 手中，maker 可随时 revoke、转走或被冻结
+/// - 成交时若授权失效或余额不足，take_delegated 会失败（DelegateRevoked / InsufficientFunds）
+/// - 同一 token 账户同时只能有一个 delegate，再次授权会使之前的 delegated escrow 失效
+/// - 节省了 vault 的租金；撤回报价请调用 close_escrow 并自行 revoke 授权
+#[derive(Accounts)]
+#[instruction(args: MakeArgs)]
+pub struct MakeDelegated<'info> {
+    /// 创建者（maker），授权 escrow PDA 动用其 Token A
+    #[account(mut)]
+    pub maker: Signer<'info>,
+
+    /// Escrow PDA（seeds 与 make 相同）
+    #[account(
+        init,
+        payer = maker,
+        space = Escrow::INIT_SPACE + Escrow::DISCRIMINATOR.len(),
+        seeds = [b"escrow", maker.key().as_ref(), args.seed.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub escrow: Box<Account<'info, Escrow>>,
+
+    /// maker 统计 PDA（首次 make 时创建）
+    #[account(
+        init_if_needed,
+        payer = maker,
+        space = MakerStats::INIT_SPACE + MakerStats::DISCRIMINATOR.len(),
+        seeds = [b"stats", maker.key().as_ref()],
+        bump,
+    )]
+    pub maker_stats: Box<Account<'info, MakerStats>>,
+
+    /// CHECK: 全局 Config PDA（与 make 相同，未初始化时为空账户）
+    #[account(
+        seeds = [b"config"],
+        bump,
+    )]
+    pub config: UncheckedAccount<'info>,
+
+    /// 交易对白名单（config.restrict_pairs 为 true 时必须传入）
+    #[account(
+        seeds = [b"pair", mint_a.key().as_ref(), mint_b.key().as_ref()],
+        bump = pair_registry.bump,
+    )]
+    pub pair_registry: Option<Box<Account<'info, PairRegistry>>>,
+
+    /// Token A 的 mint
+    #[account(
+        mint::token_program = token_program
+    )]
+    pub mint_a: Box<InterfaceAccount<'info, Mint>>,
+
+    /// Token B 的 mint
+    #[account(
+        mint::token_program = token_program
+    )]
+    pub mint_b: Box<InterfaceAccount<'info, Mint>>,
+
+    /// Maker 的 Token A ATA（授权给 escrow PDA）
+    #[account(
+        mut,
+        associated_token::mint = mint_a,
+        associated_token::authority = maker,
+        associated_token::token_program = token_program
+    )]
+    pub maker_ata_a: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Programs
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> MakeDelegated<'info> {
+    /// 将 escrow PDA 设为 maker_ata_a 的 delegate，额度为 amount
+    fn approve_escrow(&self, amount: u64) -> Result<()> {
+        approve_checked(
+            CpiContext::new(
+                self.token_program.to_account_info(),
+                ApproveChecked {
+                    to: self.maker_ata_a.to_account_info(),
+                    mint: self.mint_a.to_account_info(),
+                    delegate: self.escrow.to_account_info(),
+                    authority: self.maker.to_account_info(),
+                },
+            ),
+            amount,
+            self.mint_a.decimals,
+        )?;
+
+        Ok(())
+    }
+
+    /// 累加 maker 创建 / 未关闭的 escrow 数量（首次创建时写入 maker 与 bump）
+    fn record_created(&mut self, bump: u8) -> Result<()> {
+        let stats = &mut self.maker_stats;
+        if stats.maker == Pubkey::default() {
+            stats.maker = self.maker.key();
+            stats.bump = bump;
+        }
+
+        stats.record_created()
+    }
+}
+
+/// MakeDelegated 指令入口
+///
+/// 参数说明见 MakeArgs
+pub fn handler(ctx: Context<MakeDelegated>, args: MakeArgs) -> Result<()> {
+    args.validate(ctx.accounts.mint_a.key(), ctx.accounts.mint_b.key())?;
+    require_transferable(&ctx.accounts.mint_a.to_account_info())?;
+    require_transferable(&ctx.accounts.mint_b.to_account_info())?;
+    require_no_permanent_delegate(&ctx.accounts.mint_a.to_account_info())?;
+    check_market_rules(
+        &ctx.accounts.config,
+        ctx.accounts.pair_registry.is_some(),
+        ctx.accounts.maker_stats.open_escrows,
+        &args,
+    )?;
+
+    require_depositable(&ctx.accounts.maker_ata_a, args.amount)?;
+
+    let created_at = Clock::get()?.unix_timestamp;
+    let mut escrow = args.to_escrow(
+        ctx.accounts.maker.key(),
+        &ctx.accounts.mint_a,
+        &ctx.accounts.mint_b,
+        created_at,
+        ctx.bumps.escrow,
+    );
+    escrow.is_delegated = true;
+    ctx.accounts.escrow.set_inner(escrow);
+
+    ctx.accounts.approve_escrow(args.amount)?;
+
+    ctx.accounts.record_created(ctx.bumps.maker_stats)?;
+
+    emit!(EscrowCreated {
+        escrow: ctx.accounts.escrow.key(),
+        maker: ctx.accounts.maker.key(),
+        mint_a: ctx.accounts.mint_a.key(),
+        mint_b: ctx.accounts.mint_b.key(),
+        deposit: args.amount,
+        receive: args.receive,
+        seed: args.seed,
+        created_at,
+        memo: args.memo,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,220 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_interface::{
+        Mint,
+        TokenAccount,
+        TokenInterface,
+        transfer_checked,
+        TransferChecked,
+    },
+};
+
+use crate::state::{Escrow, MakerStats, PairRegistry};
+use crate::events::EscrowCreated;
+use crate::instructions::make::{
+    check_market_rules,
+    require_depositable,
+    require_no_permanent_delegate,
+    require_transferable,
+    MakeArgs,
+};
+
+/// MakeFromAccount 指令：
+///
+/// 与 make 完全相同，但 Token A 可以从 maker 拥有的任意 token 账户（非 ATA，
+/// 例如旧账户或辅助账户）存入；vault 仍是 escrow 的 ATA。
+///
+/// refund 等仍把 Token A 退回 maker 的 ATA，而不是这里的来源账户
+#[derive(Accounts)]
+#[instruction(args: MakeArgs)]
+pub struct MakeFromAccount<'info> {
+    /// 创建者（maker），决定交易条款并存入 Token A
+    #[account(mut)]
+    pub maker: Signer<'info>,
+
+    /// Escrow PDA（seeds 与 make 相同）
+    #[account(
+        init,
+        payer = maker,
+        space = Escrow::INIT_SPACE + Escrow::DISCRIMINATOR.len(),
+        seeds = [b"escrow", maker.key().as_ref(), args.seed.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub escrow: Box<Account<'info, Escrow>>,
+
+    /// maker 统计 PDA（首次 make 时创建）
+    #[account(
+        init_if_needed,
+        payer = maker,
+        space = MakerStats::INIT_SPACE + MakerStats::DISCRIMINATOR.len(),
+        seeds = [b"stats", maker.key().as_ref()],
+        bump,
+    )]
+    pub maker_stats: Box<Account<'info, MakerStats>>,
+
+    /// CHECK: 全局 Config PDA（与 make 相同，未初始化时为空账户）
+    #[account(
+        seeds = [b"config"],
+        bump,
+    )]
+    pub config: UncheckedAccount<'info>,
+
+    /// 交易对白名单（config.restrict_pairs 为 true 时必须传入）
+    #[account(
+        seeds = [b"pair", mint_a.key().as_ref(), mint_b.key().as_ref()],
+        bump = pair_registry.bump,
+    )]
+    pub pair_registry: Option<Box<Account<'info, PairRegistry>>>,
+
+    /// Token A 的 mint
+    #[account(
+        mint::token_program = token_program
+    )]
+    pub mint_a: Box<InterfaceAccount<'info, Mint>>,
+
+    /// Token B 的 mint
+    #[account(
+        mint::token_program = token_program
+    )]
+    pub mint_b: Box<InterfaceAccount<'info, Mint>>,
+
+    /// Maker 拥有的 Token A 账户（不要求是 ATA）
+    #[account(
+        mut,
+        token::mint = mint_a,
+        token::authority = maker,
+        token::token_program = token_program,
+    )]
+    pub source_a: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Vault：Escrow 拥有的 Token A ATA
+    #[account(
+        init,
+        payer = maker,
+        associated_token::mint = mint_a,
+        associated_token::authority = escrow,
+        associated_token::token_program = token_program
+    )]
+    pub vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Programs
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> MakeFromAccount<'info> {
+    /// 将来源账户中的 Token A 存入 Vault
+    fn deposit_tokens(&self, amount: u64) -> Result<()> {
+        transfer_checked(
+            CpiContext::new(
+                self.token_program.to_account_info(),
+                TransferChecked {
+                    from: self.source_a.to_account_info(),
+                    mint: self.mint_a.to_account_info(),
+                    to: self.vault.to_account_info(),
+                    authority: self.maker.to_account_info(),
+                },
+            ),
+            amount,
+            self.mint_a.decimals,
+        )?;
+
+        Ok(())
+    }
+
+    /// 累加 maker 创建 / 未关闭的 escrow 数量（首次创建时写入 maker 与 bump）
+    fn record_created(&mut self, bump: u8) -> Result<()> {
+        let stats = &mut self.maker_stats;
+        if stats.maker == Pubkey::default() {
+            stats.maker = self.maker.key();
+            stats.bump = bump;
+        }
+
+        stats.record_created()
+    }
+}
+
+/// MakeFromAccount 指令入口
+///
+/// 参数说明见 MakeArgs
+pub fn handler(ctx: Context<MakeFromAccount>, args: MakeArgs) -> Result<()> {
+    args.validate(ctx.accounts.mint_a.key(), ctx.accounts.mint_b.key())?;
+    require_transferable(&ctx.accounts.mint_a.to_account_info())?;
+    require_transferable(&ctx.accounts.mint_b.to_account_info())?;
+    require_no_permanent_delegate(&ctx.accounts.mint_a.to_account_info())?;
+    check_market_rules(
+        &ctx.accounts.config,
+        ctx.accounts.pair_registry.is_some(),
+        ctx.accounts.maker_stats.open_escrows,
+        &args,
+    )?;
+
+    // 提前校验来源账户余额是否足够、是否被冻结
+    require_depositable(&ctx.accounts.source_a, args.amount)?;
+
+    let created_at = Clock::get()?.unix_timestamp;
+    let escrow = args.to_escrow(
+        ctx.accounts.maker.key(),
+        &ctx.accounts.mint_a,
+        &ctx.accounts.mint_b,
+        created_at,
+        ctx.bumps.escrow,
+    );
+    ctx.accounts.escrow.set_inner(escrow);
+
+    ctx.accounts.deposit_tokens(args.amount)?;
+
+    ctx.accounts.record_created(ctx.bumps.maker_stats)?;
+
+    emit!(EscrowCreated {
+        escrow: ctx.accounts.escrow.key(),
+        maker: ctx.accounts.maker.key(),
+        mint_a: ctx.accounts.mint_a.key(),
+        mint_b: ctx.accounts.mint_b.key(),
+        deposit: args.amount,
+        receive: args.receive,
+        seed: args.seed,
+        created_at,
+        memo: args.memo,
+    });
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::errors::EscrowError;
+    use crate::test_utils::*;
+
+    /// maker 拥有、但地址不是 ATA 的 Token A 账户
+    fn non_ata_source(maker: &Pubkey, mint_a: &TestAccount, amount: u64) -> TestAccount {
+        TestAccount {
+            key: Pubkey::new_unique(),
+            ..fund_ata(maker, mint_a, amount)
+        }
+    }
+
+    #[test]
+    fn deposits_from_non_ata_source() {
+        let maker = Pubkey::new_unique();
+        let (mint_a, _) = setup_mints(6, 6);
+        let source = non_ata_source(&maker, &mint_a, 100);
+        assert_ne!(source.key, fund_ata(&maker, &mint_a, 0).key);
+
+        // 来源账户只需属于 maker、mint 为 mint_a
+        let source_a = load_interface_account::<TokenAccount>(source);
+        assert_eq!((source_a.owner, source_a.mint), (maker, mint_a.key));
+        assert!(require_depositable(&source_a, 100).is_ok());
+
+        let short = load_interface_account::<TokenAccount>(non_ata_source(&maker, &mint_a, 99));
+        assert_err(require_depositable(&short, 100), EscrowError::InsufficientFunds);
+        let frozen = freeze(non_ata_source(&maker, &mint_a, 100));
+        assert_err(
+            require_depositable(&load_interface_account::<TokenAccount>(frozen), 100),
+            EscrowError::AccountFrozen,
+        );
+    }
+}
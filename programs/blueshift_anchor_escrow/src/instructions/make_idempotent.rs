@@ -0,0 +1,93 @@
+use anchor_lang::prelude::*;
+
+use crate::state::Escrow;
+use crate::errors::EscrowError;
+use crate::instructions::make::{MadeEscrow, MakeArgs};
+use crate::instructions::make_checked::{self, MakeChecked};
+
+/// MakeIdempotent 指令入口
+///
+/// 账户与 make_checked 相同，便于客户端在网络超时后安全重试：
+/// - escrow 不存在：与 make_checked 相同，创建并存入 Token A
+/// - escrow 已存在且条款与 args 完全一致（即上一次请求已上链）：不做任何操作，直接成功
+/// - escrow 已存在但条款不同（包括已被成交 / update 过）：返回 EscrowExistsWithDifferentTerms
+///
+/// 两种成功情况都通过 return data 返回 escrow 地址与 bump
+pub fn handler(ctx: Context<MakeChecked>, args: MakeArgs) -> Result<MadeEscrow> {
+    let made = MadeEscrow {
+        escrow: ctx.accounts.escrow.key(),
+        bump: ctx.bumps.escrow,
+    };
+
+    if ctx.accounts.is_fresh_escrow()? {
+        make_checked::handler(ctx, args)?;
+        return Ok(made);
+    }
+
+    // 用已存在 escrow 的 created_at 构造期望状态，其余字段必须逐一相同
+    let existing: &Escrow = &ctx.accounts.escrow;
+    let expected = args.to_escrow(
+        ctx.accounts.maker.key(),
+        &ctx.accounts.mint_a,
+        &ctx.accounts.mint_b,
+        existing.created_at,
+        ctx.bumps.escrow,
+    );
+    require!(
+        *existing == expected,
+        EscrowError::EscrowExistsWithDifferentTerms
+    );
+
+    msg!("make_idempotent: escrow={} already exists", made.escrow);
+
+    Ok(made)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::*;
+
+    const MAKE_IDEMPOTENT: u8 = 29;
+
+    /// 重放 make_idempotent(args)，escrow 为上一次请求已创建的账户
+    fn replay(
+        maker: &Pubkey,
+        existing: &Escrow,
+        mints: &(TestAccount, TestAccount),
+    ) -> ProgramResult {
+        set_clock(2_000, 2);
+        let (mint_a, mint_b) = mints;
+        let accounts = make_checked_accounts(maker, existing, mint_a, mint_b);
+
+        run(&ix_data(MAKE_IDEMPOTENT, make_args()), accounts).0
+    }
+
+    /// 上一次 make_idempotent(make_args()) 上链后的 escrow
+    fn landed(maker: &Pubkey, (mint_a, mint_b): &(TestAccount, TestAccount)) -> Escrow {
+        made_escrow(maker, mint_a, mint_b)
+    }
+
+    #[test]
+    fn replay_with_matching_terms_succeeds() {
+        let maker = Pubkey::new_unique();
+        let mints = setup_mints(6, 6);
+
+        assert_eq!(replay(&maker, &landed(&maker, &mints), &mints), Ok(()));
+    }
+
+    #[test]
+    fn replay_with_different_terms_is_rejected() {
+        let maker = Pubkey::new_unique();
+        let mints = setup_mints(6, 6);
+
+        // 例如上一次请求之后被部分成交或 update 过
+        let mut existing = landed(&maker, &mints);
+        existing.receive = 60;
+
+        assert_error(
+            replay(&maker, &existing, &mints),
+            EscrowError::EscrowExistsWithDifferentTerms,
+        );
+    }
+}
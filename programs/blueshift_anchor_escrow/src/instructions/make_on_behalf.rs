@@ -0,0 +1,257 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_interface::{
+        Mint,
+        TokenAccount,
+        TokenInterface,
+        transfer_checked,
+        TransferChecked,
+    },
+};
+
+use crate::state::{Escrow, MakerStats, PairRegistry};
+use crate::errors::EscrowError;
+use crate::events::EscrowCreated;
+use crate::instructions::make::{
+    check_market_rules,
+    require_depositable,
+    require_no_permanent_delegate,
+    require_transferable,
+    MakeArgs,
+};
+
+/// MakeOnBehalf 指令：
+///
+/// 与 make 相同的交易条款，但由 funder 代替 maker 发起：
+/// - funder 必须是 maker_ata_a 的 delegate，且授权额度不低于 amount，以 delegate 身份把 Token A 存入 vault
+/// - funder 支付 escrow / vault 的租金（如需取回请在 args.rent_collector 中填写 funder）
+/// - maker 无需签名，但 escrow 归属 maker：refund / update 等权利与普通 make 完全相同
+#[derive(Accounts)]
+#[instruction(args: MakeArgs)]
+pub struct MakeOnBehalf<'info> {
+    /// 代为创建 escrow 的 delegate，支付租金
+    #[account(mut)]
+    pub funder: Signer<'info>,
+
+    /// escrow 的所有者（不签名）
+    pub maker: SystemAccount<'info>,
+
+    /// Escrow PDA（seeds 与 make 相同，使用 maker 而非 funder）
+    #[account(
+        init,
+        payer = funder,
+        space = Escrow::INIT_SPACE + Escrow::DISCRIMINATOR.len(),
+        seeds = [b"escrow", maker.key().as_ref(), args.seed.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub escrow: Box<Account<'info, Escrow>>,
+
+    /// maker 统计 PDA（首次 make 时创建）
+    #[account(
+        init_if_needed,
+        payer = funder,
+        space = MakerStats::INIT_SPACE + MakerStats::DISCRIMINATOR.len(),
+        seeds = [b"stats", maker.key().as_ref()],
+        bump,
+    )]
+    pub maker_stats: Box<Account<'info, MakerStats>>,
+
+    /// CHECK: 全局 Config PDA（与 make 相同，未初始化时为空账户）
+    #[account(
+        seeds = [b"config"],
+        bump,
+    )]
+    pub config: UncheckedAccount<'info>,
+
+    /// 交易对白名单（config.restrict_pairs 为 true 时必须传入）
+    #[account(
+        seeds = [b"pair", mint_a.key().as_ref(), mint_b.key().as_ref()],
+        bump = pair_registry.bump,
+    )]
+    pub pair_registry: Option<Box<Account<'info, PairRegistry>>>,
+
+    /// Token A 的 mint
+    #[account(
+        mint::token_program = token_program
+    )]
+    pub mint_a: Box<InterfaceAccount<'info, Mint>>,
+
+    /// Token B 的 mint
+    #[account(
+        mint::token_program = token_program
+    )]
+    pub mint_b: Box<InterfaceAccount<'info, Mint>>,
+
+    /// Maker 的 Token A ATA（delegate = funder）
+    #[account(
+        mut,
+        associated_token::mint = mint_a,
+        associated_token::authority = maker,
+        associated_token::token_program = token_program
+    )]
+    pub maker_ata_a: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Vault：Escrow 拥有的 Token A ATA
+    #[account(
+        init,
+        payer = funder,
+        associated_token::mint = mint_a,
+        associated_token::authority = escrow,
+        associated_token::token_program = token_program
+    )]
+    pub vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Programs
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> MakeOnBehalf<'info> {
+    /// funder 以 delegate 身份将 maker 的 Token A 存入 Vault
+    fn deposit_tokens(&self, amount: u64) -> Result<()> {
+        transfer_checked(
+            CpiContext::new(
+                self.token_program.to_account_info(),
+                TransferChecked {
+                    from: self.maker_ata_a.to_account_info(),
+                    mint: self.mint_a.to_account_info(),
+                    to: self.vault.to_account_info(),
+                    authority: self.funder.to_account_info(),
+                },
+            ),
+            amount,
+            self.mint_a.decimals,
+        )?;
+
+        Ok(())
+    }
+
+    /// 累加 maker 创建 / 未关闭的 escrow 数量（首次创建时写入 maker 与 bump）
+    fn record_created(&mut self, bump: u8) -> Result<()> {
+        let stats = &mut self.maker_stats;
+        if stats.maker == Pubkey::default() {
+            stats.maker = self.maker.key();
+            stats.bump = bump;
+        }
+
+        stats.record_created()
+    }
+}
+
+/// funder 必须是 maker 授权的 delegate，且额度不低于 amount
+pub fn require_delegate(maker_ata_a: &TokenAccount, funder: &Pubkey, amount: u64) -> Result<()> {
+    if maker_ata_a.delegate != Some(*funder).into() || maker_ata_a.delegated_amount < amount {
+        msg!(
+            "InvalidDelegate: expected delegate {} for {}, got {:?} for {}",
+            funder,
+            amount,
+            Option::<Pubkey>::from(maker_ata_a.delegate),
+            maker_ata_a.delegated_amount
+        );
+        return err!(EscrowError::InvalidDelegate);
+    }
+
+    Ok(())
+}
+
+/// MakeOnBehalf 指令入口
+///
+/// 参数说明见 MakeArgs
+pub fn handler(ctx: Context<MakeOnBehalf>, args: MakeArgs) -> Result<()> {
+    args.validate(ctx.accounts.mint_a.key(), ctx.accounts.mint_b.key())?;
+    require_transferable(&ctx.accounts.mint_a.to_account_info())?;
+    require_transferable(&ctx.accounts.mint_b.to_account_info())?;
+    require_no_permanent_delegate(&ctx.accounts.mint_a.to_account_info())?;
+    check_market_rules(
+        &ctx.accounts.config,
+        ctx.accounts.pair_registry.is_some(),
+        ctx.accounts.maker_stats.open_escrows,
+        &args,
+    )?;
+
+    require_delegate(&ctx.accounts.maker_ata_a, ctx.accounts.funder.key, args.amount)?;
+    require_depositable(&ctx.accounts.maker_ata_a, args.amount)?;
+
+    let created_at = Clock::get()?.unix_timestamp;
+    let escrow = args.to_escrow(
+        ctx.accounts.maker.key(),
+        &ctx.accounts.mint_a,
+        &ctx.accounts.mint_b,
+        created_at,
+        ctx.bumps.escrow,
+    );
+    ctx.accounts.escrow.set_inner(escrow);
+
+    ctx.accounts.deposit_tokens(args.amount)?;
+
+    ctx.accounts.record_created(ctx.bumps.maker_stats)?;
+
+    emit!(EscrowCreated {
+        escrow: ctx.accounts.escrow.key(),
+        maker: ctx.accounts.maker.key(),
+        mint_a: ctx.accounts.mint_a.key(),
+        mint_b: ctx.accounts.mint_b.key(),
+        deposit: args.amount,
+        receive: args.receive,
+        seed: args.seed,
+        created_at,
+        memo: args.memo,
+    });
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::*;
+
+    #[test]
+    fn funder_must_be_approved_delegate() {
+        let (maker, funder) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let (mint_a, _) = setup_mints(6, 6);
+        let maker_ata_a = |delegate: &Pubkey, allowance| {
+            let account = approve(fund_ata(&maker, &mint_a, 1_000), delegate, allowance);
+            load_interface_account::<TokenAccount>(account)
+        };
+
+        // 已授权且额度足够
+        assert!(require_delegate(&maker_ata_a(&funder, 100), &funder, 100).is_ok());
+
+        // 额度不足、授权给了别人、未授权
+        assert_err(
+            require_delegate(&maker_ata_a(&funder, 99), &funder, 100),
+            EscrowError::InvalidDelegate,
+        );
+        assert_err(
+            require_delegate(&maker_ata_a(&Pubkey::new_unique(), 100), &funder, 100),
+            EscrowError::InvalidDelegate,
+        );
+        let undelegated = load_interface_account::<TokenAccount>(fund_ata(&maker, &mint_a, 1_000));
+        assert_err(require_delegate(&undelegated, &funder, 100), EscrowError::InvalidDelegate);
+    }
+
+    #[test]
+    fn invalid_delegate_logs_expected_and_actual() {
+        let funder = Pubkey::new_from_array([3; 32]);
+        let other = Pubkey::new_from_array([4; 32]);
+        let (mint_a, _) = setup_mints(6, 6);
+        let maker_ata_a = approve(fund_ata(&Pubkey::new_unique(), &mint_a, 1_000), &other, 100);
+
+        let test = "instructions::make_on_behalf::tests::invalid_delegate_logs_expected_and_actual";
+        let Some(logs) = program_logs(test, || {
+            let maker_ata_a = load_interface_account::<TokenAccount>(maker_ata_a);
+            assert_err(
+                require_delegate(&maker_ata_a, &funder, 100),
+                EscrowError::InvalidDelegate,
+            );
+        }) else {
+            return;
+        };
+
+        assert!(logs.contains(&format!("expected delegate {funder}")));
+        assert!(logs.contains(&format!("got Some({other})")));
+    }
+}
@@ -0,0 +1,108 @@
+use anchor_lang::prelude::*;
+
+use crate::state::Escrow;
+use crate::errors::EscrowError;
+use crate::instructions::make::MakeArgs;
+use crate::instructions::make_checked::{self, MakeChecked};
+
+/// MakeResume 指令入口
+///
+/// 账户与 make_checked 相同（vault 为 init_if_needed）。make 对 vault 使用 init，
+/// 若 vault ATA 已被提前创建（任何人都可以为 escrow PDA 创建 ATA）则 make 无法完成；
+/// 本指令把 vault 中已有的 Token A 计入存入，只从 maker 转入缺少的部分：
+/// - escrow 不存在：校验并创建 escrow，转入 args.amount - vault 余额
+/// - escrow 已存在且条款与 args 一致：转入 escrow.deposit - vault 余额（通常为 0）
+/// - escrow 已存在但条款不同：返回 EscrowExistsWithDifferentTerms
+///
+/// vault 余额超过应存入数量时返回 VaultBalanceMismatch，不会重复存入
+pub fn handler(ctx: Context<MakeChecked>, args: MakeArgs) -> Result<()> {
+    let vault_balance = ctx.accounts.vault.amount;
+
+    if ctx.accounts.is_fresh_escrow()? {
+        let missing = args
+            .amount
+            .checked_sub(vault_balance)
+            .ok_or(error!(EscrowError::VaultBalanceMismatch))?;
+        msg!("make_resume: vault={} missing={}", vault_balance, missing);
+
+        return make_checked::create_escrow(ctx, missing, args);
+    }
+
+    // 用已存在 escrow 的 created_at 构造期望状态，其余字段必须逐一相同
+    let existing: &Escrow = &ctx.accounts.escrow;
+    let expected = args.to_escrow(
+        ctx.accounts.maker.key(),
+        &ctx.accounts.mint_a,
+        &ctx.accounts.mint_b,
+        existing.created_at,
+        ctx.bumps.escrow,
+    );
+    require!(
+        *existing == expected,
+        EscrowError::EscrowExistsWithDifferentTerms
+    );
+
+    let missing = existing
+        .deposit
+        .checked_sub(vault_balance)
+        .ok_or(error!(EscrowError::VaultBalanceMismatch))?;
+    msg!("make_resume: vault={} missing={}", vault_balance, missing);
+
+    if missing > 0 {
+        require!(
+            ctx.accounts.maker_ata_a.amount >= missing,
+            EscrowError::InsufficientFunds
+        );
+        ctx.accounts.deposit_tokens(missing)?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::*;
+
+    const MAKE_RESUME: u8 = 35;
+
+    /// 重试 make_resume(make_args())：上一次请求已创建报价为 receive 的 escrow，
+    /// vault 中有 vault_balance 个 Token A，maker 还持有 maker_balance 个
+    fn resume(receive: u64, vault_balance: u64, maker_balance: u64) -> ProgramResult {
+        set_clock(2_000, 2);
+        let maker = Pubkey::new_unique();
+        let (mint_a, mint_b) = setup_mints(6, 6);
+        let existing = Escrow {
+            receive,
+            ..made_escrow(&maker, &mint_a, &mint_b)
+        };
+        let mut accounts = make_checked_accounts(&maker, &existing, &mint_a, &mint_b);
+        let escrow_key = accounts[1].key;
+        accounts[7] = fund_ata(&maker, &mint_a, maker_balance).writable();
+        accounts[8] = fund_ata(&escrow_key, &mint_a, vault_balance).writable();
+
+        run(&ix_data(MAKE_RESUME, make_args()), accounts).0
+    }
+
+    #[test]
+    fn completed_make_is_not_deposited_twice() {
+        // 上一次请求已全部存入：无需 maker 再转入
+        assert_eq!(resume(50, 100, 0), Ok(()));
+    }
+
+    #[test]
+    fn resume_tops_up_only_missing_deposit() {
+        // 缺少 40：maker 只需持有 40（此处差 1，在转账之前失败）
+        assert_error(resume(50, 60, 39), EscrowError::InsufficientFunds);
+    }
+
+    #[test]
+    fn vault_above_deposit_is_rejected() {
+        assert_error(resume(50, 101, 1_000), EscrowError::VaultBalanceMismatch);
+    }
+
+    #[test]
+    fn resume_with_different_terms_is_rejected() {
+        assert_error(resume(60, 100, 1_000), EscrowError::EscrowExistsWithDifferentTerms);
+    }
+}
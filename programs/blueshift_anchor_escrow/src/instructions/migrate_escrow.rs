@@ -0,0 +1,401 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+
+use crate::state::{transfer_lamports_rent_safe, Escrow};
+use crate::errors::EscrowError;
+
+/// MigrateEscrow 指令：
+///
+/// Escrow 新增字段后，之前创建的账户数据较短，无法按新布局反序列化。
+/// 本指令按数据长度识别旧布局（见 LEGACY_LAYOUTS），扩容到当前 INIT_SPACE 并写入当前 version：
+/// - 旧布局中已有的字段保持原值
+/// - 旧布局中没有的字段取默认值：creator = maker，fee_bps_override = FEE_BPS_USE_GLOBAL，
+///   decimals 取自 mint，deposit 取 vault 余额，其余为 0（即关闭对应功能）
+/// - 扩容所需的租金由 maker 支付
+///
+/// 只有 maker 可以迁移自己的 escrow，已是当前版本时直接成功（幂等）
+#[derive(Accounts)]
+pub struct MigrateEscrow<'info> {
+    /// Maker：escrow 的所有者，支付扩容租金
+    #[account(mut)]
+    pub maker: Signer<'info>,
+
+    /// CHECK: 旧布局无法按 Account<Escrow> 反序列化；
+    /// owner、discriminator、maker 与 PDA 地址在 handler 中校验
+    #[account(mut)]
+    pub escrow: UncheckedAccount<'info>,
+
+    /// Token A 的 mint（补齐 mint_a_decimals）
+    #[account(
+        mint::token_program = token_program
+    )]
+    pub mint_a: Box<InterfaceAccount<'info, Mint>>,
+
+    /// Token B 的 mint（补齐 mint_b_decimals）
+    pub mint_b: Box<InterfaceAccount<'info, Mint>>,
+
+    /// Vault：escrow 持有的 Token A（补齐 deposit）
+    #[account(
+        associated_token::mint = mint_a,
+        associated_token::authority = escrow,
+        associated_token::token_program = token_program
+    )]
+    pub vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Programs
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+/// version 字段出现之前的 Escrow 布局
+///
+/// 每个旧版本都是当前字段顺序的前缀，末尾为 bump；唯一的例外是 maker 与 seed 的顺序：
+/// maker 移到最前（MAKER_OFFSET）之前，seed 紧跟 discriminator。
+/// 410 字节的布局两种顺序都出现过，由 escrow 的 PDA 地址区分
+#[derive(Clone, Copy)]
+struct LegacyLayout {
+    /// 账户数据长度（含 1 字节 discriminator 与末尾的 bump）
+    len: usize,
+    /// maker 是否位于 seed 之前
+    maker_first: bool,
+}
+
+impl LegacyLayout {
+    const fn seed_first(len: usize) -> Self {
+        Self { len, maker_first: false }
+    }
+
+    const fn maker_first(len: usize) -> Self {
+        Self { len, maker_first: true }
+    }
+
+    /// 是否包含当前布局中结束于 end（不含 bump）的字段
+    fn has_field_ending_at(&self, end: usize) -> bool {
+        self.len > end
+    }
+
+    /// 转换为当前布局的数据：缺失字段补 0，bump 移到末尾，maker 移到 seed 之前
+    fn upgrade(&self, data: &[u8], new_len: usize) -> Vec<u8> {
+        let mut upgraded = data[..self.len - 1].to_vec();
+        upgraded.resize(new_len, 0);
+        upgraded[new_len - 1] = data[self.len - 1];
+
+        // [seed u64 | maker] -> [maker | seed u64]
+        if !self.maker_first {
+            let start = Escrow::DISCRIMINATOR.len();
+            upgraded[start..start + 8 + 32].rotate_left(8);
+        }
+
+        upgraded
+    }
+}
+
+/// 按时间顺序排列的旧布局（长度 = 新增字段在当前布局中的结束位置 + 1 字节 bump）
+const LEGACY_LAYOUTS: [LegacyLayout; 26] = [
+    LegacyLayout::seed_first(114),  // 初始版本：seed, maker, mint_a, mint_b, receive
+    LegacyLayout::seed_first(122),  // + deposit
+    LegacyLayout::seed_first(130),  // + expiry
+    LegacyLayout::seed_first(138),  // + min_hold_until
+    LegacyLayout::seed_first(170),  // + allowed_taker
+    LegacyLayout::seed_first(171),  // + status
+    LegacyLayout::seed_first(179),  // + created_at
+    LegacyLayout::seed_first(212),  // + 荷兰拍参数
+    LegacyLayout::seed_first(228),  // + min_fill / max_fill
+    LegacyLayout::seed_first(268),  // + mint_b_alt / receive_alt
+    LegacyLayout::seed_first(300),  // + rent_collector
+    LegacyLayout::seed_first(301),  // + is_delegated
+    LegacyLayout::seed_first(303),  // + mint_a_decimals / mint_b_decimals
+    LegacyLayout::seed_first(335),  // + memo
+    LegacyLayout::seed_first(367),  // + creator
+    LegacyLayout::seed_first(407),  // + mint_a2 / deposit2
+    LegacyLayout::seed_first(408),  // + in_progress
+    LegacyLayout::seed_first(410),  // + fee_bps_override
+    LegacyLayout::maker_first(410), // maker 移到 seed 之前
+    LegacyLayout::maker_first(442), // + vault_rent_payer
+    LegacyLayout::maker_first(474), // + proceeds_recipient
+    LegacyLayout::maker_first(475), // + round_up_receive
+    LegacyLayout::maker_first(492), // + is_ratio / price_num / price_den
+    LegacyLayout::maker_first(493), // + maker_ata_b_ready
+    LegacyLayout::maker_first(501), // + reserved
+    LegacyLayout::maker_first(541), // + gate_mint / gate_min_balance
+];
+
+/// 以下字段在当前布局中的结束位置（用于判断旧布局是否已包含该字段）
+const DEPOSIT_END: usize = 121;
+const DECIMALS_END: usize = 302;
+const CREATOR_END: usize = 366;
+const FEE_BPS_OVERRIDE_END: usize = 409;
+
+/// 按 layout 解码旧账户，返回当前布局的 escrow；PDA 地址与 escrow 不符时返回 None
+fn decode_legacy(
+    layout: &LegacyLayout,
+    data: &[u8],
+    new_len: usize,
+    address: &Pubkey,
+) -> Result<Option<Escrow>> {
+    let mut escrow = Escrow::try_deserialize(&mut &layout.upgrade(data, new_len)[..])?;
+
+    // creator 字段之前的布局中 escrow 不可能转让过，PDA 由 maker 推导
+    if !layout.has_field_ending_at(CREATOR_END) {
+        escrow.creator = escrow.maker;
+    }
+
+    let seed_bytes = escrow.seed.to_le_bytes();
+    let matches = Pubkey::create_program_address(&escrow.signer_seeds(&seed_bytes), &crate::ID)
+        .is_ok_and(|pda| pda == *address);
+
+    Ok(matches.then_some(escrow))
+}
+
+/// MigrateEscrow 指令入口
+pub fn handler(ctx: Context<MigrateEscrow>) -> Result<()> {
+    let accounts = &ctx.accounts;
+    let escrow_info = accounts.escrow.to_account_info();
+    let new_len = Escrow::DISCRIMINATOR.len() + Escrow::INIT_SPACE;
+
+    require!(
+        escrow_info.owner == &crate::ID
+            && escrow_info.try_borrow_data()?.starts_with(Escrow::DISCRIMINATOR),
+        EscrowError::InvalidEscrowAccount
+    );
+
+    let old_len = escrow_info.data_len();
+    if old_len == new_len {
+        let escrow = Escrow::try_deserialize(&mut &escrow_info.try_borrow_data()?[..])?;
+        require_keys_eq!(escrow.maker, accounts.maker.key(), EscrowError::InvalidMaker);
+
+        msg!(
+            "migrate_escrow: escrow={} already at version {}",
+            escrow_info.key(),
+            escrow.version
+        );
+        return Ok(());
+    }
+
+    // =======================
+    // 按数据长度识别旧布局（410 字节时按 PDA 地址区分 maker / seed 的顺序）
+    // =======================
+    let mut decoded = None;
+    {
+        let data = escrow_info.try_borrow_data()?;
+        for layout in LEGACY_LAYOUTS.iter().filter(|layout| layout.len == old_len) {
+            if let Some(escrow) = decode_legacy(layout, &data, new_len, escrow_info.key)? {
+                decoded = Some((*layout, escrow));
+                break;
+            }
+        }
+    }
+    let (layout, mut escrow) = decoded.ok_or(error!(EscrowError::InvalidEscrowAccount))?;
+
+    require_keys_eq!(escrow.maker, accounts.maker.key(), EscrowError::InvalidMaker);
+    require_keys_eq!(accounts.mint_a.key(), escrow.mint_a, EscrowError::InvalidMintA);
+    require_keys_eq!(accounts.mint_b.key(), escrow.mint_b, EscrowError::InvalidMintB);
+
+    // =======================
+    // 旧布局中没有的字段取默认值
+    // =======================
+    if !layout.has_field_ending_at(DEPOSIT_END) {
+        escrow.deposit = accounts.vault.amount;
+    }
+    if !layout.has_field_ending_at(DECIMALS_END) {
+        escrow.mint_a_decimals = accounts.mint_a.decimals;
+        escrow.mint_b_decimals = accounts.mint_b.decimals;
+    }
+    if !layout.has_field_ending_at(FEE_BPS_OVERRIDE_END) {
+        escrow.fee_bps_override = Escrow::FEE_BPS_USE_GLOBAL;
+    }
+    escrow.version = Escrow::CURRENT_VERSION;
+
+    // =======================
+    // 扩容（maker 补足租金）并写回
+    // =======================
+    let rent = Rent::get()?
+        .minimum_balance(new_len)
+        .saturating_sub(escrow_info.lamports());
+    if rent > 0 {
+        transfer_lamports_rent_safe(
+            &accounts.system_program,
+            accounts.maker.to_account_info(),
+            escrow_info.clone(),
+            rent,
+        )?;
+    }
+    escrow_info.resize(new_len)?;
+
+    escrow.try_serialize(&mut &mut escrow_info.try_borrow_mut_data()?[..])?;
+
+    msg!(
+        "migrate_escrow: escrow={} {} -> {} bytes, version {}",
+        escrow_info.key(),
+        old_len,
+        new_len,
+        Escrow::CURRENT_VERSION
+    );
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use anchor_spl::token::spl_token;
+
+    use super::*;
+    use crate::state::EscrowStatus;
+    use crate::test_utils::*;
+
+    const MIGRATE_ESCROW: u8 = 43;
+
+    struct Legacy {
+        escrow: Escrow,
+        mint_a: TestAccount,
+        mint_b: TestAccount,
+    }
+
+    impl Legacy {
+        fn new() -> Self {
+            let (mint_a, mint_b) = setup_mints(6, 9);
+            let escrow = make_escrow(&Pubkey::new_unique(), 7, &mint_a, &mint_b, 100, 50);
+
+            Self { escrow, mint_a, mint_b }
+        }
+
+        /// 当前布局的前 len - 1 字节 + bump（maker 在 seed 之前）
+        fn maker_first(&self, len: usize) -> Vec<u8> {
+            let mut data = Vec::new();
+            self.escrow.try_serialize(&mut data).unwrap();
+            data.truncate(len - 1);
+            data.push(self.escrow.bump);
+
+            data
+        }
+
+        /// 同 maker_first，但 seed 在 maker 之前
+        fn seed_first(&self, len: usize) -> Vec<u8> {
+            let mut data = self.maker_first(len);
+            data[1..41].rotate_right(8);
+
+            data
+        }
+
+        /// 以 data 为 escrow 账户数据执行迁移，返回结果与迁移后的账户数据
+        ///
+        /// escrow 的 lamports 已满足扩容后的免租要求，迁移不需要转账
+        fn migrate(&self, data: Vec<u8>) -> (ProgramResult, Vec<u8>) {
+            set_clock(1_000, 1);
+            let escrow = escrow_account(&self.escrow);
+            let new_len = Escrow::DISCRIMINATOR.len() + Escrow::INIT_SPACE;
+
+            let (result, infos) = run(
+                &[MIGRATE_ESCROW],
+                vec![
+                    TestAccount::wallet(self.escrow.maker, 1_000_000_000).signer(),
+                    TestAccount {
+                        lamports: Rent::default().minimum_balance(new_len),
+                        ..TestAccount::new(escrow.key, crate::ID, data).writable()
+                    },
+                    self.mint_a.clone(),
+                    self.mint_b.clone(),
+                    fund_ata(&escrow.key, &self.mint_a, 80),
+                    TestAccount::program(spl_token::ID),
+                    TestAccount::program(anchor_lang::system_program::ID),
+                ],
+            );
+            let data = infos[1].data.borrow().to_vec();
+
+            (result, data)
+        }
+    }
+
+    #[test]
+    fn layouts_match_current_field_offsets() {
+        assert_eq!(LEGACY_LAYOUTS[1].len, DEPOSIT_END + 1);
+        assert_eq!(LEGACY_LAYOUTS[12].len, DECIMALS_END + 1);
+        assert_eq!(LEGACY_LAYOUTS[14].len, CREATOR_END + 1);
+        assert_eq!(LEGACY_LAYOUTS[17].len, FEE_BPS_OVERRIDE_END + 1);
+        // 当前布局 = 最后一个旧布局 + version
+        assert_eq!(
+            LEGACY_LAYOUTS[LEGACY_LAYOUTS.len() - 1].len + 1,
+            Escrow::DISCRIMINATOR.len() + Escrow::INIT_SPACE
+        );
+    }
+
+    #[test]
+    fn migrates_initial_layout() {
+        let legacy = Legacy::new();
+        let escrow = &legacy.escrow;
+
+        // 初始版本：disc(1) | seed | maker | mint_a | mint_b | receive | bump
+        let mut data = vec![1u8];
+        data.extend_from_slice(&escrow.seed.to_le_bytes());
+        data.extend_from_slice(escrow.maker.as_ref());
+        data.extend_from_slice(escrow.mint_a.as_ref());
+        data.extend_from_slice(escrow.mint_b.as_ref());
+        data.extend_from_slice(&escrow.receive.to_le_bytes());
+        data.push(escrow.bump);
+        assert_eq!(data.len(), LEGACY_LAYOUTS[0].len);
+
+        let (result, data) = legacy.migrate(data);
+
+        assert_eq!(result, Ok(()));
+        let migrated = Escrow::try_deserialize(&mut &data[..]).unwrap();
+        assert_eq!(migrated.seed, escrow.seed);
+        assert_eq!(migrated.maker, escrow.maker);
+        assert_eq!(migrated.creator, escrow.maker);
+        assert_eq!(migrated.mint_a, escrow.mint_a);
+        assert_eq!(migrated.mint_b, escrow.mint_b);
+        assert_eq!(migrated.receive, 50);
+        // deposit 取 vault 余额
+        assert_eq!(migrated.deposit, 80);
+        assert_eq!(migrated.mint_a_decimals, 6);
+        assert_eq!(migrated.mint_b_decimals, 9);
+        assert_eq!(migrated.fee_bps_override, Escrow::FEE_BPS_USE_GLOBAL);
+        assert!(migrated.status == EscrowStatus::Active);
+        assert_eq!(migrated.version, Escrow::CURRENT_VERSION);
+        assert_eq!(migrated.bump, escrow.bump);
+    }
+
+    /// 410 字节的两种布局（maker 移动前后）都能迁移，已有字段保持原值
+    #[test]
+    fn migrates_both_orders_of_410_byte_layout() {
+        let mut legacy = Legacy::new();
+        legacy.escrow.fee_bps_override = 25;
+        legacy.escrow.expiry = 5_000;
+        legacy.escrow.gate_min_balance = 9;
+
+        for data in [legacy.seed_first(410), legacy.maker_first(410)] {
+            let (result, data) = legacy.migrate(data);
+
+            assert_eq!(result, Ok(()));
+            let migrated = Escrow::try_deserialize(&mut &data[..]).unwrap();
+            assert_eq!(migrated.seed, legacy.escrow.seed);
+            assert_eq!(migrated.maker, legacy.escrow.maker);
+            assert_eq!(migrated.deposit, 100);
+            assert_eq!(migrated.expiry, 5_000);
+            assert_eq!(migrated.fee_bps_override, 25);
+            // 410 字节之后新增的字段取默认值
+            assert_eq!(migrated.gate_min_balance, 0);
+            assert_eq!(migrated.version, Escrow::CURRENT_VERSION);
+        }
+    }
+
+    #[test]
+    fn unknown_layout_is_rejected() {
+        let legacy = Legacy::new();
+
+        let (result, _) = legacy.migrate(legacy.maker_first(400));
+
+        assert_error(result, EscrowError::InvalidEscrowAccount);
+    }
+
+    #[test]
+    fn current_layout_is_left_unchanged() {
+        let legacy = Legacy::new();
+        let mut data = Vec::new();
+        legacy.escrow.try_serialize(&mut data).unwrap();
+
+        let (result, migrated) = legacy.migrate(data.clone());
+
+        assert_eq!(result, Ok(()));
+        assert_eq!(migrated, data);
+    }
+}
@@ -1,7 +1,83 @@
 pub mod make;
 pub mod take;
 pub mod refund;
+pub mod initialize_config;
+pub mod update;
+pub mod top_up;
+pub mod take_with_sol;
+pub mod refund_to;
+pub mod batch_take;
+pub mod sweep_stranded;
+pub mod pause;
+pub mod quote;
+pub mod close_escrow;
+pub mod make_delegated;
+pub mod take_delegated;
+pub mod cancel_remainder;
+pub mod transfer_ownership;
+pub mod make_basket;
+pub mod take_basket;
+pub mod refund_basket;
+pub mod crank_refund;
+pub mod refund_native;
+pub mod make_checked;
+pub mod get_terms;
+pub mod partial_refund;
+pub mod add_pair;
+pub mod remove_pair;
+pub mod set_recipient;
+pub mod make_idempotent;
+pub mod commit_take;
+pub mod reveal_take;
+pub mod take_split;
+pub mod make_on_behalf;
+pub mod take_lamports;
+pub mod make_resume;
+pub mod can_take;
+pub mod make_from_account;
+pub mod refund_all;
+pub mod make_auto_seed;
+pub mod reserve;
+pub mod claim;
+pub mod release_reservation;
+pub mod migrate_escrow;
+pub mod cancel_commit;
 
 pub use make::*;
 pub use take::*;
 pub use refund::*;
+pub use initialize_config::*;
+pub use update::*;
+pub use top_up::*;
+pub use refund_to::*;
+pub use batch_take::*;
+pub use sweep_stranded::*;
+pub use pause::*;
+pub use quote::*;
+pub use close_escrow::*;
+pub use make_delegated::*;
+pub use take_delegated::*;
+pub use transfer_ownership::*;
+pub use make_basket::*;
+pub use take_basket::*;
+pub use refund_basket::*;
+pub use crank_refund::*;
+pub use make_checked::*;
+pub use get_terms::*;
+pub use partial_refund::*;
+pub use add_pair::*;
+pub use remove_pair::*;
+pub use set_recipient::*;
+pub use commit_take::*;
+pub use reveal_take::*;
+pub use take_split::*;
+pub use make_on_behalf::*;
+pub use take_lamports::*;
+pub use can_take::*;
+pub use make_from_account::*;
+pub use refund_all::*;
+pub use reserve::*;
+pub use claim::*;
+pub use release_reservation::*;
+pub use migrate_escrow::*;
+pub use cancel_commit::*;
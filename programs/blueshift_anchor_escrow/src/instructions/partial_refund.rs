@@ -0,0 +1,318 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::{get_associated_token_address_with_program_id, AssociatedToken},
+    token_interface::{
+        close_account,
+        Mint,
+        TokenAccount,
+        TokenInterface,
+        TransferChecked,
+        CloseAccount,
+    },
+};
+
+use crate::state::{transfer_lamports_rent_safe, Escrow, EscrowStatus, MakerStats};
+use crate::errors::EscrowError;
+use crate::events::EscrowPartiallyRefunded;
+use crate::instructions::refund::RefundTerms;
+use crate::instructions::take::transfer_checked_with_hook;
+
+/// PartialRefund 指令：
+///
+/// maker 撤回部分 Token A，剩余部分继续挂单（报价按比例缩小，单价不变）；
+/// 撤回后 deposit 为 0 时关闭 vault 与 escrow，并与 refund 一样收取撤单费
+#[derive(Accounts)]
+pub struct PartialRefund<'info> {
+    /// Maker: 创建 escrow 的人，退款的发起者
+    #[account(mut)]
+    pub maker: Signer<'info>,
+
+    /// Escrow PDA：存储交易条款（仅在全部撤回时手动关闭）
+    #[account(
+        mut,
+        seeds = [b"escrow", escrow.creator.as_ref(), escrow.seed.to_le_bytes().as_ref()],
+        bump = escrow.bump,
+        constraint = escrow.has_canonical_bump() @ EscrowError::InvalidBump,
+        has_one = maker @ EscrowError::InvalidMaker,
+        has_one = mint_a @ EscrowError::InvalidMintA,
+        constraint = !escrow.is_basket() @ EscrowError::BasketMismatch,
+        constraint = escrow.is_active() @ EscrowError::EscrowNotActive,
+        constraint = !escrow.in_progress @ EscrowError::ReentrancyDetected,
+    )]
+    pub escrow: Box<Account<'info, Escrow>>,
+
+    /// CHECK: 仅在全部撤回时接收关闭 vault / escrow 返还的 lamports，
+    /// 地址为 escrow.rent_collector（未设置时为 maker）
+    #[account(
+        mut,
+        address = escrow.rent_destination(escrow.maker),
+    )]
+    pub rent_collector: UncheckedAccount<'info>,
+
+    /// CHECK: 全局 Config PDA（与 make 相同，未初始化时为空账户），
+    /// 仅读取撤单费、treasury 与挂单数上限（见 RefundTerms::load）
+    #[account(
+        seeds = [b"config"],
+        bump,
+    )]
+    pub config: UncheckedAccount<'info>,
+
+    /// maker 统计 PDA（可选，全部撤回时累加 total_refunded；config 限制挂单数时必须传入）
+    #[account(
+        mut,
+        seeds = [b"stats", maker.key().as_ref()],
+        bump = maker_stats.bump,
+    )]
+    pub maker_stats: Option<Box<Account<'info, MakerStats>>>,
+
+    /// CHECK: 仅接收撤单费 lamports，config 已初始化时地址必须为 config.treasury
+    #[account(mut)]
+    pub treasury: UncheckedAccount<'info>,
+
+    /// Token A 的 mint
+    #[account(
+        mint::token_program = token_program,
+        constraint = mint_a.decimals == escrow.mint_a_decimals @ EscrowError::DecimalsMismatch,
+    )]
+    pub mint_a: Box<InterfaceAccount<'info, Mint>>,
+
+    /// Vault：escrow PDA 持有的 Token A
+    #[account(
+        mut,
+        associated_token::mint = mint_a,
+        associated_token::authority = escrow,
+        associated_token::token_program = token_program
+    )]
+    pub vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Maker 的 Token A ATA（接收退款）
+    #[account(
+        init_if_needed,
+        payer = maker,
+        associated_token::mint = mint_a,
+        associated_token::authority = maker,
+        associated_token::token_program = token_program,
+        address = get_associated_token_address_with_program_id(
+            &maker.key(),
+            &mint_a.key(),
+            &token_program.key(),
+        ) @ EscrowError::NonCanonicalAta,
+    )]
+    pub maker_ata_a: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Programs
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> PartialRefund<'info> {
+    /// maker 向 treasury 支付撤单费（lamports）
+    fn pay_cancel_fee(&mut self, fee: u64) -> Result<()> {
+        require!(
+            self.maker.lamports() >= fee,
+            EscrowError::InsufficientFunds
+        );
+
+        transfer_lamports_rent_safe(
+            &self.system_program,
+            self.maker.to_account_info(),
+            self.treasury.to_account_info(),
+            fee,
+        )?;
+
+        Ok(())
+    }
+
+    /// 从 Vault 中把 amount 个 Token A 转回给 Maker；vault 清空时一并关闭
+    fn withdraw(&mut self, amount: u64, remaining: &[AccountInfo<'info>]) -> Result<()> {
+        // Escrow PDA 作为 Vault 的 authority，需要 signer seeds
+        let seed_bytes = self.escrow.seed.to_le_bytes();
+        let seeds = self.escrow.signer_seeds(&seed_bytes);
+        let signer_seeds: [&[&[u8]]; 1] = [&seeds];
+
+        msg!(
+            "partial_refund: escrow={} amount_a={}",
+            self.escrow.key(),
+            amount
+        );
+
+        // Vault -> Maker ATA 转账 Token A
+        transfer_checked_with_hook(
+            CpiContext::new_with_signer(
+                self.token_program.to_account_info(),
+                TransferChecked {
+                    from: self.vault.to_account_info(),
+                    to: self.maker_ata_a.to_account_info(),
+                    mint: self.mint_a.to_account_info(),
+                    authority: self.escrow.to_account_info(),
+                },
+                &signer_seeds,
+            )
+            .with_remaining_accounts(remaining.to_vec()),
+            amount,
+            self.mint_a.decimals,
+        )?;
+
+        self.vault.reload()?;
+        if self.vault.amount == 0 {
+            // 关闭 Vault，把 rent lamports 返还给 rent_collector
+            close_account(
+                CpiContext::new_with_signer(
+                    self.token_program.to_account_info(),
+                    CloseAccount {
+                        account: self.vault.to_account_info(),
+                        authority: self.escrow.to_account_info(),
+                        destination: self.rent_collector.to_account_info(),
+                    },
+                    &signer_seeds,
+                ),
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+/// PartialRefund 指令入口
+///
+/// 参数说明：
+/// - amount: 撤回的 Token A 数量（不超过 escrow.deposit；等于 deposit 时关闭 escrow）
+///
+/// transfer hook 所需账户通过 remaining_accounts 传入
+pub fn handler<'info>(
+    ctx: Context<'_, '_, 'info, 'info, PartialRefund<'info>>,
+    amount: u64,
+) -> Result<()> {
+    let accounts = &mut *ctx.accounts;
+
+    require!(
+        amount > 0 && amount <= accounts.escrow.deposit,
+        EscrowError::InvalidAmount
+    );
+
+    // 被 reserve 锁定的部分在 claim 或过期释放之前不能撤回
+    require!(
+        amount <= accounts.escrow.available(),
+        EscrowError::InsufficientAvailable
+    );
+
+    // 承诺持有期内 maker 不能撤回报价
+    let now = Clock::get()?.unix_timestamp;
+    require!(!accounts.escrow.is_held(now), EscrowError::RefundTooEarly);
+
+    // 冻结的 token 账户无法转账，在 CPI 之前给出明确错误
+    require!(
+        !accounts.vault.is_frozen() && !accounts.maker_ata_a.is_frozen(),
+        EscrowError::AccountFrozen
+    );
+
+    let terms = RefundTerms::load(&accounts.config, accounts.treasury.key)?;
+
+    accounts.escrow.apply_partial_refund(amount)?;
+    let remaining_deposit = accounts.escrow.deposit;
+
+    if remaining_deposit == 0 {
+        // 全部撤回等同于 refund，收取撤单费（config 未初始化时免费）
+        let cancel_fee = terms.cancel_fee_lamports;
+        if cancel_fee > 0 {
+            accounts.pay_cancel_fee(cancel_fee)?;
+        }
+    } else {
+        // 剩余报价缩小后不能变为 0（否则 taker 可以免费成交）
+        require_gt!(accounts.escrow.current_receive(now)?, 0, EscrowError::InvalidAmount);
+    }
+
+    accounts.withdraw(amount, ctx.remaining_accounts)?;
+
+    if remaining_deposit == 0 {
+        accounts.escrow.status = EscrowStatus::Cancelled;
+        accounts.escrow.close(accounts.rent_collector.to_account_info())?;
+
+        require!(
+            accounts.maker_stats.is_some() || !terms.tracks_open_escrows,
+            EscrowError::MakerStatsRequired
+        );
+        if let Some(stats) = accounts.maker_stats.as_mut() {
+            stats.record_refunded()?;
+        }
+    }
+
+    emit!(EscrowPartiallyRefunded {
+        escrow: accounts.escrow.key(),
+        maker: accounts.maker.key(),
+        amount_refunded: amount,
+        remaining_deposit,
+    });
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use anchor_spl::associated_token;
+    use anchor_spl::token::spl_token;
+
+    use super::*;
+    use crate::instructions::take::quote_fill;
+    use crate::test_utils::*;
+
+    const PARTIAL_REFUND: u8 = 25;
+
+    /// 100 Token A 换 50 Token B 的 escrow
+    fn escrow() -> (Escrow, TestAccount) {
+        let (mint_a, mint_b) = setup_mints(6, 6);
+        let escrow = make_escrow(&Pubkey::new_unique(), 3, &mint_a, &mint_b, 100, 50);
+
+        (escrow, mint_a)
+    }
+
+    fn partial_refund(escrow: &Escrow, mint_a: &TestAccount, amount: u64) -> ProgramResult {
+        set_clock(1_000, 1);
+        let escrow_account = escrow_account(escrow);
+        let accounts = vec![
+            TestAccount::wallet(escrow.maker, 1_000_000).signer(),
+            escrow_account.clone().writable(),
+            TestAccount::wallet(escrow.maker, 1_000_000).writable(),
+            no_config(),
+            none(),
+            TestAccount::wallet(Pubkey::new_unique(), 0).writable(),
+            mint_a.clone(),
+            fund_ata(&escrow_account.key, mint_a, escrow.deposit).writable(),
+            fund_ata(&escrow.maker, mint_a, 0).writable(),
+            TestAccount::program(associated_token::ID),
+            TestAccount::program(spl_token::ID),
+            TestAccount::program(anchor_lang::system_program::ID),
+        ];
+
+        run(&ix_data(PARTIAL_REFUND, amount), accounts).0
+    }
+
+    #[test]
+    fn amount_must_be_within_deposit() {
+        let (escrow, mint_a) = escrow();
+
+        assert_error(partial_refund(&escrow, &mint_a, 0), EscrowError::InvalidAmount);
+        assert_error(partial_refund(&escrow, &mint_a, 101), EscrowError::InvalidAmount);
+    }
+
+    #[test]
+    fn remainder_can_be_taken_after_partial_refund() {
+        set_clock(1_000, 1);
+        let (mut escrow, _) = escrow();
+        let config = default_config();
+        let taker = Pubkey::new_unique();
+
+        // 撤回 40：剩余 60 Token A，报价按比例缩小为 30，单价不变
+        escrow.apply_partial_refund(40).unwrap();
+        assert_eq!((escrow.deposit, escrow.receive), (60, 30));
+
+        let escrow = load_account::<Escrow>(escrow_account(&escrow));
+        let quote = |fill_amount| {
+            quote_fill(&escrow, &config, &taker, None, fill_amount, u64::MAX, false)
+        };
+        assert_eq!(quote(60).unwrap(), 30);
+        assert_eq!(quote(30).unwrap(), 15);
+        assert_err(quote(61), EscrowError::InvalidFillAmount);
+    }
+}
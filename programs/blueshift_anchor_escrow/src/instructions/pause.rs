@@ -0,0 +1,78 @@
+use anchor_lang::prelude::*;
+
+use crate::state::Config;
+use crate::errors::EscrowError;
+
+/// Pause / Unpause 指令：
+///
+/// 配置管理员暂停或恢复所有 take（事故响应用），
+/// 暂停期间 refund 不受影响，maker 始终可以取回资金
+#[derive(Accounts)]
+pub struct SetPaused<'info> {
+    /// 配置管理员
+    pub authority: Signer<'info>,
+
+    /// 全局 Config PDA
+    #[account(
+        mut,
+        seeds = [b"config"],
+        bump = config.bump,
+        has_one = authority @ EscrowError::Unauthorized,
+    )]
+    pub config: Account<'info, Config>,
+}
+
+/// Pause / Unpause 指令入口
+///
+/// 参数说明：
+/// - paused: true 为暂停，false 为恢复
+pub fn handler(ctx: Context<SetPaused>, paused: bool) -> Result<()> {
+    ctx.accounts.config.paused = paused;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::*;
+
+    const PAUSE: u8 = 10;
+    const UNPAUSE: u8 = 11;
+
+    fn set_paused(config: &Config, signer: Pubkey, discriminator: u8) -> (ProgramResult, Config) {
+        let (result, infos) = run(
+            &[discriminator],
+            vec![
+                TestAccount::wallet(signer, 0).signer(),
+                config_account(config).writable(),
+            ],
+        );
+        let stored = Config::try_deserialize(&mut &infos[1].data.borrow()[..]).unwrap();
+
+        (result, stored)
+    }
+
+    #[test]
+    fn authority_pauses_and_unpauses() {
+        let config = default_config();
+
+        let (result, paused) = set_paused(&config, config.authority, PAUSE);
+        assert_eq!(result, Ok(()));
+        assert!(paused.paused);
+
+        let (result, resumed) = set_paused(&paused, config.authority, UNPAUSE);
+        assert_eq!(result, Ok(()));
+        assert!(!resumed.paused);
+    }
+
+    #[test]
+    fn only_authority_can_pause() {
+        let config = default_config();
+
+        let (result, stored) = set_paused(&config, Pubkey::new_unique(), PAUSE);
+
+        assert_error(result, EscrowError::Unauthorized);
+        assert!(!stored.paused);
+    }
+}
@@ -0,0 +1,80 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::Mint;
+
+use crate::state::{Config, Escrow};
+use crate::errors::EscrowError;
+use crate::instructions::take::gross_up_transfer_fee;
+
+/// 报价结果（通过 return data 返回）
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct FillQuote {
+    /// 本次成交的 Token B 价格（荷兰拍已按当前时间插值）
+    pub amount_b: u64,
+    /// 其中归 treasury 的协议费
+    pub protocol_fee: u64,
+    /// taker 实际需转出的 Token B 总额（含 Token-2022 转账费）
+    pub taker_cost: u64,
+}
+
+/// Quote 指令：
+///
+/// 只读指令，不修改任何账户。钱包可通过模拟交易获取与 take 完全一致的报价
+#[derive(Accounts)]
+pub struct Quote<'info> {
+    /// Escrow 状态账户（Active 或 PartiallyFilled）
+    #[account(
+        constraint = escrow.is_active() @ EscrowError::EscrowNotActive,
+    )]
+    pub escrow: Account<'info, Escrow>,
+
+    /// 全局配置（协议费率）
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, Config>,
+
+    /// taker 选择支付的 Token B mint（mint_b 或 mint_b_alt），用于计算 Token-2022 转账费
+    pub mint_b: InterfaceAccount<'info, Mint>,
+}
+
+/// Quote 指令入口
+///
+/// 参数说明：
+/// - fill_amount: 希望获得的 Token A 数量
+pub fn handler(ctx: Context<Quote>, fill_amount: u64) -> Result<FillQuote> {
+    let escrow = &ctx.accounts.escrow;
+
+    require!(
+        fill_amount > 0 && fill_amount <= escrow.deposit,
+        EscrowError::InvalidFillAmount
+    );
+
+    let now = Clock::get()?.unix_timestamp;
+    let alt = escrow.check_mint_b(&ctx.accounts.mint_b.key())?;
+    let amount_b = escrow.receive_for(fill_amount, now, alt)?;
+    require_gt!(amount_b, 0, EscrowError::InvalidFillAmount);
+
+    let protocol_fee = ctx.accounts.config.fee_for(&ctx.accounts.escrow, amount_b)?;
+    let maker_net = amount_b
+        .checked_sub(protocol_fee)
+        .ok_or(error!(EscrowError::MathOverflow))?;
+    let maker_gross = gross_up_transfer_fee(&ctx.accounts.mint_b.to_account_info(), maker_net)?;
+    let taker_cost = maker_gross
+        .checked_add(protocol_fee)
+        .ok_or(error!(EscrowError::MathOverflow))?;
+
+    msg!(
+        "quote: fill_amount={} amount_b={} protocol_fee={} taker_cost={}",
+        fill_amount,
+        amount_b,
+        protocol_fee,
+        taker_cost
+    );
+
+    Ok(FillQuote {
+        amount_b,
+        protocol_fee,
+        taker_cost,
+    })
+}
@@ -1,9 +1,8 @@
 use anchor_lang::prelude::*;
 use anchor_spl::{
-    associated_token::AssociatedToken,
+    associated_token::{get_associated_token_address_with_program_id, AssociatedToken},
     token_interface::{
         close_account,
-        transfer_checked,
         Mint,
         TokenAccount,
         TokenInterface,
@@ -12,9 +11,43 @@ use anchor_spl::{
     },
 };
 
-use crate::state::Escrow;
+use crate::state::{transfer_lamports_rent_safe, Escrow, EscrowStatus, MakerStats};
 use crate::errors::EscrowError;
+use crate::events::EscrowRefunded;
+use crate::instructions::make::load_config;
+use crate::instructions::take::transfer_checked_with_hook;
 
+/// 撤单相关的全局配置
+///
+/// config 尚未初始化时撤单免费、不限制挂单数，refund 不依赖运营方先初始化 config
+#[derive(Default)]
+pub struct RefundTerms {
+    /// maker 支付给 treasury 的撤单费（lamports）
+    pub cancel_fee_lamports: u64,
+    /// 是否限制挂单数（此时必须传入 MakerStats）
+    pub tracks_open_escrows: bool,
+}
+
+impl RefundTerms {
+    /// 读取 config；已初始化时 treasury 必须为 config.treasury
+    pub fn load(config: &AccountInfo, treasury: &Pubkey) -> Result<Self> {
+        let Some(config) = load_config(config)? else {
+            return Ok(Self::default());
+        };
+        require_keys_eq!(*treasury, config.treasury, ErrorCode::ConstraintAddress);
+
+        Ok(Self {
+            cancel_fee_lamports: config.cancel_fee_lamports,
+            tracks_open_escrows: config.tracks_open_escrows(),
+        })
+    }
+}
+
+/// Refund 指令：
+///
+/// 退回 vault 中全部 Token A，关闭 vault 与 escrow
+///
+/// 栈空间：与 Take 相同，只有 escrow 与可选账户保持 Box，mint 与 token 账户直接持有（共 632 字节）
 #[derive(Accounts)]
 pub struct Refund<'info> {
     /// Maker: 创建 escrow 的人，退款的发起者
@@ -22,19 +55,54 @@ pub struct Refund<'info> {
     pub maker: Signer<'info>,
 
     /// Escrow PDA：存储交易条款
-    /// close = maker 表示关闭后 lamports 返还给 maker
+    /// close = rent_collector 表示关闭后 lamports 返还给 rent_collector（默认 maker）
     #[account(
         mut,
-        close = maker,
-        seeds = [b"escrow", maker.key().as_ref(), escrow.seed.to_le_bytes().as_ref()],
+        close = rent_collector,
+        seeds = [b"escrow", escrow.creator.as_ref(), escrow.seed.to_le_bytes().as_ref()],
         bump = escrow.bump,
         has_one = maker @ EscrowError::InvalidMaker,
         has_one = mint_a @ EscrowError::InvalidMintA,
+        constraint = !escrow.is_basket() @ EscrowError::BasketMismatch,
+        constraint = escrow.is_active() @ EscrowError::EscrowNotActive,
+        constraint = !escrow.in_progress @ EscrowError::ReentrancyDetected,
     )]
     pub escrow: Box<Account<'info, Escrow>>,
 
+    /// CHECK: 仅接收关闭 vault / escrow 返还的 lamports，
+    /// 地址为 escrow.rent_collector（未设置时为 maker）
+    #[account(
+        mut,
+        address = escrow.rent_destination(escrow.maker),
+    )]
+    pub rent_collector: UncheckedAccount<'info>,
+
+    /// CHECK: 全局 Config PDA（与 make 相同，未初始化时为空账户），
+    /// 仅读取撤单费、treasury 与挂单数上限（见 RefundTerms::load）
+    #[account(
+        seeds = [b"config"],
+        bump,
+    )]
+    pub config: UncheckedAccount<'info>,
+
+    /// maker 统计 PDA（可选，传入时累加 total_refunded；config 限制挂单数时必须传入）
+    #[account(
+        mut,
+        seeds = [b"stats", maker.key().as_ref()],
+        bump = maker_stats.bump,
+    )]
+    pub maker_stats: Option<Box<Account<'info, MakerStats>>>,
+
+    /// CHECK: 仅接收撤单费 lamports，config 已初始化时地址必须为 config.treasury
+    #[account(mut)]
+    pub treasury: UncheckedAccount<'info>,
+
     /// Token A 的 mint
-    pub mint_a: Box<InterfaceAccount<'info, Mint>>,
+    #[account(
+        mint::token_program = token_program,
+        constraint = mint_a.decimals == escrow.mint_a_decimals @ EscrowError::DecimalsMismatch,
+    )]
+    pub mint_a: InterfaceAccount<'info, Mint>,
 
     /// Vault：escrow PDA 持有的 Token A
     #[account(
@@ -43,7 +111,7 @@ pub struct Refund<'info> {
         associated_token::authority = escrow,
         associated_token::token_program = token_program
     )]
-    pub vault: Box<InterfaceAccount<'info, TokenAccount>>,
+    pub vault: InterfaceAccount<'info, TokenAccount>,
 
     /// Maker 的 Token A ATA（接收退款）
     #[account(
@@ -51,62 +119,511 @@ pub struct Refund<'info> {
         payer = maker,
         associated_token::mint = mint_a,
         associated_token::authority = maker,
-        associated_token::token_program = token_program
+        associated_token::token_program = token_program,
+        address = get_associated_token_address_with_program_id(
+            &maker.key(),
+            &mint_a.key(),
+            &token_program.key(),
+        ) @ EscrowError::NonCanonicalAta,
     )]
-    pub maker_ata_a: Box<InterfaceAccount<'info, TokenAccount>>,
+    pub maker_ata_a: InterfaceAccount<'info, TokenAccount>,
 
-    /// Programs
+    /// Programs（associated_token_program 显式约束地址）
+    #[account(address = anchor_spl::associated_token::ID)]
     pub associated_token_program: Program<'info, AssociatedToken>,
     pub token_program: Interface<'info, TokenInterface>,
     pub system_program: Program<'info, System>,
 }
 
 impl<'info> Refund<'info> {
+    /// 退款流程使用的账户（Token A 退回 maker_ata_a）
+    pub fn flow(&mut self) -> RefundFlow<'_, 'info> {
+        RefundFlow {
+            maker: &self.maker,
+            escrow: &mut self.escrow,
+            rent_collector: &self.rent_collector,
+            config: &self.config,
+            maker_stats: &mut self.maker_stats,
+            treasury: &self.treasury,
+            mint_a: &self.mint_a,
+            vault: &self.vault,
+            recipient_ata_a: &self.maker_ata_a,
+            token_program: &self.token_program,
+            system_program: &self.system_program,
+        }
+    }
+
+    /// 退款前的校验与撤单费，返回 vault 当前余额（用于事件）
+    pub fn prepare_refund(&mut self) -> Result<u64> {
+        self.flow().prepare_refund()
+    }
 
-    /// 从 Vault 中把所有 Token A 转回给 Maker，并关闭 Vault
-    fn refund_and_close_vault(&mut self) -> Result<()> {
+    /// vault 关闭后更新统计与状态，并发出退款事件
+    pub fn finish_refund(&mut self, amount_refunded: u64) -> Result<()> {
+        self.flow().finish_refund(amount_refunded)
+    }
+}
+
+/// refund 与 refund_to 共用的退款流程
+///
+/// 两者只有 Token A 的接收账户与 rent_collector 的默认值不同，
+/// 校验、撤单费、转出并关闭 vault 与收尾统计完全相同
+pub struct RefundFlow<'a, 'info> {
+    pub maker: &'a Signer<'info>,
+    pub escrow: &'a mut Account<'info, Escrow>,
+    pub rent_collector: &'a UncheckedAccount<'info>,
+    pub config: &'a UncheckedAccount<'info>,
+    pub maker_stats: &'a mut Option<Box<Account<'info, MakerStats>>>,
+    pub treasury: &'a UncheckedAccount<'info>,
+    pub mint_a: &'a InterfaceAccount<'info, Mint>,
+    pub vault: &'a InterfaceAccount<'info, TokenAccount>,
+    /// 接收退回 Token A 的账户（maker_ata_a 或 destination_ata_a）
+    pub recipient_ata_a: &'a InterfaceAccount<'info, TokenAccount>,
+    pub token_program: &'a Interface<'info, TokenInterface>,
+    pub system_program: &'a Program<'info, System>,
+}
+
+impl<'info> RefundFlow<'_, 'info> {
+    /// 撤单费与挂单数限制（config 未初始化时均为默认值）
+    fn terms(&self) -> Result<RefundTerms> {
+        RefundTerms::load(self.config, self.treasury.key)
+    }
+
+    /// maker 向 treasury 支付撤单费（lamports）
+    fn pay_cancel_fee(&self, fee: u64) -> Result<()> {
+        require!(
+            self.maker.lamports() >= fee,
+            EscrowError::InsufficientFunds
+        );
+
+        cu_profile!(
+            "refund: cancel_fee",
+            transfer_lamports_rent_safe(
+                self.system_program,
+                self.maker.to_account_info(),
+                self.treasury.to_account_info(),
+                fee,
+            )
+        )?;
+
+        Ok(())
+    }
+
+    /// 从 Vault 中把所有 Token A 转给 recipient_ata_a，并关闭 Vault
+    pub fn refund_and_close_vault(&self, remaining: &[AccountInfo<'info>]) -> Result<()> {
         // Escrow PDA 作为 Vault 的 authority，需要 signer seeds
-        let signer_seeds: [&[&[u8]]; 1] = [&[
-            b"escrow",
-            self.maker.key.as_ref(),
-            &self.escrow.seed.to_le_bytes(),
-            &[self.escrow.bump],
-        ]];
-
-        // Vault -> Maker ATA 转账 Token A
-        transfer_checked(
-            CpiContext::new_with_signer(
-                self.token_program.to_account_info(),
-                TransferChecked {
-                    from: self.vault.to_account_info(),
-                    to: self.maker_ata_a.to_account_info(),
-                    mint: self.mint_a.to_account_info(),
-                    authority: self.escrow.to_account_info(),
-                },
-                &signer_seeds,
-            ),
-            self.vault.amount,        // 全部余额
-            self.mint_a.decimals,     // mint 精度
+        let seed_bytes = self.escrow.seed.to_le_bytes();
+        let seeds = self.escrow.signer_seeds(&seed_bytes);
+        let signer_seeds: [&[&[u8]]; 1] = [&seeds];
+
+        msg!(
+            "refund: escrow={} amount_a={}",
+            self.escrow.key(),
+            self.vault.amount
+        );
+
+        // Vault -> 接收方 ATA 转账 Token A
+        cu_profile!(
+            "refund: withdraw",
+            transfer_checked_with_hook(
+                CpiContext::new_with_signer(
+                    self.token_program.to_account_info(),
+                    TransferChecked {
+                        from: self.vault.to_account_info(),
+                        to: self.recipient_ata_a.to_account_info(),
+                        mint: self.mint_a.to_account_info(),
+                        authority: self.escrow.to_account_info(),
+                    },
+                    &signer_seeds,
+                )
+                .with_remaining_accounts(remaining.to_vec()),
+                self.vault.amount,        // 全部余额
+                self.mint_a.decimals,     // mint 精度
+            )
         )?;
 
-        // 关闭 Vault，把 rent lamports 返还给 maker
-        close_account(
-            CpiContext::new_with_signer(
-                self.token_program.to_account_info(),
-                CloseAccount {
-                    account: self.vault.to_account_info(),
-                    authority: self.escrow.to_account_info(),
-                    destination: self.maker.to_account_info(),
-                },
-                &signer_seeds,
-            ),
+        // 关闭 Vault，把 rent lamports 返还给 rent_collector
+        cu_profile!(
+            "refund: close_vault",
+            close_account(
+                CpiContext::new_with_signer(
+                    self.token_program.to_account_info(),
+                    CloseAccount {
+                        account: self.vault.to_account_info(),
+                        authority: self.escrow.to_account_info(),
+                        destination: self.rent_collector.to_account_info(),
+                    },
+                    &signer_seeds,
+                ),
+            )
         )?;
 
+        msg!("refund: done, vault closed");
+
         Ok(())
     }
+
+    /// 退款前的校验与撤单费，返回 vault 当前余额（用于事件）
+    pub fn prepare_refund(&mut self) -> Result<u64> {
+        // 纵深防御：Account<Escrow> 反序列化时已校验 owner 与 discriminator，
+        // 这里再显式确认 escrow 属于本程序且有数据，将来放宽账户类型时也能给出明确错误
+        let escrow_info = self.escrow.to_account_info();
+        require!(
+            escrow_info.owner == &crate::ID && !escrow_info.data_is_empty(),
+            EscrowError::InvalidEscrowAccount
+        );
+
+        // strict-pda 构建：重新推导 escrow 地址并校验规范 bump
+        #[cfg(feature = "strict-pda")]
+        require_keys_eq!(
+            self.escrow.verify_pda(&self.escrow.creator, &crate::ID)?,
+            self.escrow.key(),
+            EscrowError::InvalidEscrowAccount
+        );
+
+        // 存在未 claim / 未释放的预留时不能整体撤回（预留最长 Reservation::MAX_DURATION_SECONDS）
+        require!(
+            self.escrow.reserved == 0,
+            EscrowError::InsufficientAvailable
+        );
+
+        // 承诺持有期内 maker 不能撤回报价
+        let now = Clock::get()?.unix_timestamp;
+        require!(!self.escrow.is_held(now), EscrowError::RefundTooEarly);
+
+        // 冻结的 token 账户无法转账，在 CPI 之前给出明确错误
+        require!(
+            !self.vault.is_frozen() && !self.recipient_ata_a.is_frozen(),
+            EscrowError::AccountFrozen
+        );
+
+        // 撤单费（0 或 config 未初始化表示免费，与之前行为一致）
+        let cancel_fee = self.terms()?.cancel_fee_lamports;
+        if cancel_fee > 0 {
+            self.pay_cancel_fee(cancel_fee)?;
+        }
+
+        Ok(self.vault.amount)
+    }
+
+    /// vault 关闭后更新统计与状态，并发出退款事件
+    pub fn finish_refund(&mut self, amount_refunded: u64) -> Result<()> {
+        require!(
+            self.maker_stats.is_some() || !self.terms()?.tracks_open_escrows,
+            EscrowError::MakerStatsRequired
+        );
+        if let Some(stats) = self.maker_stats.as_mut() {
+            stats.record_refunded()?;
+        }
+
+        // escrow 会因 close = rent_collector 在指令结束时关闭
+        self.escrow.status = EscrowStatus::Cancelled;
+
+        emit!(EscrowRefunded {
+            escrow: self.escrow.key(),
+            maker: self.maker.key(),
+            amount_refunded,
+        });
+
+        Ok(())
+    }
+}
+
+/// transfer hook 所需账户通过 remaining_accounts 传入
+pub fn handler<'info>(ctx: Context<'_, '_, 'info, 'info, Refund<'info>>) -> Result<()> {
+    let mut flow = ctx.accounts.flow();
+    let amount_refunded = flow.prepare_refund()?;
+
+    flow.refund_and_close_vault(ctx.remaining_accounts)?;
+
+    flow.finish_refund(amount_refunded)
 }
 
-pub fn handler(ctx: Context<Refund>) -> Result<()> {
-    ctx.accounts.refund_and_close_vault()?;
-    Ok(())
+#[cfg(test)]
+mod tests {
+    use anchor_spl::associated_token;
+    use anchor_spl::token::spl_token;
+
+    use super::*;
+    use crate::state::Config;
+    use crate::test_utils::*;
+
+    const REFUND: u8 = 2;
+
+    struct Setup {
+        escrow: Escrow,
+        mint_a: TestAccount,
+    }
+
+    fn setup() -> Setup {
+        let (mint_a, mint_b) = setup_mints(6, 6);
+        let escrow = make_escrow(&Pubkey::new_unique(), 5, &mint_a, &mint_b, 100, 50);
+
+        Setup { escrow, mint_a }
+    }
+
+    fn refund(
+        setup: &Setup,
+        maker_lamports: u64,
+        config: TestAccount,
+        treasury: Pubkey,
+    ) -> ProgramResult {
+        set_clock(1_000, 1);
+
+        run(&[REFUND], refund_accounts(setup, maker_lamports, config, treasury)).0
+    }
+
+    /// Refund 的全部账户（按结构体字段顺序），vault 存有 escrow.deposit
+    fn refund_accounts(
+        setup: &Setup,
+        maker_lamports: u64,
+        config: TestAccount,
+        treasury: Pubkey,
+    ) -> Vec<TestAccount> {
+        let Setup { escrow, mint_a } = setup;
+        let escrow_account = escrow_account(escrow);
+
+        vec![
+            TestAccount::wallet(escrow.maker, maker_lamports).signer(),
+            escrow_account.clone().writable(),
+            TestAccount::wallet(escrow.maker, maker_lamports).writable(),
+            config,
+            none(),
+            TestAccount::wallet(treasury, 0).writable(),
+            mint_a.clone(),
+            fund_ata(&escrow_account.key, mint_a, escrow.deposit).writable(),
+            fund_ata(&escrow.maker, mint_a, 0).writable(),
+            TestAccount::program(associated_token::ID),
+            TestAccount::program(spl_token::ID),
+            TestAccount::program(anchor_lang::system_program::ID),
+        ]
+    }
+
+    #[test]
+    fn terms_default_to_free_without_config() {
+        let config = no_config().into_info();
+        let terms = RefundTerms::load(&config, &Pubkey::new_unique()).unwrap();
+
+        assert_eq!(terms.cancel_fee_lamports, 0);
+        assert!(!terms.tracks_open_escrows);
+    }
+
+    #[test]
+    fn terms_read_initialized_config() {
+        let config = Config {
+            cancel_fee_lamports: 5_000,
+            max_open_per_maker: 3,
+            ..default_config()
+        };
+        let info = config_account(&config).into_info();
+        let terms = RefundTerms::load(&info, &config.treasury).unwrap();
+
+        assert_eq!(terms.cancel_fee_lamports, 5_000);
+        assert!(terms.tracks_open_escrows);
+        assert_err(
+            RefundTerms::load(&info, &Pubkey::new_unique()),
+            ErrorCode::ConstraintAddress,
+        );
+    }
+
+    #[test]
+    fn refund_works_without_config() {
+        // config 未初始化时账户校验通过，之后的校验照常进行
+        let mut setup = setup();
+        setup.escrow.min_hold_until = 2_000;
+
+        let result = refund(&setup, 1_000_000_000, no_config(), Pubkey::new_unique());
+
+        assert_error(result, EscrowError::RefundTooEarly);
+    }
+
+    #[test]
+    fn maker_must_cover_cancel_fee() {
+        let config = Config {
+            cancel_fee_lamports: 5_000,
+            ..default_config()
+        };
+
+        let result = refund(&setup(), 4_999, config_account(&config), config.treasury);
+
+        assert_error(result, EscrowError::InsufficientFunds);
+    }
+
+    #[test]
+    fn cancel_fee_must_leave_maker_rent_exempt() {
+        let config = Config {
+            cancel_fee_lamports: 5_000,
+            ..default_config()
+        };
+        let maker_lamports = Rent::default().minimum_balance(0) + 4_999;
+
+        let result = refund(&setup(), maker_lamports, config_account(&config), config.treasury);
+
+        assert_error(result, EscrowError::WouldBreakRentExemption);
+    }
+
+    #[test]
+    fn cancel_fee_goes_to_config_treasury() {
+        let config = Config {
+            cancel_fee_lamports: 5_000,
+            ..default_config()
+        };
+
+        let treasury = Pubkey::new_unique();
+
+        let result = refund(&setup(), 1_000_000_000, config_account(&config), treasury);
+
+        assert_error(result, ErrorCode::ConstraintAddress);
+    }
+
+    #[test]
+    fn refund_waits_for_hold_period() {
+        let mut setup = setup();
+        setup.escrow.min_hold_until = 1_001;
+
+        let result = refund(&setup, 1_000_000_000, no_config(), Pubkey::new_unique());
+
+        assert_error(result, EscrowError::RefundTooEarly);
+        // 持有期截止当秒起即可撤回，0 表示不限制
+        assert!(!setup.escrow.is_held(1_001));
+        setup.escrow.min_hold_until = 0;
+        assert!(!setup.escrow.is_held(0));
+    }
+
+    #[test]
+    fn mint_must_match_token_program() {
+        set_clock(1_000, 1);
+        // token 账户均属于 SPL Token，只有 mint_a 归 Token-2022 所有
+        let mut accounts =
+            refund_accounts(&setup(), 1_000_000_000, no_config(), Pubkey::new_unique());
+        accounts[6].owner = anchor_spl::token_2022::ID;
+
+        let (result, _) = run(&[REFUND], accounts);
+
+        assert_error(result, ErrorCode::ConstraintMintTokenProgram);
+    }
+
+    #[test]
+    fn refund_is_allowed_while_paused() {
+        set_clock(1_000, 1);
+        let config = Config {
+            paused: true,
+            ..default_config()
+        };
+        let accounts =
+            refund_accounts(&setup(), 1_000_000_000, config_account(&config), config.treasury);
+
+        // 暂停只影响 take：账户校验与退款前检查均通过（之后的转账需要 CPI，宿主机上无法执行）
+        let (mut refund, _) = load_accounts::<Refund>(accounts, &[REFUND]).unwrap();
+        assert_eq!(refund.flow().prepare_refund().unwrap(), 100);
+    }
+
+    #[test]
+    fn escrow_rent_goes_to_rent_collector() {
+        set_clock(1_000, 1);
+        let load = |setup: &Setup, rent_collector: Option<Pubkey>| {
+            let mut accounts =
+                refund_accounts(setup, 1_000_000_000, no_config(), Pubkey::new_unique());
+            if let Some(key) = rent_collector {
+                accounts[2] = TestAccount::wallet(key, 0).writable();
+            }
+            load_accounts::<Refund>(accounts, &[REFUND]).map(|_| ())
+        };
+
+        // 默认返还给 maker
+        let mut setup = setup();
+        assert!(load(&setup, None).is_ok());
+        assert_err(load(&setup, Some(Pubkey::new_unique())), ErrorCode::ConstraintAddress);
+
+        // 设置 rent_collector 后只能返还给 rent_collector
+        let collector = Pubkey::new_unique();
+        setup.escrow.rent_collector = collector;
+        assert_err(load(&setup, None), ErrorCode::ConstraintAddress);
+        assert!(load(&setup, Some(collector)).is_ok());
+    }
+
+    #[test]
+    fn mint_decimals_must_match_escrow() {
+        let mut setup = setup();
+        setup.mint_a = TestAccount {
+            key: setup.mint_a.key,
+            ..mint_account(9)
+        };
+
+        let result = refund(&setup, 1_000_000_000, no_config(), Pubkey::new_unique());
+
+        assert_error(result, EscrowError::DecimalsMismatch);
+    }
+
+    #[test]
+    fn new_maker_can_refund_after_transfer() {
+        set_clock(1_000, 1);
+        let mut setup = setup();
+        let creator = setup.escrow.maker;
+        setup.escrow.maker = Pubkey::new_unique();
+
+        // 新 maker 通过全部账户校验与退款前检查
+        let accounts = refund_accounts(&setup, 1_000_000_000, no_config(), Pubkey::new_unique());
+        let (mut refund, _) = load_accounts::<Refund>(accounts, &[REFUND]).unwrap();
+        assert_eq!(refund.flow().prepare_refund().unwrap(), 100);
+
+        // 原 maker 不再有 refund 权利
+        let mut accounts =
+            refund_accounts(&setup, 1_000_000_000, no_config(), Pubkey::new_unique());
+        accounts[0] = TestAccount::wallet(creator, 1_000_000_000).signer();
+        accounts[8] = fund_ata(&creator, &setup.mint_a, 0).writable();
+        let (result, _) = run(&[REFUND], accounts);
+        assert_error(result, EscrowError::InvalidMaker);
+    }
+
+    #[test]
+    fn non_canonical_maker_account_is_rejected() {
+        set_clock(1_000, 1);
+        let mut accounts =
+            refund_accounts(&setup(), 1_000_000_000, no_config(), Pubkey::new_unique());
+        accounts[8].key = Pubkey::new_unique();
+
+        let (result, _) = run(&[REFUND], accounts);
+
+        assert_error(result, ErrorCode::AccountNotAssociatedTokenAccount);
+    }
+
+    #[test]
+    fn refund_during_take_is_rejected() {
+        let mut setup = setup();
+        setup.escrow.in_progress = true;
+
+        let result = refund(&setup, 1_000_000_000, no_config(), Pubkey::new_unique());
+
+        assert_error(result, EscrowError::ReentrancyDetected);
+    }
+
+    #[test]
+    fn associated_token_program_is_pinned() {
+        set_clock(1_000, 1);
+        let setup = setup();
+        let mut accounts =
+            refund_accounts(&setup, 1_000_000, no_config(), Pubkey::new_unique());
+        accounts[9] = TestAccount::program(Pubkey::new_unique());
+
+        assert_error(run(&[REFUND], accounts).0, ErrorCode::InvalidProgramId);
+    }
+
+    #[test]
+    fn foreign_owned_escrow_is_rejected() {
+        set_clock(1_000, 1);
+        let setup = setup();
+        let foreign = Pubkey::new_unique();
+
+        // 反序列化阶段即拒绝其他程序拥有的账户
+        let mut accounts = refund_accounts(&setup, 1_000_000, no_config(), Pubkey::new_unique());
+        accounts[1].owner = foreign;
+        assert_error(run(&[REFUND], accounts).0, ErrorCode::AccountOwnedByWrongProgram);
+
+        // 反序列化之后 owner 被改变时，handler 内的检查仍然拒绝
+        let accounts = refund_accounts(&setup, 1_000_000, no_config(), Pubkey::new_unique());
+        let (mut refund, _) = load_accounts::<Refund>(accounts, &[REFUND]).unwrap();
+        refund.escrow.to_account_info().assign(&foreign);
+        assert_err(refund.flow().prepare_refund(), EscrowError::InvalidEscrowAccount);
+    }
 }
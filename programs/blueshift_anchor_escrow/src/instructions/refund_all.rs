@@ -0,0 +1,377 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::get_associated_token_address_with_program_id,
+    token_interface::{
+        close_account,
+        transfer_checked,
+        CloseAccount,
+        Mint,
+        TokenAccount,
+        TokenInterface,
+        TransferChecked,
+    },
+};
+
+use crate::state::{transfer_lamports_rent_safe, Config, Escrow, EscrowStatus, MakerStats};
+use crate::errors::EscrowError;
+use crate::events::EscrowRefunded;
+
+/// 每个 escrow 在 remaining_accounts 中占用的账户数
+pub const REFUND_ALL_GROUP_LEN: usize = 4;
+
+/// 通过校验、待撤回的一个 escrow 分组
+struct RefundGroup<'info> {
+    escrow_info: &'info AccountInfo<'info>,
+    vault_info: &'info AccountInfo<'info>,
+    mint_info: &'info AccountInfo<'info>,
+    maker_ata_a: &'info AccountInfo<'info>,
+    escrow: Account<'info, Escrow>,
+    mint_a: InterfaceAccount<'info, Mint>,
+    vault: InterfaceAccount<'info, TokenAccount>,
+}
+
+/// RefundAll 指令：
+///
+/// maker 在一笔交易中撤回多个 escrow，每个 escrow 与 refund 相同：
+/// 退回 vault 中全部 Token A，关闭 vault 与 escrow，并按个数收取撤单费。
+/// 任意一个 escrow 校验或退款失败则整笔交易回滚
+///
+/// remaining_accounts 按每个 escrow 4 个账户依次排列：
+/// 1. escrow（mut，必须属于签名的 maker，seeds 重新推导校验）
+/// 2. vault（mut，escrow 持有的 mint_a ATA）
+/// 3. mint_a（escrow.mint_a）
+/// 4. maker_ata_a（mut，maker 的 mint_a ATA，必须已存在）
+///
+/// 限制：设置了 rent_collector / vault_rent_payer 的 escrow 与组合报价需使用 refund 单独撤回；
+/// mint_a 不能配置 transfer hook（remaining_accounts 已用于 escrow 分组）
+#[derive(Accounts)]
+pub struct RefundAll<'info> {
+    /// Maker: 所有 escrow 的所有者，接收退回的 Token A 与租金
+    #[account(mut)]
+    pub maker: Signer<'info>,
+
+    /// 全局配置（撤单费与 treasury）
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+    )]
+    pub config: Box<Account<'info, Config>>,
+
+    /// CHECK: 仅接收撤单费 lamports，地址由 config.treasury 约束
+    #[account(
+        mut,
+        address = config.treasury,
+    )]
+    pub treasury: UncheckedAccount<'info>,
+
+    /// maker 统计 PDA（可选，传入时累加 total_refunded；config 限制挂单数时必须传入）
+    #[account(
+        mut,
+        seeds = [b"stats", maker.key().as_ref()],
+        bump = maker_stats.bump,
+    )]
+    pub maker_stats: Option<Box<Account<'info, MakerStats>>>,
+
+    /// Programs
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> RefundAll<'info> {
+    /// maker 向 treasury 支付一个 escrow 的撤单费（lamports）
+    fn pay_cancel_fee(&self, fee: u64) -> Result<()> {
+        require!(
+            self.maker.lamports() >= fee,
+            EscrowError::InsufficientFunds
+        );
+
+        transfer_lamports_rent_safe(
+            &self.system_program,
+            self.maker.to_account_info(),
+            self.treasury.to_account_info(),
+            fee,
+        )
+    }
+
+    /// 校验一个 escrow 分组（不发起任何 CPI）
+    fn check_group(
+        &self,
+        group: &'info [AccountInfo<'info>],
+        now: i64,
+    ) -> Result<RefundGroup<'info>> {
+        let [escrow_info, vault_info, mint_info, maker_ata_a] = group else {
+            return err!(EscrowError::InvalidAccountGroup);
+        };
+
+        // =======================
+        // 校验 escrow（等价于 Refund 的账户约束）
+        // =======================
+        let escrow = Account::<Escrow>::try_from(escrow_info)?;
+
+        let seed_bytes = escrow.seed.to_le_bytes();
+        let pda = Pubkey::create_program_address(&escrow.signer_seeds(&seed_bytes), &crate::ID)
+            .map_err(|_| error!(EscrowError::InvalidAccountGroup))?;
+        require_keys_eq!(pda, escrow.key(), EscrowError::InvalidAccountGroup);
+        require!(escrow.has_canonical_bump(), EscrowError::InvalidBump);
+
+        let maker = self.maker.key();
+        require_keys_eq!(escrow.maker, maker, EscrowError::InvalidMaker);
+        require_keys_eq!(
+            escrow.rent_destination(maker),
+            maker,
+            EscrowError::InvalidAccountGroup
+        );
+        require_keys_eq!(
+            escrow.vault_rent_destination(maker),
+            maker,
+            EscrowError::InvalidAccountGroup
+        );
+        require!(!escrow.is_basket(), EscrowError::BasketMismatch);
+        require!(escrow.is_active(), EscrowError::EscrowNotActive);
+        require!(!escrow.in_progress, EscrowError::ReentrancyDetected);
+        require!(!escrow.is_held(now), EscrowError::RefundTooEarly);
+        require!(escrow.reserved == 0, EscrowError::InsufficientAvailable);
+
+        // =======================
+        // 校验 mint / token 账户地址
+        // =======================
+        require_keys_eq!(mint_info.key(), escrow.mint_a, EscrowError::InvalidMintA);
+        let mint_a = InterfaceAccount::<Mint>::try_from(mint_info)?;
+        require_eq!(
+            mint_a.decimals,
+            escrow.mint_a_decimals,
+            EscrowError::DecimalsMismatch
+        );
+
+        let token_program = self.token_program.key();
+        require_keys_eq!(
+            vault_info.key(),
+            get_associated_token_address_with_program_id(
+                &escrow.key(),
+                &escrow.mint_a,
+                &token_program,
+            ),
+            EscrowError::InvalidAccountGroup
+        );
+        require_keys_eq!(
+            maker_ata_a.key(),
+            get_associated_token_address_with_program_id(&maker, &escrow.mint_a, &token_program),
+            EscrowError::InvalidAccountGroup
+        );
+
+        let vault = InterfaceAccount::<TokenAccount>::try_from(vault_info)?;
+        let maker_token_a = InterfaceAccount::<TokenAccount>::try_from(maker_ata_a)?;
+        require!(
+            !vault.is_frozen() && !maker_token_a.is_frozen(),
+            EscrowError::AccountFrozen
+        );
+
+        Ok(RefundGroup {
+            escrow_info,
+            vault_info,
+            mint_info,
+            maker_ata_a,
+            escrow,
+            mint_a,
+            vault,
+        })
+    }
+
+    /// 撤回一个已校验的 escrow
+    fn refund_one(&self, group: RefundGroup<'info>) -> Result<()> {
+        let RefundGroup {
+            escrow_info,
+            vault_info,
+            mint_info,
+            maker_ata_a,
+            mut escrow,
+            mint_a,
+            vault,
+        } = group;
+        let seed_bytes = escrow.seed.to_le_bytes();
+        let maker = self.maker.key();
+
+        // 撤单费按 escrow 个数收取，与逐个 refund 相同
+        let cancel_fee = self.config.cancel_fee_lamports;
+        if cancel_fee > 0 {
+            self.pay_cancel_fee(cancel_fee)?;
+        }
+
+        // =======================
+        // vault -> maker（Token A），关闭 vault 与 escrow，lamports 返还给 maker
+        // =======================
+        let seeds = escrow.signer_seeds(&seed_bytes);
+        let signer_seeds: [&[&[u8]]; 1] = [&seeds];
+
+        transfer_checked(
+            CpiContext::new_with_signer(
+                self.token_program.to_account_info(),
+                TransferChecked {
+                    from: vault_info.clone(),
+                    to: maker_ata_a.clone(),
+                    mint: mint_info.clone(),
+                    authority: escrow_info.clone(),
+                },
+                &signer_seeds,
+            ),
+            vault.amount,
+            mint_a.decimals,
+        )?;
+
+        close_account(
+            CpiContext::new_with_signer(
+                self.token_program.to_account_info(),
+                CloseAccount {
+                    account: vault_info.clone(),
+                    authority: escrow_info.clone(),
+                    destination: self.maker.to_account_info(),
+                },
+                &signer_seeds,
+            )
+        )?;
+
+        escrow.status = EscrowStatus::Cancelled;
+        escrow.close(self.maker.to_account_info())?;
+
+        emit!(EscrowRefunded {
+            escrow: escrow.key(),
+            maker,
+            amount_refunded: vault.amount,
+        });
+
+        Ok(())
+    }
+}
+
+/// RefundAll 指令入口
+///
+/// 需撤回的 escrow 通过 remaining_accounts 传入（排列见 RefundAll）
+pub fn handler<'info>(ctx: Context<'_, '_, 'info, 'info, RefundAll<'info>>) -> Result<()> {
+    let groups = ctx.remaining_accounts.chunks_exact(REFUND_ALL_GROUP_LEN);
+    require!(
+        !ctx.remaining_accounts.is_empty() && groups.remainder().is_empty(),
+        EscrowError::InvalidAccountGroup
+    );
+
+    let accounts = &mut *ctx.accounts;
+    require!(
+        accounts.maker_stats.is_some() || !accounts.config.tracks_open_escrows(),
+        EscrowError::MakerStatsRequired
+    );
+
+    // 先校验全部分组：任一分组不合法时在发起任何 CPI 之前失败
+    let now = Clock::get()?.unix_timestamp;
+    let groups = groups
+        .map(|group| accounts.check_group(group, now))
+        .collect::<Result<Vec<_>>>()?;
+
+    for group in groups {
+        accounts.refund_one(group)?;
+
+        if let Some(stats) = accounts.maker_stats.as_mut() {
+            stats.record_refunded()?;
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use anchor_spl::token::spl_token;
+
+    use super::*;
+    use crate::test_utils::*;
+
+    const REFUND_ALL: u8 = 38;
+
+    /// RefundAll 的固定账户（按结构体字段顺序）
+    fn refund_all_accounts(maker: &Pubkey) -> Vec<TestAccount> {
+        let config = default_config();
+
+        vec![
+            TestAccount::wallet(*maker, 1_000_000_000).signer().writable(),
+            config_account(&config),
+            TestAccount::wallet(config.treasury, 0).writable(),
+            none(),
+            TestAccount::program(spl_token::ID),
+            TestAccount::program(anchor_lang::system_program::ID),
+        ]
+    }
+
+    /// 一个 escrow 的 remaining_accounts 分组，vault 存有 escrow.deposit
+    fn group(escrow: &Escrow, mint_a: &TestAccount) -> Vec<TestAccount> {
+        let escrow_account = escrow_account(escrow);
+        let vault = fund_ata(&escrow_account.key, mint_a, escrow.deposit);
+
+        vec![
+            escrow_account.writable(),
+            vault.writable(),
+            mint_a.clone(),
+            fund_ata(&escrow.maker, mint_a, 0).writable(),
+        ]
+    }
+
+    fn infos(accounts: Vec<TestAccount>) -> &'static [AccountInfo<'static>] {
+        Box::leak(
+            accounts
+                .into_iter()
+                .map(TestAccount::into_info)
+                .collect::<Vec<_>>()
+                .into_boxed_slice(),
+        )
+    }
+
+    #[test]
+    fn validates_two_escrows_of_the_signing_maker() {
+        set_clock(1_000, 1);
+        let maker = Pubkey::new_unique();
+        let (mint_a, mint_b) = setup_mints(6, 6);
+        let first = make_escrow(&maker, 1, &mint_a, &mint_b, 100, 50);
+        let second = make_escrow(&maker, 2, &mint_a, &mint_b, 40, 20);
+
+        let (refund_all, _) =
+            load_accounts::<RefundAll>(refund_all_accounts(&maker), &[REFUND_ALL]).unwrap();
+        let remaining = infos([group(&first, &mint_a), group(&second, &mint_a)].concat());
+
+        let checked = remaining
+            .chunks_exact(REFUND_ALL_GROUP_LEN)
+            .map(|group| refund_all.check_group(group, 1_000).unwrap())
+            .map(|group| (group.escrow.seed, group.vault.amount))
+            .collect::<Vec<_>>();
+
+        assert_eq!(checked, [(1, 100), (2, 40)]);
+    }
+
+    #[test]
+    fn malformed_group_fails_before_any_refund() {
+        set_clock(1_000, 1);
+        let maker = Pubkey::new_unique();
+        let (mint_a, mint_b) = setup_mints(6, 6);
+        let own = make_escrow(&maker, 1, &mint_a, &mint_b, 100, 50);
+        let foreign = make_escrow(&Pubkey::new_unique(), 2, &mint_a, &mint_b, 100, 50);
+        let refund_all = |remaining: Vec<TestAccount>| {
+            run(&[REFUND_ALL], [refund_all_accounts(&maker), remaining].concat()).0
+        };
+
+        // 合法分组在前、不合法分组在后，整笔指令仍在任何 CPI 之前失败
+        assert_error(
+            refund_all([group(&own, &mint_a), group(&foreign, &mint_a)].concat()),
+            EscrowError::InvalidMaker,
+        );
+
+        let mut swapped = group(&own, &mint_a);
+        swapped.swap(1, 3);
+        assert_error(
+            refund_all([group(&own, &mint_a), swapped].concat()),
+            EscrowError::InvalidAccountGroup,
+        );
+
+        let truncated = group(&own, &mint_a)[..3].to_vec();
+        assert_error(
+            refund_all([group(&own, &mint_a), truncated].concat()),
+            EscrowError::InvalidAccountGroup,
+        );
+        assert_error(refund_all(Vec::new()), EscrowError::InvalidAccountGroup);
+    }
+}
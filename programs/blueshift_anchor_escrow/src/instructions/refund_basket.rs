@@ -0,0 +1,235 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_interface::{
+        close_account,
+        transfer_checked,
+        CloseAccount,
+        Mint,
+        TokenAccount,
+        TokenInterface,
+        TransferChecked,
+    },
+};
+
+use crate::state::{transfer_lamports_rent_safe, Config, Escrow, EscrowStatus, MakerStats};
+use crate::errors::EscrowError;
+use crate::events::EscrowRefunded;
+
+/// RefundBasket 指令：
+///
+/// maker 撤回 make_basket 创建的组合报价，
+/// 两种 Token A 退回 maker，关闭两个 vault 与 escrow（撤单费规则与 refund 相同）
+#[derive(Accounts)]
+pub struct RefundBasket<'info> {
+    /// Maker: 创建 escrow 的人，退款的发起者
+    #[account(mut)]
+    pub maker: Signer<'info>,
+
+    /// Escrow PDA（必须是组合报价）
+    /// close = rent_collector 表示关闭后 lamports 返还给 rent_collector（默认 maker）
+    #[account(
+        mut,
+        close = rent_collector,
+        seeds = [b"escrow", escrow.creator.as_ref(), escrow.seed.to_le_bytes().as_ref()],
+        bump = escrow.bump,
+        constraint = escrow.has_canonical_bump() @ EscrowError::InvalidBump,
+        has_one = maker @ EscrowError::InvalidMaker,
+        has_one = mint_a @ EscrowError::InvalidMintA,
+        has_one = mint_a2 @ EscrowError::InvalidMintA,
+        constraint = escrow.is_basket() @ EscrowError::BasketMismatch,
+        constraint = escrow.is_active() @ EscrowError::EscrowNotActive,
+    )]
+    pub escrow: Box<Account<'info, Escrow>>,
+
+    /// CHECK: 仅接收关闭 vault / escrow 返还的 lamports，
+    /// 地址为 escrow.rent_collector（未设置时为 maker）
+    #[account(
+        mut,
+        address = escrow.rent_destination(escrow.maker),
+    )]
+    pub rent_collector: UncheckedAccount<'info>,
+
+    /// 全局配置（撤单费与 treasury）
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+    )]
+    pub config: Box<Account<'info, Config>>,
+
+    /// maker 统计 PDA（可选，传入时累加 total_refunded；config 限制挂单数时必须传入）
+    #[account(
+        mut,
+        seeds = [b"stats", maker.key().as_ref()],
+        bump = maker_stats.bump,
+    )]
+    pub maker_stats: Option<Box<Account<'info, MakerStats>>>,
+
+    /// CHECK: 仅接收撤单费 lamports，地址由 config.treasury 约束
+    #[account(
+        mut,
+        address = config.treasury,
+    )]
+    pub treasury: UncheckedAccount<'info>,
+
+    /// 第一种 Token A 的 mint
+    #[account(
+        mint::token_program = token_program
+    )]
+    pub mint_a: Box<InterfaceAccount<'info, Mint>>,
+
+    /// 第二种 Token A 的 mint
+    #[account(
+        mint::token_program = token_program
+    )]
+    pub mint_a2: Box<InterfaceAccount<'info, Mint>>,
+
+    /// Vault：escrow 持有的 mint_a
+    #[account(
+        mut,
+        associated_token::mint = mint_a,
+        associated_token::authority = escrow,
+        associated_token::token_program = token_program
+    )]
+    pub vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Vault2：escrow 持有的 mint_a2
+    #[account(
+        mut,
+        associated_token::mint = mint_a2,
+        associated_token::authority = escrow,
+        associated_token::token_program = token_program
+    )]
+    pub vault2: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Maker 的 mint_a ATA（接收退款）
+    #[account(
+        init_if_needed,
+        payer = maker,
+        associated_token::mint = mint_a,
+        associated_token::authority = maker,
+        associated_token::token_program = token_program
+    )]
+    pub maker_ata_a: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Maker 的 mint_a2 ATA（接收退款）
+    #[account(
+        init_if_needed,
+        payer = maker,
+        associated_token::mint = mint_a2,
+        associated_token::authority = maker,
+        associated_token::token_program = token_program
+    )]
+    pub maker_ata_a2: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Programs
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> RefundBasket<'info> {
+    /// maker 向 treasury 支付撤单费（lamports）
+    fn pay_cancel_fee(&self, fee: u64) -> Result<()> {
+        require!(
+            self.maker.lamports() >= fee,
+            EscrowError::InsufficientFunds
+        );
+
+        transfer_lamports_rent_safe(
+            &self.system_program,
+            self.maker.to_account_info(),
+            self.treasury.to_account_info(),
+            fee,
+        )?;
+
+        Ok(())
+    }
+
+    /// 将 vault 全部余额退回 maker 并关闭 vault
+    fn refund_and_close(
+        &self,
+        vault: &InterfaceAccount<'info, TokenAccount>,
+        mint: &InterfaceAccount<'info, Mint>,
+        to: &InterfaceAccount<'info, TokenAccount>,
+    ) -> Result<()> {
+        let seed_bytes = self.escrow.seed.to_le_bytes();
+        let seeds = self.escrow.signer_seeds(&seed_bytes);
+        let signer_seeds: [&[&[u8]]; 1] = [&seeds];
+
+        transfer_checked(
+            CpiContext::new_with_signer(
+                self.token_program.to_account_info(),
+                TransferChecked {
+                    from: vault.to_account_info(),
+                    to: to.to_account_info(),
+                    mint: mint.to_account_info(),
+                    authority: self.escrow.to_account_info(),
+                },
+                &signer_seeds,
+            ),
+            vault.amount,
+            mint.decimals,
+        )?;
+
+        close_account(CpiContext::new_with_signer(
+            self.token_program.to_account_info(),
+            CloseAccount {
+                account: vault.to_account_info(),
+                authority: self.escrow.to_account_info(),
+                destination: self.rent_collector.to_account_info(),
+            },
+            &signer_seeds,
+        ))?;
+
+        Ok(())
+    }
+}
+
+/// RefundBasket 指令入口
+pub fn handler(ctx: Context<RefundBasket>) -> Result<()> {
+    let now = Clock::get()?.unix_timestamp;
+    require!(
+        !ctx.accounts.escrow.is_held(now),
+        EscrowError::RefundTooEarly
+    );
+
+    let accounts = &ctx.accounts;
+    require!(
+        !accounts.vault.is_frozen()
+            && !accounts.vault2.is_frozen()
+            && !accounts.maker_ata_a.is_frozen()
+            && !accounts.maker_ata_a2.is_frozen(),
+        EscrowError::AccountFrozen
+    );
+
+    let cancel_fee = accounts.config.cancel_fee_lamports;
+    if cancel_fee > 0 {
+        accounts.pay_cancel_fee(cancel_fee)?;
+    }
+
+    let amount_refunded = accounts.vault.amount;
+
+    accounts.refund_and_close(&accounts.vault, &accounts.mint_a, &accounts.maker_ata_a)?;
+    accounts.refund_and_close(&accounts.vault2, &accounts.mint_a2, &accounts.maker_ata_a2)?;
+
+    let accounts = &mut *ctx.accounts;
+    require!(
+        accounts.maker_stats.is_some() || !accounts.config.tracks_open_escrows(),
+        EscrowError::MakerStatsRequired
+    );
+    if let Some(stats) = accounts.maker_stats.as_mut() {
+        stats.record_refunded()?;
+    }
+
+    // escrow 会因 close = rent_collector 在指令结束时关闭
+    accounts.escrow.status = EscrowStatus::Cancelled;
+
+    emit!(EscrowRefunded {
+        escrow: accounts.escrow.key(),
+        maker: accounts.maker.key(),
+        amount_refunded,
+    });
+
+    Ok(())
+}
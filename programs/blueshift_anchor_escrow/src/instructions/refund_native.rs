@@ -0,0 +1,136 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    token::spl_token::native_mint,
+    token_interface::{close_account, CloseAccount},
+};
+
+use crate::state::transfer_lamports_rent_safe;
+use crate::errors::EscrowError;
+use crate::instructions::refund::{self, Refund};
+
+impl<'info> Refund<'info> {
+    /// 直接关闭 wSOL vault，包装的 SOL 连同租金一起以 native lamports 返还给 maker
+    ///
+    /// native 账户允许在余额非 0 时关闭；设置了 rent_collector 时，
+    /// vault 的租金部分再由 maker 转给 rent_collector
+    fn unwrap_and_close_vault(&mut self) -> Result<()> {
+        let seed_bytes = self.escrow.seed.to_le_bytes();
+        let seeds = self.escrow.signer_seeds(&seed_bytes);
+        let signer_seeds: [&[&[u8]]; 1] = [&seeds];
+
+        let vault_rent = self
+            .vault
+            .to_account_info()
+            .lamports()
+            .checked_sub(self.vault.amount)
+            .ok_or(error!(EscrowError::MathOverflow))?;
+
+        close_account(CpiContext::new_with_signer(
+            self.token_program.to_account_info(),
+            CloseAccount {
+                account: self.vault.to_account_info(),
+                authority: self.escrow.to_account_info(),
+                destination: self.maker.to_account_info(),
+            },
+            &signer_seeds,
+        ))?;
+
+        if self.rent_collector.key() != self.maker.key() && vault_rent > 0 {
+            transfer_lamports_rent_safe(
+                &self.system_program,
+                self.maker.to_account_info(),
+                self.rent_collector.to_account_info(),
+                vault_rent,
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+/// RefundNative 指令入口
+///
+/// mint_a 为 native mint（wSOL）时，maker 直接收到 native SOL 而不是 wSOL 余额；
+/// 其他 mint 与 refund 完全相同
+pub fn handler<'info>(ctx: Context<'_, '_, 'info, 'info, Refund<'info>>) -> Result<()> {
+    if ctx.accounts.mint_a.key() != native_mint::ID {
+        return refund::handler(ctx);
+    }
+
+    let amount_refunded = ctx.accounts.prepare_refund()?;
+
+    ctx.accounts.unwrap_and_close_vault()?;
+
+    ctx.accounts.finish_refund(amount_refunded)
+}
+
+#[cfg(test)]
+mod tests {
+    use anchor_spl::associated_token;
+    use anchor_spl::token::spl_token;
+
+    use super::*;
+    use crate::state::Escrow;
+    use crate::test_utils::*;
+
+    const REFUND_NATIVE: u8 = 22;
+
+    /// 存入 1 SOL（wSOL）换 50 Token B 的 escrow
+    fn native_escrow() -> (Escrow, TestAccount) {
+        let (_, mint_b) = setup_mints(9, 6);
+        let native = TestAccount {
+            key: native_mint::ID,
+            ..mint_account(9)
+        };
+        let escrow = make_escrow(&Pubkey::new_unique(), 8, &native, &mint_b, 1_000_000_000, 50);
+
+        (escrow, native)
+    }
+
+    fn accounts(escrow: &Escrow, mint_a: &TestAccount) -> Vec<TestAccount> {
+        let escrow_account = escrow_account(escrow);
+
+        vec![
+            TestAccount::wallet(escrow.maker, 1_000_000_000).signer(),
+            escrow_account.clone().writable(),
+            TestAccount::wallet(escrow.maker, 1_000_000_000).writable(),
+            no_config(),
+            none(),
+            TestAccount::wallet(Pubkey::new_unique(), 0).writable(),
+            mint_a.clone(),
+            fund_ata(&escrow_account.key, mint_a, escrow.deposit).writable(),
+            fund_ata(&escrow.maker, mint_a, 0).writable(),
+            TestAccount::program(associated_token::ID),
+            TestAccount::program(spl_token::ID),
+            TestAccount::program(anchor_lang::system_program::ID),
+        ]
+    }
+
+    #[test]
+    fn native_refund_shares_refund_checks() {
+        set_clock(1_000, 1);
+        let (mut escrow, native) = native_escrow();
+
+        escrow.min_hold_until = 2_000;
+        let (result, _) = run(&[REFUND_NATIVE], accounts(&escrow, &native));
+        assert_error(result, EscrowError::RefundTooEarly);
+
+        // 持有期结束后退回全部 1 SOL（关闭 vault 需要 CPI，宿主机上无法执行）
+        escrow.min_hold_until = 0;
+        let (mut refund, _) =
+            load_accounts::<Refund>(accounts(&escrow, &native), &[REFUND_NATIVE]).unwrap();
+        assert_eq!(refund.prepare_refund().unwrap(), 1_000_000_000);
+    }
+
+    #[test]
+    fn non_native_mint_falls_back_to_refund() {
+        set_clock(1_000, 1);
+        let (mint_a, mint_b) = setup_mints(6, 6);
+        let mut escrow = make_escrow(&Pubkey::new_unique(), 8, &mint_a, &mint_b, 100, 50);
+        escrow.min_hold_until = 2_000;
+
+        let (result, _) = run(&[REFUND_NATIVE], accounts(&escrow, &mint_a));
+
+        assert_error(result, EscrowError::RefundTooEarly);
+    }
+}
@@ -0,0 +1,186 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::{get_associated_token_address_with_program_id, AssociatedToken},
+    token_interface::{Mint, TokenAccount, TokenInterface},
+};
+
+use crate::state::{Escrow, MakerStats};
+use crate::errors::EscrowError;
+use crate::instructions::refund::RefundFlow;
+
+/// RefundTo 指令：
+///
+/// 与 refund 相同（共用 RefundFlow 的校验与流程），但退回的 Token A 与关闭账户的租金
+/// 都发送到 maker 指定的 destination（例如冷钱包）；
+/// escrow 设置了 rent_collector 时租金仍返还给 rent_collector
+#[derive(Accounts)]
+pub struct RefundTo<'info> {
+    /// Maker: 创建 escrow 的人，退款的发起者
+    #[account(mut)]
+    pub maker: Signer<'info>,
+
+    /// 退款目标钱包（接收 Token A 与 lamports）
+    #[account(mut)]
+    pub destination: SystemAccount<'info>,
+
+    /// Escrow PDA：存储交易条款
+    /// close = rent_collector 表示关闭后 lamports 发送给 rent_collector（默认 destination）
+    #[account(
+        mut,
+        close = rent_collector,
+        seeds = [b"escrow", escrow.creator.as_ref(), escrow.seed.to_le_bytes().as_ref()],
+        bump = escrow.bump,
+        constraint = escrow.has_canonical_bump() @ EscrowError::InvalidBump,
+        has_one = maker @ EscrowError::InvalidMaker,
+        has_one = mint_a @ EscrowError::InvalidMintA,
+        constraint = !escrow.is_basket() @ EscrowError::BasketMismatch,
+        constraint = escrow.is_active() @ EscrowError::EscrowNotActive,
+        constraint = !escrow.in_progress @ EscrowError::ReentrancyDetected,
+    )]
+    pub escrow: Box<Account<'info, Escrow>>,
+
+    /// CHECK: 仅接收关闭 vault / escrow 返还的 lamports，
+    /// 地址为 escrow.rent_collector（未设置时为 destination）
+    #[account(
+        mut,
+        address = escrow.rent_destination(destination.key()),
+    )]
+    pub rent_collector: UncheckedAccount<'info>,
+
+    /// CHECK: 全局 Config PDA（与 make 相同，未初始化时为空账户），
+    /// 仅读取撤单费、treasury 与挂单数上限（见 RefundTerms::load）
+    #[account(
+        seeds = [b"config"],
+        bump,
+    )]
+    pub config: UncheckedAccount<'info>,
+
+    /// maker 统计 PDA（可选，传入时累加 total_refunded；config 限制挂单数时必须传入）
+    #[account(
+        mut,
+        seeds = [b"stats", maker.key().as_ref()],
+        bump = maker_stats.bump,
+    )]
+    pub maker_stats: Option<Box<Account<'info, MakerStats>>>,
+
+    /// CHECK: 仅接收撤单费 lamports，config 已初始化时地址必须为 config.treasury
+    #[account(mut)]
+    pub treasury: UncheckedAccount<'info>,
+
+    /// Token A 的 mint
+    #[account(
+        mint::token_program = token_program,
+        constraint = mint_a.decimals == escrow.mint_a_decimals @ EscrowError::DecimalsMismatch,
+    )]
+    pub mint_a: Box<InterfaceAccount<'info, Mint>>,
+
+    /// Vault：escrow PDA 持有的 Token A
+    #[account(
+        mut,
+        associated_token::mint = mint_a,
+        associated_token::authority = escrow,
+        associated_token::token_program = token_program
+    )]
+    pub vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Destination 的 Token A ATA（接收退款，mint 必须为 mint_a）
+    #[account(
+        init_if_needed,
+        payer = maker,
+        associated_token::mint = mint_a,
+        associated_token::authority = destination,
+        associated_token::token_program = token_program,
+        address = get_associated_token_address_with_program_id(
+            &destination.key(),
+            &mint_a.key(),
+            &token_program.key(),
+        ) @ EscrowError::NonCanonicalAta,
+    )]
+    pub destination_ata_a: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Programs（associated_token_program 显式约束地址）
+    #[account(address = anchor_spl::associated_token::ID)]
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> RefundTo<'info> {
+    /// 退款流程使用的账户（Token A 退回 destination_ata_a）
+    fn flow(&mut self) -> RefundFlow<'_, 'info> {
+        RefundFlow {
+            maker: &self.maker,
+            escrow: &mut self.escrow,
+            rent_collector: &self.rent_collector,
+            config: &self.config,
+            maker_stats: &mut self.maker_stats,
+            treasury: &self.treasury,
+            mint_a: &self.mint_a,
+            vault: &self.vault,
+            recipient_ata_a: &self.destination_ata_a,
+            token_program: &self.token_program,
+            system_program: &self.system_program,
+        }
+    }
+}
+
+/// 校验与流程与 refund 相同（RefundFlow）；transfer hook 所需账户通过 remaining_accounts 传入
+pub fn handler<'info>(ctx: Context<'_, '_, 'info, 'info, RefundTo<'info>>) -> Result<()> {
+    let mut flow = ctx.accounts.flow();
+    let amount_refunded = flow.prepare_refund()?;
+
+    flow.refund_and_close_vault(ctx.remaining_accounts)?;
+
+    flow.finish_refund(amount_refunded)
+}
+
+#[cfg(test)]
+mod tests {
+    use anchor_spl::associated_token;
+    use anchor_spl::token::spl_token;
+
+    use super::*;
+    use crate::test_utils::*;
+
+    const REFUND_TO: u8 = 7;
+
+    fn refund_to(escrow: &Escrow, mint_a: &TestAccount) -> ProgramResult {
+        set_clock(1_000, 1);
+        let destination = Pubkey::new_unique();
+        let escrow_account = escrow_account(escrow);
+
+        let (result, _) = run(
+            &[REFUND_TO],
+            vec![
+                TestAccount::wallet(escrow.maker, 1_000_000_000).signer(),
+                TestAccount::wallet(destination, 0).writable(),
+                escrow_account.clone().writable(),
+                TestAccount::wallet(destination, 0).writable(),
+                no_config(),
+                none(),
+                TestAccount::wallet(Pubkey::new_unique(), 0).writable(),
+                mint_a.clone(),
+                fund_ata(&escrow_account.key, mint_a, escrow.deposit).writable(),
+                fund_ata(&destination, mint_a, 0).writable(),
+                TestAccount::program(associated_token::ID),
+                TestAccount::program(spl_token::ID),
+                TestAccount::program(anchor_lang::system_program::ID),
+            ],
+        );
+
+        result
+    }
+
+    #[test]
+    fn shares_refund_checks() {
+        let (mint_a, mint_b) = setup_mints(6, 6);
+        let mut escrow = make_escrow(&Pubkey::new_unique(), 2, &mint_a, &mint_b, 100, 50);
+
+        escrow.reserved = 10;
+        assert_error(refund_to(&escrow, &mint_a), EscrowError::InsufficientAvailable);
+
+        escrow.reserved = 0;
+        escrow.min_hold_until = 2_000;
+        assert_error(refund_to(&escrow, &mint_a), EscrowError::RefundTooEarly);
+    }
+}
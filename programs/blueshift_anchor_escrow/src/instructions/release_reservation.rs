@@ -0,0 +1,104 @@
+use anchor_lang::prelude::*;
+
+use crate::state::{Escrow, Reservation};
+use crate::errors::EscrowError;
+
+/// ReleaseReservation 指令：
+///
+/// 预留过期后任何人都可以释放：把锁定数量归还 escrow 的可成交部分，
+/// 关闭 Reservation（租金返还给预留的 taker）
+#[derive(Accounts)]
+pub struct ReleaseReservation<'info> {
+    /// 发起释放的任意账户
+    pub caller: Signer<'info>,
+
+    /// Escrow 状态账户（扣减 reserved）
+    #[account(
+        mut,
+        address = reservation.escrow,
+    )]
+    pub escrow: Box<Account<'info, Escrow>>,
+
+    /// 过期的预留 PDA
+    #[account(
+        mut,
+        close = taker,
+        seeds = [b"reservation", escrow.key().as_ref(), reservation.taker.as_ref()],
+        bump = reservation.bump,
+    )]
+    pub reservation: Account<'info, Reservation>,
+
+    /// CHECK: 接收关闭 Reservation 返还的 lamports，必须是 reservation.taker
+    #[account(
+        mut,
+        address = reservation.taker,
+    )]
+    pub taker: UncheckedAccount<'info>,
+}
+
+/// ReleaseReservation 指令入口
+pub fn handler(ctx: Context<ReleaseReservation>) -> Result<()> {
+    let reservation = &ctx.accounts.reservation;
+    require!(
+        reservation.is_expired(Clock::get()?.unix_timestamp),
+        EscrowError::ReservationNotExpired
+    );
+
+    let escrow = &mut ctx.accounts.escrow;
+    escrow.reserved = escrow
+        .reserved
+        .checked_sub(reservation.amount)
+        .ok_or(error!(EscrowError::MathOverflow))?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::*;
+
+    const RELEASE_RESERVATION: u8 = 42;
+
+    fn release(
+        escrow: &Escrow,
+        reservation: TestAccount,
+    ) -> (ProgramResult, Vec<AccountInfo<'static>>) {
+        let taker = load_account::<Reservation>(reservation.clone()).taker;
+
+        run(
+            &[RELEASE_RESERVATION],
+            vec![
+                TestAccount::wallet(Pubkey::new_unique(), 0).signer(),
+                escrow_account(escrow).writable(),
+                reservation.writable(),
+                TestAccount::wallet(taker, 0).writable(),
+            ],
+        )
+    }
+
+    #[test]
+    fn anyone_releases_an_expired_reservation() {
+        let (mint_a, mint_b) = setup_mints(6, 6);
+        let mut escrow = make_escrow(&Pubkey::new_unique(), 1, &mint_a, &mint_b, 100, 50);
+        escrow.reserved = 40;
+        let reservation = reservation_account(&escrow, &Pubkey::new_unique(), 40, 1_500);
+        let rent = reservation.lamports;
+
+        set_clock(1_499, 1);
+        assert_error(
+            release(&escrow, reservation.clone()).0,
+            EscrowError::ReservationNotExpired,
+        );
+
+        set_clock(1_500, 1);
+        let (result, infos) = release(&escrow, reservation);
+        assert_eq!(result, Ok(()));
+
+        // 锁定数量归还 escrow，Reservation 关闭、租金返还给 taker
+        let stored = Escrow::try_deserialize(&mut &infos[1].data.borrow()[..]).unwrap();
+        assert_eq!((stored.reserved, stored.available()), (0, 100));
+        assert_eq!(infos[2].lamports(), 0);
+        assert_eq!(infos[3].lamports(), rent);
+    }
+}
@@ -0,0 +1,41 @@
+use anchor_lang::prelude::*;
+
+use crate::state::{Config, PairRegistry};
+use crate::errors::EscrowError;
+
+/// RemovePair 指令：
+///
+/// 配置管理员移除已登记的交易对，关闭 PairRegistry 并取回租金；
+/// 已创建的 escrow 不受影响，仍可正常 take / refund
+#[derive(Accounts)]
+pub struct RemovePair<'info> {
+    /// 配置管理员，接收关闭 PairRegistry 返还的 lamports
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    /// 全局 Config PDA
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        has_one = authority @ EscrowError::Unauthorized,
+    )]
+    pub config: Account<'info, Config>,
+
+    /// 交易对白名单 PDA
+    #[account(
+        mut,
+        close = authority,
+        seeds = [
+            b"pair",
+            pair_registry.mint_a.as_ref(),
+            pair_registry.mint_b.as_ref(),
+        ],
+        bump = pair_registry.bump,
+    )]
+    pub pair_registry: Account<'info, PairRegistry>,
+}
+
+/// RemovePair 指令入口
+pub fn handler(_ctx: Context<RemovePair>) -> Result<()> {
+    Ok(())
+}
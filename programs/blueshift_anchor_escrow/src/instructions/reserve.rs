@@ -0,0 +1,174 @@
+use anchor_lang::prelude::*;
+
+use crate::state::{Escrow, Reservation};
+use crate::errors::EscrowError;
+
+/// Reserve 指令：
+///
+/// 大额报价可被多个 taker 分份成交：taker 先锁定 escrow 剩余存入中的 amount 个 Token A，
+/// 锁定部分不会被其他 taker 成交或被 maker 撤回，taker 在 expires_at 之前通过 claim 成交；
+/// 过期未 claim 的预留任何人都可以通过 release_reservation 释放。
+/// 每个 taker 对同一 escrow 同时只能持有一个预留
+#[derive(Accounts)]
+pub struct Reserve<'info> {
+    /// 预留成交份额的 taker，支付 Reservation 账户租金
+    #[account(mut)]
+    pub taker: Signer<'info>,
+
+    /// Escrow 状态账户（累加 reserved）
+    #[account(
+        mut,
+        seeds = [b"escrow", escrow.creator.as_ref(), escrow.seed.to_le_bytes().as_ref()],
+        bump = escrow.bump,
+        constraint = !escrow.is_basket() @ EscrowError::BasketMismatch,
+        constraint = escrow.is_active() @ EscrowError::EscrowNotActive,
+        constraint = !escrow.in_progress @ EscrowError::ReentrancyDetected,
+    )]
+    pub escrow: Box<Account<'info, Escrow>>,
+
+    /// 成交预留 PDA
+    ///
+    /// PDA seeds:
+    /// - "reservation"
+    /// - escrow 公钥
+    /// - taker 公钥
+    #[account(
+        init,
+        payer = taker,
+        space = Reservation::INIT_SPACE + Reservation::DISCRIMINATOR.len(),
+        seeds = [b"reservation", escrow.key().as_ref(), taker.key().as_ref()],
+        bump,
+    )]
+    pub reservation: Account<'info, Reservation>,
+
+    /// System Program（创建 Reservation PDA）
+    pub system_program: Program<'info, System>,
+}
+
+/// 校验预留参数并在 escrow 上锁定 amount 个 Token A（累加 reserved）
+pub fn lock_fill(
+    escrow: &mut Escrow,
+    taker: &Pubkey,
+    amount: u64,
+    expires_at: i64,
+    now: i64,
+) -> Result<()> {
+    require!(!escrow.is_expired(now), EscrowError::OfferExpired);
+    require_keys_neq!(*taker, escrow.maker, EscrowError::MakerCannotTake);
+    require!(
+        escrow.is_taker_allowed(taker),
+        EscrowError::UnauthorizedTaker
+    );
+
+    require!(
+        expires_at > now
+            && expires_at <= now.saturating_add(Reservation::MAX_DURATION_SECONDS)
+            && (escrow.expiry == 0 || expires_at <= escrow.expiry),
+        EscrowError::InvalidReservationExpiry
+    );
+
+    // 锁定数量与 claim 时的成交数量相同，提前按单次成交限制校验
+    require_gt!(amount, 0, EscrowError::InvalidFillAmount);
+    require!(
+        amount <= escrow.available(),
+        EscrowError::InsufficientAvailable
+    );
+    require!(escrow.meets_min_fill(amount), EscrowError::FillBelowMinimum);
+    require!(escrow.within_max_fill(amount), EscrowError::FillAboveMaximum);
+
+    escrow.reserved = escrow
+        .reserved
+        .checked_add(amount)
+        .ok_or(error!(EscrowError::MathOverflow))?;
+
+    Ok(())
+}
+
+/// Reserve 指令入口
+///
+/// 参数说明：
+/// - amount: 锁定的 Token A 数量（不超过 escrow 未被锁定的剩余存入）
+/// - expires_at: 预留过期时间（unix 时间戳），最长 Reservation::MAX_DURATION_SECONDS，
+///   且不晚于 escrow 的过期时间
+pub fn handler(ctx: Context<Reserve>, amount: u64, expires_at: i64) -> Result<()> {
+    let now = Clock::get()?.unix_timestamp;
+    let escrow = &mut ctx.accounts.escrow;
+    let taker = ctx.accounts.taker.key();
+
+    lock_fill(escrow, &taker, amount, expires_at, now)?;
+
+    ctx.accounts.reservation.set_inner(Reservation {
+        escrow: escrow.key(),
+        taker,
+        amount,
+        expires_at,
+        bump: ctx.bumps.reservation,
+    });
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::instructions::take::quote_fill;
+    use crate::test_utils::*;
+
+    #[test]
+    fn reserve_locks_part_of_the_deposit() {
+        set_clock(1_000, 1);
+        let (mint_a, mint_b) = setup_mints(6, 6);
+        let mut escrow = make_escrow(&Pubkey::new_unique(), 1, &mint_a, &mint_b, 100, 50);
+        let (first, second) = (Pubkey::new_unique(), Pubkey::new_unique());
+
+        lock_fill(&mut escrow, &first, 40, 1_500, 1_000).unwrap();
+        assert_eq!((escrow.reserved, escrow.available()), (40, 60));
+
+        // 其他 taker 只能预留 / 成交未被锁定的部分
+        assert_err(
+            lock_fill(&mut escrow, &second, 61, 1_500, 1_000),
+            EscrowError::InsufficientAvailable,
+        );
+        let quote = |escrow: &Escrow, fill_amount| {
+            let escrow = load_account::<Escrow>(escrow_account(escrow));
+            quote_fill(&escrow, &default_config(), &second, None, fill_amount, u64::MAX, false)
+        };
+        assert_err(quote(&escrow, 61), EscrowError::InsufficientAvailable);
+        assert_eq!(quote(&escrow, 60).unwrap(), 30);
+
+        lock_fill(&mut escrow, &second, 60, 1_500, 1_000).unwrap();
+        assert_eq!((escrow.reserved, escrow.available()), (100, 0));
+    }
+
+    #[test]
+    fn reservation_expiry_is_bounded() {
+        set_clock(1_000, 1);
+        let (mint_a, mint_b) = setup_mints(6, 6);
+        let mut escrow = make_escrow(&Pubkey::new_unique(), 1, &mint_a, &mint_b, 100, 50);
+        let taker = Pubkey::new_unique();
+        let max = 1_000 + Reservation::MAX_DURATION_SECONDS;
+
+        // 必须晚于当前时间，且不超过最长有效期
+        for expires_at in [1_000, max + 1] {
+            assert_err(
+                lock_fill(&mut escrow, &taker, 10, expires_at, 1_000),
+                EscrowError::InvalidReservationExpiry,
+            );
+        }
+        // 不晚于 escrow 自身的过期时间
+        escrow.expiry = 1_200;
+        assert_err(
+            lock_fill(&mut escrow, &taker, 10, 1_201, 1_000),
+            EscrowError::InvalidReservationExpiry,
+        );
+        let maker = escrow.maker;
+        assert_err(
+            lock_fill(&mut escrow, &maker, 10, 1_200, 1_000),
+            EscrowError::MakerCannotTake,
+        );
+        assert_eq!(escrow.reserved, 0);
+
+        lock_fill(&mut escrow, &taker, 10, 1_200, 1_000).unwrap();
+        assert_eq!(escrow.reserved, 10);
+    }
+}
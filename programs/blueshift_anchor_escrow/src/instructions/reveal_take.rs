@@ -0,0 +1,192 @@
+use anchor_lang::prelude::*;
+
+use crate::state::TakeCommit;
+use crate::errors::EscrowError;
+use crate::instructions::take::*;
+
+/// RevealTake 指令：
+///
+/// 两步成交的第二步：公开 nonce，校验 commit_take 提交的 commitment
+/// （含 escrow、fill_amount 与 max_receive），然后按普通 take 流程成交，
+/// 并关闭 TakeCommit（租金返还给 taker）
+#[derive(Accounts)]
+pub struct RevealTake<'info> {
+    /// 与 take 完全相同的账户
+    pub take: Take<'info>,
+
+    /// commit_take 创建的承诺 PDA
+    #[account(
+        mut,
+        close = taker,
+        seeds = [b"commit", take.escrow.key().as_ref(), take.taker.key().as_ref()],
+        bump = take_commit.bump,
+    )]
+    pub take_commit: Account<'info, TakeCommit>,
+
+    /// CHECK: 接收关闭 TakeCommit 返还的 lamports，必须是 take.taker
+    #[account(
+        mut,
+        address = take.taker.key(),
+    )]
+    pub taker: UncheckedAccount<'info>,
+}
+
+/// RevealTake 指令入口
+///
+/// 参数说明：
+/// - nonce: commit_take 时使用的 32 字节随机数
+/// - fill_amount / max_receive: 见 Take::quote_fill，必须与 commit 时的取值一致
+///
+/// transfer hook 所需账户通过 remaining_accounts 传入
+pub fn handler<'info>(
+    ctx: Context<'_, '_, 'info, 'info, RevealTake<'info>>,
+    nonce: [u8; 32],
+    fill_amount: u64,
+    max_receive: u64,
+) -> Result<()> {
+    let commit = &ctx.accounts.take_commit;
+
+    // commit 之后至少等待 MIN_REVEAL_SLOT_GAP 个 slot，同一区块内 commit + reveal 无意义
+    require!(
+        commit.can_reveal(Clock::get()?.slot),
+        EscrowError::RevealTooEarly
+    );
+
+    let commitment = TakeCommit::commitment_for(
+        ctx.accounts.take.taker.key,
+        &ctx.accounts.take.escrow.key(),
+        fill_amount,
+        max_receive,
+        &nonce,
+    );
+    require!(
+        commitment == commit.commitment,
+        EscrowError::InvalidCommitment
+    );
+
+    let take = &mut ctx.accounts.take;
+    take.ensure_atas(false)?;
+
+    let amount_b = take.quote_fill(fill_amount, max_receive)?;
+
+    take.execute_fill(fill_amount, amount_b, &[], ctx.remaining_accounts)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::*;
+
+    const REVEAL_TAKE: u8 = 31;
+    const NONCE: [u8; 32] = [7; 32];
+
+    /// 100 Token A 换 50 Token B 的 escrow，taker 于 slot 10 承诺 (fill_amount, max_receive)
+    fn accounts(fill_amount: u64, max_receive: u64) -> Vec<TestAccount> {
+        let (mint_a, mint_b) = setup_mints(6, 6);
+        let escrow = make_escrow(&Pubkey::new_unique(), 1, &mint_a, &mint_b, 100, 50);
+        let escrow_key = escrow_account(&escrow).key;
+        let taker = Pubkey::new_unique();
+        let (commit_key, bump) = Pubkey::find_program_address(
+            &[TakeCommit::SEED_PREFIX, escrow_key.as_ref(), taker.as_ref()],
+            &crate::ID,
+        );
+        let commit = TakeCommit {
+            escrow: escrow_key,
+            taker,
+            commitment: TakeCommit::commitment_for(
+                &taker,
+                &escrow_key,
+                fill_amount,
+                max_receive,
+                &NONCE,
+            ),
+            slot: 10,
+            bump,
+        };
+
+        let config = default_config();
+        let mut accounts =
+            take_accounts(&taker, &escrow, &config, &mint_a, &mint_b, 1_000).into_vec();
+        accounts.push(program_account(commit_key, &commit).writable());
+        accounts.push(TestAccount::wallet(taker, 0).writable());
+
+        accounts
+    }
+
+    #[test]
+    fn commitment_binds_escrow_and_amounts() {
+        let taker = Pubkey::new_unique();
+        let escrow = Pubkey::new_unique();
+        let commitment = TakeCommit::commitment_for(&taker, &escrow, 40, 20, &NONCE);
+
+        assert_eq!(
+            TakeCommit::commitment_for(&taker, &escrow, 40, 20, &NONCE),
+            commitment
+        );
+        for other in [
+            TakeCommit::commitment_for(&Pubkey::new_unique(), &escrow, 40, 20, &NONCE),
+            TakeCommit::commitment_for(&taker, &Pubkey::new_unique(), 40, 20, &NONCE),
+            TakeCommit::commitment_for(&taker, &escrow, 41, 20, &NONCE),
+            TakeCommit::commitment_for(&taker, &escrow, 40, 21, &NONCE),
+            TakeCommit::commitment_for(&taker, &escrow, 40, 20, &[8; 32]),
+        ] {
+            assert_ne!(other, commitment);
+        }
+    }
+
+    #[test]
+    fn matching_preimage_proceeds_to_take() {
+        set_clock(1_000, 12);
+
+        // 承诺的 max_receive 低于报价：通过 commitment 校验后由 take 的滑点检查拒绝
+        // （宿主机上无法执行成交所需的 CPI）
+        let (result, _) = run(
+            &ix_data(REVEAL_TAKE, (NONCE, 100u64, 49u64)),
+            accounts(100, 49),
+        );
+
+        assert_error(result, EscrowError::SlippageExceeded);
+    }
+
+    #[test]
+    fn wrong_preimage_is_rejected() {
+        set_clock(1_000, 12);
+
+        for args in [(NONCE, 100u64, 60u64), (NONCE, 90, 50), ([8; 32], 100, 50)] {
+            let (result, _) = run(&ix_data(REVEAL_TAKE, args), accounts(100, 50));
+
+            assert_error(result, EscrowError::InvalidCommitment);
+        }
+    }
+
+    #[test]
+    fn reveal_waits_for_slot_gap() {
+        set_clock(1_000, 11);
+
+        let (result, _) = run(
+            &ix_data(REVEAL_TAKE, (NONCE, 100u64, 50u64)),
+            accounts(100, 50),
+        );
+
+        assert_error(result, EscrowError::RevealTooEarly);
+    }
+
+    #[test]
+    fn can_reveal_after_minimum_slot_gap() {
+        let commit = |slot| TakeCommit {
+            escrow: Pubkey::new_unique(),
+            taker: Pubkey::new_unique(),
+            commitment: [0; 32],
+            slot,
+            bump: 255,
+        };
+        let gap = TakeCommit::MIN_REVEAL_SLOT_GAP;
+
+        assert!(!commit(10).can_reveal(10));
+        assert!(!commit(10).can_reveal(10 + gap - 1));
+        assert!(commit(10).can_reveal(10 + gap));
+        assert!(commit(10).can_reveal(u64::MAX));
+        // slot 接近上限时饱和加法不会回绕成可立即揭示
+        assert!(!commit(u64::MAX - 1).can_reveal(u64::MAX - 1));
+    }
+}
@@ -0,0 +1,104 @@
+use anchor_lang::prelude::*;
+
+use crate::state::Escrow;
+use crate::errors::EscrowError;
+
+/// SetRecipient 指令：
+///
+/// maker 修改接收 Token B 的钱包（之后的 take 付款到该钱包的规范 ATA），
+/// refund 权利与租金接收方不受影响
+#[derive(Accounts)]
+pub struct SetRecipient<'info> {
+    /// 当前 maker
+    pub maker: Signer<'info>,
+
+    /// Escrow PDA
+    #[account(
+        mut,
+        seeds = [b"escrow", escrow.creator.as_ref(), escrow.seed.to_le_bytes().as_ref()],
+        bump = escrow.bump,
+        has_one = maker @ EscrowError::InvalidMaker,
+        constraint = escrow.is_active() @ EscrowError::EscrowNotActive,
+    )]
+    pub escrow: Account<'info, Escrow>,
+}
+
+/// SetRecipient 指令入口
+///
+/// 参数说明：
+/// - new_recipient: 新的收款钱包（Pubkey::default() 表示恢复为 maker）
+pub fn handler(ctx: Context<SetRecipient>, new_recipient: Pubkey) -> Result<()> {
+    ctx.accounts.escrow.proceeds_recipient = new_recipient;
+
+    // make 时预先创建的是原收款方的 ATA，新收款方的 ATA 回到由 take 按需创建
+    ctx.accounts.escrow.maker_ata_b_ready = false;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::*;
+
+    const SET_RECIPIENT: u8 = 28;
+
+    fn set_recipient(
+        escrow: &Escrow,
+        signer: Pubkey,
+        recipient: Pubkey,
+    ) -> (ProgramResult, Escrow) {
+        let (result, infos) = run(
+            &ix_data(SET_RECIPIENT, recipient),
+            vec![
+                TestAccount::wallet(signer, 0).signer(),
+                escrow_account(escrow).writable(),
+            ],
+        );
+        let stored = Escrow::try_deserialize(&mut &infos[1].data.borrow()[..]).unwrap();
+
+        (result, stored)
+    }
+
+    fn escrow() -> Escrow {
+        let (mint_a, mint_b) = setup_mints(6, 6);
+        let mut escrow = make_escrow(&Pubkey::new_unique(), 1, &mint_a, &mint_b, 100, 50);
+        escrow.maker_ata_b_ready = true;
+
+        escrow
+    }
+
+    #[test]
+    fn proceeds_default_to_maker() {
+        let escrow = escrow();
+
+        assert_eq!(escrow.proceeds_destination(), escrow.maker);
+    }
+
+    #[test]
+    fn maker_sets_custom_recipient() {
+        let escrow = escrow();
+        let recipient = Pubkey::new_unique();
+
+        let (result, stored) = set_recipient(&escrow, escrow.maker, recipient);
+
+        assert_eq!(result, Ok(()));
+        assert_eq!(stored.proceeds_destination(), recipient);
+        assert!(!stored.maker_ata_b_ready);
+
+        // Pubkey::default() 恢复为 maker
+        let (result, stored) = set_recipient(&stored, escrow.maker, Pubkey::default());
+        assert_eq!(result, Ok(()));
+        assert_eq!(stored.proceeds_destination(), escrow.maker);
+    }
+
+    #[test]
+    fn only_maker_can_set_recipient() {
+        let escrow = escrow();
+
+        let (result, stored) = set_recipient(&escrow, Pubkey::new_unique(), Pubkey::new_unique());
+
+        assert_error(result, EscrowError::InvalidMaker);
+        assert_eq!(stored.proceeds_destination(), escrow.maker);
+    }
+}
@@ -0,0 +1,182 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_interface::{
+        close_account,
+        transfer_checked,
+        Mint,
+        TokenAccount,
+        TokenInterface,
+        TransferChecked,
+        CloseAccount,
+    },
+};
+
+use crate::state::Escrow;
+use crate::errors::EscrowError;
+
+/// SweepStranded 指令：
+///
+/// escrow 关闭后，vault 地址仍可被确定性推导，
+/// 若有人向该地址重新创建 ATA 并转入 Token A，资金会被卡住。
+/// 原 maker 可通过 seeds 重新证明所有权，取回余额并关闭 vault
+#[derive(Accounts)]
+#[instruction(seed: u64)]
+pub struct SweepStranded<'info> {
+    /// 原 maker（escrow PDA seeds 中的 maker）
+    #[account(mut)]
+    pub maker: Signer<'info>,
+
+    /// CHECK: 已关闭的 escrow PDA 地址，仅作为 vault 的 authority 签名。
+    /// 由 seeds 约束重新推导校验，handler 中要求账户已关闭
+    #[account(
+        seeds = [b"escrow", maker.key().as_ref(), seed.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub escrow: UncheckedAccount<'info>,
+
+    /// Token A 的 mint
+    #[account(
+        mint::token_program = token_program
+    )]
+    pub mint_a: Box<InterfaceAccount<'info, Mint>>,
+
+    /// 被重新创建的 vault（authority = escrow PDA）
+    #[account(
+        mut,
+        associated_token::mint = mint_a,
+        associated_token::authority = escrow,
+        associated_token::token_program = token_program
+    )]
+    pub vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Maker 的 Token A ATA（接收被卡住的 Token A）
+    #[account(
+        init_if_needed,
+        payer = maker,
+        associated_token::mint = mint_a,
+        associated_token::authority = maker,
+        associated_token::token_program = token_program
+    )]
+    pub maker_ata_a: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Programs
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> SweepStranded<'info> {
+    /// 将 vault 中全部 Token A 转给 maker，并关闭 vault
+    fn sweep_and_close_vault(&mut self, seed: u64, bump: u8) -> Result<()> {
+        let seed_bytes = seed.to_le_bytes();
+        let signer_seeds: [&[&[u8]]; 1] = [&[
+            Escrow::SEED_PREFIX,
+            self.maker.key.as_ref(),
+            &seed_bytes,
+            &[bump],
+        ]];
+
+        transfer_checked(
+            CpiContext::new_with_signer(
+                self.token_program.to_account_info(),
+                TransferChecked {
+                    from: self.vault.to_account_info(),
+                    to: self.maker_ata_a.to_account_info(),
+                    mint: self.mint_a.to_account_info(),
+                    authority: self.escrow.to_account_info(),
+                },
+                &signer_seeds,
+            ),
+            self.vault.amount,
+            self.mint_a.decimals,
+        )?;
+
+        close_account(
+            CpiContext::new_with_signer(
+                self.token_program.to_account_info(),
+                CloseAccount {
+                    account: self.vault.to_account_info(),
+                    authority: self.escrow.to_account_info(),
+                    destination: self.maker.to_account_info(),
+                },
+                &signer_seeds,
+            ),
+        )?;
+
+        Ok(())
+    }
+}
+
+/// SweepStranded 指令入口
+///
+/// 参数说明：
+/// - seed: 原 escrow 的 seed
+pub fn handler(ctx: Context<SweepStranded>, seed: u64) -> Result<()> {
+    // escrow 仍存在时应使用 refund，避免绕过 escrow 状态
+    // （close_escrow 要求 vault 为空，因此有余额的 vault 只能通过 refund / take 取出）
+    require!(
+        ctx.accounts.escrow.data_is_empty() && ctx.accounts.escrow.owner == &System::id(),
+        EscrowError::EscrowStillOpen
+    );
+
+    require!(
+        !ctx.accounts.vault.is_frozen() && !ctx.accounts.maker_ata_a.is_frozen(),
+        EscrowError::AccountFrozen
+    );
+
+    ctx.accounts.sweep_and_close_vault(seed, ctx.bumps.escrow)?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use anchor_spl::associated_token;
+    use anchor_spl::token::spl_token;
+
+    use super::*;
+    use crate::test_utils::*;
+
+    const SWEEP_STRANDED: u8 = 9;
+
+    fn accounts(maker: &Pubkey, escrow: TestAccount, mint_a: &TestAccount) -> Vec<TestAccount> {
+        vec![
+            TestAccount::wallet(*maker, 1_000_000_000).signer(),
+            escrow.clone(),
+            mint_a.clone(),
+            fund_ata(&escrow.key, mint_a, 100).writable(),
+            fund_ata(maker, mint_a, 0).writable(),
+            TestAccount::program(associated_token::ID),
+            TestAccount::program(spl_token::ID),
+            TestAccount::program(anchor_lang::system_program::ID),
+        ]
+    }
+
+    #[test]
+    fn funded_vault_of_open_escrow_cannot_be_swept() {
+        let (mint_a, mint_b) = setup_mints(6, 6);
+        let escrow = make_escrow(&Pubkey::new_unique(), 3, &mint_a, &mint_b, 100, 50);
+
+        let (result, _) = run(
+            &ix_data(SWEEP_STRANDED, 3u64),
+            accounts(&escrow.maker, escrow_account(&escrow), &mint_a),
+        );
+
+        assert_error(result, EscrowError::EscrowStillOpen);
+    }
+
+    #[test]
+    fn only_original_maker_can_sweep() {
+        let (mint_a, mint_b) = setup_mints(6, 6);
+        let escrow = make_escrow(&Pubkey::new_unique(), 3, &mint_a, &mint_b, 100, 50);
+        let closed = TestAccount::wallet(escrow_account(&escrow).key, 0);
+
+        let (result, _) = run(
+            &ix_data(SWEEP_STRANDED, 3u64),
+            accounts(&Pubkey::new_unique(), closed, &mint_a),
+        );
+
+        assert_error(result, ErrorCode::ConstraintSeeds);
+    }
+}
@@ -1,8 +1,18 @@
 use anchor_lang::prelude::*;
 use anchor_spl::{
-    associated_token::AssociatedToken,
+    associated_token::{create, get_associated_token_address_with_program_id, AssociatedToken, Create},
     token_interface::{
-        transfer_checked,
+        spl_token_2022::{
+            extension::{
+                transfer_fee::TransferFeeConfig,
+                BaseStateWithExtensions,
+                ExtensionType,
+                StateWithExtensions,
+            },
+            onchain::invoke_transfer_checked,
+            state::{Account as SplAccount, Mint as SplMint},
+        },
+        get_mint_extension_data,
         close_account,
         Mint,
         TokenAccount,
@@ -12,15 +22,32 @@ use anchor_spl::{
     },
 };
 
-use crate::state::Escrow;
+use crate::state::{Config, Escrow, EscrowStatus, MakerStats};
 use crate::errors::EscrowError;
+use crate::events::EscrowTaken;
 
 /// Take 指令：
-/// - taker 用 Token B 换取 Vault 中的 Token A
-/// - Token B：taker -> maker
+/// - taker 用 Token B 换取 Vault 中的 Token A（支持部分成交）
+/// - Token B：taker -> maker 的收款钱包（按成交比例计算，扣除协议费）
+/// - 协议费（Token B）：taker -> treasury（传入 referrer_ata_b 时部分转给推荐人）
 /// - Token A：vault -> taker
-/// - 关闭 vault
-/// - 关闭 escrow（lamports 返还给 maker）
+/// - 全部成交后关闭 vault 和 escrow（escrow 的 lamports 返还给 rent_collector，
+///   vault 的 lamports 返还给 vault_rent_payer，均默认 maker）；
+///   他人额外转入 vault 的 Token A 在关闭前一并转给 taker
+///
+/// 租金：taker_ata_a / taker_ata_b / maker_ata_b / treasury_ata_b 不存在时由 taker 付费创建，
+/// 最坏情况下 taker 需预留 4 个 ATA 的租金（maker_ata_b 可通过 require_maker_ata_exists 拒绝代付）。
+/// 四个账户均为 UncheckedAccount，由 handler 中的 ensure_atas 创建：创建任何一个之前
+/// 先合计全部缺失 ATA 的租金一次预检（InsufficientLamportsForRent），不会创建到一半才失败
+///
+/// ImmutableOwner：ensure_atas 通过 Associated Token Program 创建 ATA，
+/// Token-2022 下 ATA 程序会自动初始化 ImmutableOwner 扩展，
+/// 无需在本程序中额外处理；已存在的 ATA 同样满足（ATA 不能被 SetAuthority 改变 owner）
+///
+/// 栈空间：Account / InterfaceAccount 把反序列化后的数据直接放在结构体里（64 位布局下
+/// Account<Escrow> 552 字节、InterfaceAccount<TokenAccount> 216、Account<Config> 136、
+/// InterfaceAccount<Mint> 128）。只有最大的 escrow 与可选账户（缺省时 Box 仅占 8 字节）保持 Box，
+/// 其余直接持有，Take 共 744 字节，不到 SBF 4KB 栈帧的一半（见 account_structs_fit_stack_frame）
 #[derive(Accounts)]
 pub struct Take<'info> {
     /// 接受报价的用户（支付 Token B）
@@ -33,25 +60,76 @@ pub struct Take<'info> {
 
     /// Escrow 状态账户
     /// - 使用 PDA 校验
-    /// - 执行完成后关闭，lamports 返还给 maker
+    /// - 部分成交时更新剩余条款
+    /// - 全部成交后手动关闭，lamports 返还给 rent_collector
     #[account(
         mut,
-        close = maker,
-        seeds = [b"escrow", maker.key().as_ref(), escrow.seed.to_le_bytes().as_ref()],
+        seeds = [b"escrow", escrow.creator.as_ref(), escrow.seed.to_le_bytes().as_ref()],
         bump = escrow.bump,
         has_one = maker @ EscrowError::InvalidMaker,
         has_one = mint_a @ EscrowError::InvalidMintA,
-        has_one = mint_b @ EscrowError::InvalidMintB,
+        constraint = !escrow.is_basket() @ EscrowError::BasketMismatch,
+        constraint = escrow.is_active() @ EscrowError::EscrowNotActive,
+        constraint = !escrow.in_progress @ EscrowError::ReentrancyDetected,
     )]
     pub escrow: Box<Account<'info, Escrow>>,
 
+    /// CHECK: 仅接收关闭 escrow 返还的 lamports，
+    /// 地址为 escrow.rent_collector（未设置时为 maker）
+    #[account(
+        mut,
+        address = escrow.rent_destination(escrow.maker),
+    )]
+    pub rent_collector: UncheckedAccount<'info>,
+
+    /// CHECK: 仅接收关闭 vault 返还的 lamports，
+    /// 地址为 escrow.vault_rent_payer（未设置时与 rent_collector 相同）
+    #[account(
+        mut,
+        address = escrow.vault_rent_destination(escrow.maker),
+    )]
+    pub vault_rent_payer: UncheckedAccount<'info>,
+
+    /// CHECK: 接收 Token B 的钱包（仅作为 maker_ata_b 的 authority），
+    /// 地址为 escrow.proceeds_recipient（未设置时为 maker）
+    #[account(address = escrow.proceeds_destination())]
+    pub proceeds_recipient: UncheckedAccount<'info>,
+
+    /// 全局配置（协议费率与 treasury）
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, Config>,
+
+    /// maker 统计 PDA（可选，传入时全部成交会累加 total_taken；config 限制挂单数时必须传入）
+    #[account(
+        mut,
+        seeds = [b"stats", maker.key().as_ref()],
+        bump = maker_stats.bump,
+    )]
+    pub maker_stats: Option<Box<Account<'info, MakerStats>>>,
+
+    /// CHECK: 仅作为 treasury ATA 的 authority，地址由 config.treasury 约束
+    #[account(address = config.treasury)]
+    pub treasury: UncheckedAccount<'info>,
+
     /// ===== Token Mints =====
 
     /// Token A（从 vault 转给 taker）
-    pub mint_a: Box<InterfaceAccount<'info, Mint>>,
+    #[account(
+        mint::token_program = token_program,
+        constraint = mint_a.decimals == escrow.mint_a_decimals @ EscrowError::DecimalsMismatch,
+    )]
+    pub mint_a: InterfaceAccount<'info, Mint>,
 
-    /// Token B（从 taker 转给 maker）
-    pub mint_b: Box<InterfaceAccount<'info, Mint>>,
+    /// Token B（从 taker 转给 maker），可以是 escrow.mint_b 或 escrow.mint_b_alt
+    /// （在 quote_fill 中校验，失败时日志输出期望的 mint）
+    #[account(
+        mint::token_program = token_program,
+        constraint = escrow.mint_b_decimals_match(&mint_b.key(), mint_b.decimals) @ EscrowError::DecimalsMismatch,
+    )]
+    pub mint_b: InterfaceAccount<'info, Mint>,
 
     /// ===== Token Accounts =====
 
@@ -62,40 +140,72 @@ pub struct Take<'info> {
         associated_token::authority = escrow,
         associated_token::token_program = token_program
     )]
-    pub vault: Box<InterfaceAccount<'info, TokenAccount>>,
+    pub vault: InterfaceAccount<'info, TokenAccount>,
 
-    /// Taker 的 Token A ATA（接收 vault 的 Token A）
+    /// CHECK: Taker 的 Token A ATA（接收 vault 的 Token A），可能尚不存在。
+    /// 地址约束为规范 ATA，由 ensure_atas 创建
     #[account(
-        init_if_needed,
-        payer = taker,
-        associated_token::mint = mint_a,
-        associated_token::authority = taker,
-        associated_token::token_program = token_program
+        mut,
+        address = get_associated_token_address_with_program_id(
+            &taker.key(),
+            &mint_a.key(),
+            &token_program.key(),
+        ) @ EscrowError::NonCanonicalAta,
     )]
-    pub taker_ata_a: Box<InterfaceAccount<'info, TokenAccount>>,
+    pub taker_ata_a: UncheckedAccount<'info>,
 
-    /// Taker 的 Token B ATA（支付给 maker）
+    /// CHECK: Taker 的 Token B ATA（支付给 maker），可能尚不存在（例如 take_with_sol 的临时
+    /// wSOL 账户）。地址约束为规范 ATA，由 ensure_atas 创建
     #[account(
         mut,
-        associated_token::mint = mint_b,
-        associated_token::authority = taker,
-        associated_token::token_program = token_program
+        address = get_associated_token_address_with_program_id(
+            &taker.key(),
+            &mint_b.key(),
+            &token_program.key(),
+        ) @ EscrowError::NonCanonicalAta,
     )]
-    pub taker_ata_b: Box<InterfaceAccount<'info, TokenAccount>>,
+    pub taker_ata_b: UncheckedAccount<'info>,
 
-    /// Maker 的 Token B ATA（接收 taker 的 Token B）
+    /// CHECK: proceeds_recipient 的 Token B ATA（接收 taker 的 Token B），可能尚不存在。
+    /// 地址约束为规范 ATA，由 ensure_atas 按 taker 的选择创建或拒绝
     #[account(
-        init_if_needed,
-        payer = taker,
-        associated_token::mint = mint_b,
-        associated_token::authority = maker,
-        associated_token::token_program = token_program
+        mut,
+        address = get_associated_token_address_with_program_id(
+            &proceeds_recipient.key(),
+            &mint_b.key(),
+            &token_program.key(),
+        ) @ EscrowError::NonCanonicalAta,
     )]
-    pub maker_ata_b: Box<InterfaceAccount<'info, TokenAccount>>,
+    pub maker_ata_b: UncheckedAccount<'info>,
+
+    /// CHECK: Treasury 的 Token B ATA（接收协议费），可能尚不存在。
+    /// 地址约束为规范 ATA，由 ensure_atas 创建
+    #[account(
+        mut,
+        address = get_associated_token_address_with_program_id(
+            &treasury.key(),
+            &mint_b.key(),
+            &token_program.key(),
+        ) @ EscrowError::NonCanonicalAta,
+    )]
+    pub treasury_ata_b: UncheckedAccount<'info>,
+
+    /// 推荐人的 Token B 账户（可选，传入时按 config.referral_bps 分得部分协议费）
+    #[account(
+        mut,
+        token::mint = mint_b,
+        token::token_program = token_program,
+    )]
+    pub referrer_ata_b: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+
+    /// taker 持有门槛 token 的账户（escrow 设置了 gate_mint 时必须传入，
+    /// mint / owner / 余额在 quote_fill 中校验；可属于任意 token program）
+    pub gate_ata: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
 
     /// ===== Programs =====
 
-    /// 创建 ATA 所需
+    /// 创建 ATA 所需（显式约束地址，防止将来放宽类型后被替换为伪造程序）
+    #[account(address = anchor_spl::associated_token::ID)]
     pub associated_token_program: Program<'info, AssociatedToken>,
 
     /// Token CPI（SPL Token / Token-2022）
@@ -107,75 +217,1679 @@ pub struct Take<'info> {
 
 impl<'info> Take<'info> {
     /// 将 Token B 从 taker 转给 maker
-    fn transfer_to_maker(&mut self) -> Result<()> {
-        transfer_checked(
-            CpiContext::new(
-                self.token_program.to_account_info(),
-                TransferChecked {
-                    from: self.taker_ata_b.to_account_info(),
-                    to: self.maker_ata_b.to_account_info(),
-                    mint: self.mint_b.to_account_info(),
-                    authority: self.taker.to_account_info(),
-                },
-            ),
-            self.escrow.receive,      // maker 期望收到的 Token B 数量
-            self.mint_b.decimals,     // 精度校验
+    fn transfer_to_maker(
+        &mut self,
+        amount: u64,
+        remaining: &[AccountInfo<'info>],
+    ) -> Result<()> {
+        msg!(
+            "transfer_to_maker: escrow={} mint_b={} amount={}",
+            self.escrow.key(),
+            self.mint_b.key(),
+            amount
+        );
+
+        cu_profile!(
+            "take: transfer_to_maker",
+            transfer_checked_with_hook(
+                CpiContext::new(
+                    self.token_program.to_account_info(),
+                    TransferChecked {
+                        from: self.taker_ata_b.to_account_info(),
+                        to: self.maker_ata_b.to_account_info(),
+                        mint: self.mint_b.to_account_info(),
+                        authority: self.taker.to_account_info(),
+                    },
+                )
+                .with_remaining_accounts(remaining.to_vec()),
+                amount,                   // 含 Token-2022 转账费的毛额
+                self.mint_b.decimals,     // 精度校验
+            )
         )?;
 
+        msg!("transfer_to_maker: done");
+
         Ok(())
     }
 
-    /// 从 vault 提取 Token A 给 taker，并关闭 vault
-    fn withdraw_and_close_vault(&mut self) -> Result<()> {
+    /// 将协议费（Token B）从 taker 转给 to（treasury 或推荐人）
+    fn transfer_fee(
+        &self,
+        to: AccountInfo<'info>,
+        fee: u64,
+        remaining: &[AccountInfo<'info>],
+    ) -> Result<()> {
+        cu_profile!(
+            "take: transfer_fee",
+            transfer_checked_with_hook(
+                CpiContext::new(
+                    self.token_program.to_account_info(),
+                    TransferChecked {
+                        from: self.taker_ata_b.to_account_info(),
+                        to,
+                        mint: self.mint_b.to_account_info(),
+                        authority: self.taker.to_account_info(),
+                    },
+                )
+                .with_remaining_accounts(remaining.to_vec()),
+                fee,
+                self.mint_b.decimals,
+            )
+        )?;
+
+        Ok(())
+    }
+
+    /// 纵深防御：即使将来放宽 vault 的账户约束，也不会用 escrow 签名转出别人的账户
+    fn check_vault_authority(&self) -> Result<()> {
+        require_keys_eq!(
+            self.vault.owner,
+            self.escrow.key(),
+            EscrowError::InvalidVaultAuthority
+        );
+
+        Ok(())
+    }
+
+    /// 从 vault 提取 Token A 给 taker
+    ///
+    /// 若本次提取后 escrow 剩余存入为 0，则同时关闭 vault
+    ///
+    /// legs 为 (接收账户, 数量) 列表，数量之和必须等于 amount（0 数量的接收方跳过）
+    fn withdraw_and_close_vault(
+        &mut self,
+        amount: u64,
+        legs: &[(AccountInfo<'info>, u64)],
+        remaining: &[AccountInfo<'info>],
+    ) -> Result<()> {
+        self.check_vault_authority()?;
+
         // escrow PDA 作为 signer
-        let signer_seeds: [&[&[u8]]; 1] = [&[
-            b"escrow",
-            self.maker.key.as_ref(),
-            &self.escrow.seed.to_le_bytes(),
-            &[self.escrow.bump],
-        ]];
-
-        // 1️⃣ Vault -> Taker（Token A）
-        transfer_checked(
-            CpiContext::new_with_signer(
-                self.token_program.to_account_info(),
-                TransferChecked {
-                    from: self.vault.to_account_info(),
-                    to: self.taker_ata_a.to_account_info(),
-                    mint: self.mint_a.to_account_info(),
-                    authority: self.escrow.to_account_info(),
-                },
-                &signer_seeds,
-            ),
-            self.vault.amount,        // vault 中全部 Token A
-            self.mint_a.decimals,
+        let seed_bytes = self.escrow.seed.to_le_bytes();
+        let seeds = self.escrow.signer_seeds(&seed_bytes);
+        let signer_seeds: [&[&[u8]]; 1] = [&seeds];
+
+        msg!(
+            "withdraw: escrow={} amount_a={}",
+            self.escrow.key(),
+            amount
+        );
+
+        let total = legs
+            .iter()
+            .try_fold(0u64, |sum, (_, leg)| sum.checked_add(*leg))
+            .ok_or(error!(EscrowError::MathOverflow))?;
+        require_eq!(total, amount, EscrowError::InvalidSplit);
+
+        // 1️⃣ Vault -> Taker / taker 指定的接收账户（Token A）
+        for (to, leg) in legs.iter().filter(|(_, leg)| *leg > 0) {
+            cu_profile!(
+                "take: withdraw",
+                transfer_checked_with_hook(
+                    CpiContext::new_with_signer(
+                        self.token_program.to_account_info(),
+                        TransferChecked {
+                            from: self.vault.to_account_info(),
+                            to: to.clone(),
+                            mint: self.mint_a.to_account_info(),
+                            authority: self.escrow.to_account_info(),
+                        },
+                        &signer_seeds,
+                    )
+                    .with_remaining_accounts(remaining.to_vec()),
+                    *leg,
+                    self.mint_a.decimals,
+                )
+            )?;
+        }
+
+        // 部分成交：vault 保持打开
+        if amount < self.escrow.deposit {
+            msg!("withdraw: done, vault kept open");
+            return Ok(());
+        }
+
+        // 2️⃣ 关闭 vault（额外转入的 Token A 归 taker），lamports 返还给 vault_rent_payer
+        cu_profile!(
+            "take: close_vault",
+            sweep_and_close_vault(
+                CpiContext::new_with_signer(
+                    self.token_program.to_account_info(),
+                    CloseAccount {
+                        account: self.vault.to_account_info(),
+                        authority: self.escrow.to_account_info(),
+                        destination: self.vault_rent_payer.to_account_info(),
+                    },
+                    &signer_seeds,
+                )
+                .with_remaining_accounts(remaining.to_vec()),
+                self.mint_a.to_account_info(),
+                self.mint_a.decimals,
+                self.taker_ata_a.to_account_info(),
+            )
         )?;
 
-        // 2️⃣ 关闭 vault，lamports 返还给 maker
-        close_account(
+        msg!("withdraw: done, vault closed");
+
+        Ok(())
+    }
+
+    /// 扣减 escrow 剩余条款；全部成交后关闭 escrow，lamports 返还给 rent_collector
+    fn settle_escrow(&mut self, fill_amount: u64, amount_b: u64) -> Result<()> {
+        self.escrow.in_progress = false;
+
+        let alt = self.pays_with_alt_mint();
+        self.escrow.apply_fill(fill_amount, amount_b, alt)?;
+
+        if self.escrow.deposit > 0 {
+            self.escrow.status = EscrowStatus::PartiallyFilled;
+        } else {
+            self.escrow.status = EscrowStatus::Filled;
+            self.escrow.close(self.rent_collector.to_account_info())?;
+
+            require!(
+                self.maker_stats.is_some() || !self.config.tracks_open_escrows(),
+                EscrowError::MakerStatsRequired
+            );
+            if let Some(stats) = self.maker_stats.as_mut() {
+                stats.record_taken()?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// 确保 taker_ata_a / taker_ata_b / maker_ata_b / treasury_ata_b 均已存在
+    ///
+    /// maker_ata_b 不存在时：require_maker_ata_exists 为 true 或 make 时已预先创建
+    /// （maker_ata_b_ready，之后被 maker 关闭）则返回 MakerAtaMissing，否则与其余缺失的 ATA 一样
+    /// 由 taker 付费创建（即代付 maker 的租金）；创建前合计全部租金一次预检。
+    /// 通过 ATA 程序创建，Token-2022 账户会带上 ImmutableOwner 扩展
+    pub fn ensure_atas(&mut self, require_maker_ata_exists: bool) -> Result<()> {
+        if self.maker_ata_b.data_is_empty() {
+            require!(
+                !require_maker_ata_exists && !self.escrow.maker_ata_b_ready,
+                EscrowError::MakerAtaMissing
+            );
+        }
+
+        let missing = self.missing_atas();
+        if missing.is_empty() {
+            return Ok(());
+        }
+
+        // 租金预检：余额不足时给出所需 lamports，而不是创建到一半时 ATA 程序内部的转账失败
+        let rent = self.missing_ata_rent()?;
+        if self.taker.lamports() < rent {
+            msg!(
+                "InsufficientLamportsForRent: need {} lamports for {} ATAs, have {}",
+                rent,
+                missing.len(),
+                self.taker.lamports()
+            );
+            return err!(EscrowError::InsufficientLamportsForRent);
+        }
+
+        for (associated_token, authority, mint) in missing {
+            cu_profile!(
+                "take: create_ata",
+                create(CpiContext::new(
+                    self.associated_token_program.to_account_info(),
+                    Create {
+                        payer: self.taker.to_account_info(),
+                        associated_token,
+                        authority,
+                        mint,
+                        system_program: self.system_program.to_account_info(),
+                        token_program: self.token_program.to_account_info(),
+                    },
+                ))
+            )?;
+        }
+
+        Ok(())
+    }
+
+    /// 尚不存在的 ATA 及其 (authority, mint)；地址相同的账户只列一次
+    /// （例如 treasury 为 taker 时 treasury_ata_b 即 taker_ata_b）
+    fn missing_atas(&self) -> Vec<(AccountInfo<'info>, AccountInfo<'info>, AccountInfo<'info>)> {
+        let candidates = [
+            (&self.taker_ata_a, self.taker.to_account_info(), &self.mint_a),
+            (&self.taker_ata_b, self.taker.to_account_info(), &self.mint_b),
+            (&self.maker_ata_b, self.proceeds_recipient.to_account_info(), &self.mint_b),
+            (&self.treasury_ata_b, self.treasury.to_account_info(), &self.mint_b),
+        ];
+
+        let mut missing: Vec<(AccountInfo<'info>, AccountInfo<'info>, AccountInfo<'info>)> =
+            Vec::with_capacity(candidates.len());
+        for (ata, authority, mint) in candidates {
+            if ata.data_is_empty() && !missing.iter().any(|(other, _, _)| other.key == ata.key) {
+                missing.push((ata.to_account_info(), authority, mint.to_account_info()));
+            }
+        }
+
+        missing
+    }
+
+    /// 创建全部缺失 ATA 所需的租金合计（lamports）
+    fn missing_ata_rent(&self) -> Result<u64> {
+        let rent = Rent::get()?;
+
+        let mut total: u64 = 0;
+        for (_, _, mint) in self.missing_atas() {
+            total = total
+                .checked_add(rent.minimum_balance(ata_len(&mint)?))
+                .ok_or(error!(EscrowError::MathOverflow))?;
+        }
+
+        Ok(total)
+    }
+
+    /// taker 是否以备选 mint（mint_b_alt）支付
+    fn pays_with_alt_mint(&self) -> bool {
+        self.mint_b.key() != self.escrow.mint_b
+    }
+
+    /// 校验成交条件，并计算本次成交需支付的 Token B 数量
+    ///
+    /// 与 escrow 条款相关的校验见 quote_fill（take 的各变体共用），这里只额外校验 Take 特有的账户
+    ///
+    /// 参数说明：
+    /// - fill_amount: taker 希望获得的 Token A 数量（可小于剩余存入，即部分成交）
+    /// - max_receive: taker 本次愿意支付的 Token B 上限（滑点保护，u64::MAX 表示不检查）
+    pub fn quote_fill(&self, fill_amount: u64, max_receive: u64) -> Result<u64> {
+        let alt = self.escrow.check_mint_b(&self.mint_b.key())?;
+
+        // 纵深防御：make 已拒绝相同 mint，这里再次确认，防止将来的修改路径引入自我兑换
+        require_keys_neq!(
+            self.mint_a.key(),
+            self.mint_b.key(),
+            EscrowError::InvalidMintA
+        );
+
+        let amount_b = quote_fill(
+            &self.escrow,
+            &self.config,
+            self.taker.key,
+            self.gate_ata.as_deref(),
+            fill_amount,
+            max_receive,
+            alt,
+        )?;
+
+        // 付款账户与收款账户相同时转账是空操作，maker 实际收不到 Token B
+        require_keys_neq!(
+            self.taker_ata_b.key(),
+            self.maker_ata_b.key(),
+            EscrowError::DuplicateTokenAccount
+        );
+
+        // 冻结的 token 账户无法转账，在 CPI 之前给出明确错误（需在 ensure_atas 之后调用）
+        require!(
+            !self.vault.is_frozen()
+                && !load_token_account(&self.taker_ata_a)?.is_frozen()
+                && !load_token_account(&self.taker_ata_b)?.is_frozen()
+                && !load_token_account(&self.maker_ata_b)?.is_frozen(),
+            EscrowError::AccountFrozen
+        );
+
+        // vault 被清空时 taker 付款也拿不到 Token A，在转出 Token B 之前拒绝
+        require_gt!(self.vault.amount, 0, EscrowError::VaultEmpty);
+
+        // vault 余额低于本次应转出的数量（例如 Token-2022 永久代理人在链外转走了资金），
+        // 在 taker 付款之前拒绝
+        require!(
+            self.vault.amount >= fill_amount,
+            EscrowError::VaultBalanceMismatch
+        );
+
+        Ok(amount_b)
+    }
+
+    /// 执行成交：交换 Token A / Token B，并更新或关闭 escrow
+    ///
+    /// memo 为 taker 附言（不接受附言的入口传空），
+    /// remaining 为 mint_a / mint_b 的 transfer hook 所需账户（无 hook 时为空）
+    pub fn execute_fill(
+        &mut self,
+        fill_amount: u64,
+        amount_b: u64,
+        memo: &[u8],
+        remaining: &[AccountInfo<'info>],
+    ) -> Result<()> {
+        let legs = [(self.taker_ata_a.to_account_info(), fill_amount)];
+
+        self.execute_fill_to(fill_amount, amount_b, &legs, memo, remaining)
+    }
+
+    /// 与 execute_fill 相同，但 Token A 按 legs 分别转给多个接收账户（数量之和为 fill_amount）
+    pub fn execute_fill_to(
+        &mut self,
+        fill_amount: u64,
+        amount_b: u64,
+        legs: &[(AccountInfo<'info>, u64)],
+        memo: &[u8],
+        remaining: &[AccountInfo<'info>],
+    ) -> Result<()> {
+        self.config.check_take_memo(memo)?;
+        if !memo.is_empty() {
+            msg!("take memo: {}", String::from_utf8_lossy(memo));
+        }
+
+        // 在任何 CPI 之前把 in_progress 写入账户数据：
+        // Token-2022 transfer hook 等若回调本程序，重新反序列化 escrow 时即被拒绝
+        self.escrow.in_progress = true;
+        self.escrow.exit(&crate::ID)?;
+
+        // 协议费从本次成交的 Token B 中扣除
+        let fee = self.config.fee_for(&self.escrow, amount_b)?;
+        let maker_net = amount_b
+            .checked_sub(fee)
+            .ok_or(error!(EscrowError::MathOverflow))?;
+
+        // Token-2022 转账费由 taker 承担，保证 maker 实收净额
+        let maker_gross = gross_up_transfer_fee(&self.mint_b.to_account_info(), maker_net)?;
+
+        // 与 make 的余额预检对称：taker 余额不足时直接给出明确错误，而不是在 CPI 中途失败
+        // （take_with_sol 刚包装过 wSOL，需重新读取余额）
+        let taker_cost = maker_gross
+            .checked_add(fee)
+            .ok_or(error!(EscrowError::MathOverflow))?;
+        require!(
+            load_token_account(&self.taker_ata_b)?.amount >= taker_cost,
+            EscrowError::InsufficientFunds
+        );
+
+        // 推荐奖励从协议费中拆分，未传入推荐人账户时全部归 treasury
+        let referral_fee = match self.referrer_ata_b.as_ref() {
+            Some(_) => self.config.referral_fee_for(amount_b, fee)?,
+            None => 0,
+        };
+        let treasury_fee = fee
+            .checked_sub(referral_fee)
+            .ok_or(error!(EscrowError::MathOverflow))?;
+
+        // 1️⃣ taker -> maker（Token B，扣除协议费）+ taker -> treasury / 推荐人（协议费）
+        self.transfer_to_maker(maker_gross, remaining)?;
+        if treasury_fee > 0 {
+            require!(
+                !load_token_account(&self.treasury_ata_b)?.is_frozen(),
+                EscrowError::AccountFrozen
+            );
+            self.transfer_fee(self.treasury_ata_b.to_account_info(), treasury_fee, remaining)?;
+        }
+        if let Some(referrer_ata_b) = self.referrer_ata_b.as_ref().filter(|_| referral_fee > 0) {
+            require!(!referrer_ata_b.is_frozen(), EscrowError::AccountFrozen);
+            self.transfer_fee(referrer_ata_b.to_account_info(), referral_fee, remaining)?;
+        }
+
+        // 2️⃣ vault -> taker（Token A），全部成交时关闭 vault
+        self.withdraw_and_close_vault(fill_amount, legs, remaining)?;
+
+        // 3️⃣ 更新剩余条款，全部成交时关闭 escrow
+        self.settle_escrow(fill_amount, amount_b)?;
+
+        emit!(EscrowTaken {
+            escrow: self.escrow.key(),
+            taker: self.taker.key(),
+            amount_a: fill_amount,
+            amount_b,
+            remaining_deposit: self.escrow.deposit,
+            memo: memo.to_vec(),
+        });
+
+        Ok(())
+    }
+}
+
+/// 校验成交条件，并计算成交 fill_amount 个 Token A 需支付的 Token B 数量
+///
+/// take 及其变体（take_delegated / take_lamports / batch_take 等）共用，只依赖 escrow 条款、
+/// config 与 taker 身份；vault 余额、token 账户冻结、delegate 授权等与账户结构相关的条件
+/// 由各入口自行校验。
+///
+/// - gate_ata: taker 的门槛 token 账户，不接受该账户的入口传 None（设置了 gate_mint 的 escrow 因此被拒绝）
+/// - alt: taker 是否以备选 mint（mint_b_alt）支付
+pub fn quote_fill(
+    escrow: &Account<Escrow>,
+    config: &Config,
+    taker: &Pubkey,
+    gate_ata: Option<&InterfaceAccount<TokenAccount>>,
+    fill_amount: u64,
+    max_receive: u64,
+    alt: bool,
+) -> Result<u64> {
+    // 协议暂停期间不允许成交
+    require!(!config.paused, EscrowError::ProgramPaused);
+
+    // 与 Take 的账户约束相同；手动反序列化 escrow 的入口（batch_take）依赖这里的检查
+    require!(escrow.is_active(), EscrowError::EscrowNotActive);
+    require!(!escrow.in_progress, EscrowError::ReentrancyDetected);
+
+    // strict-pda 构建：重新推导 escrow 地址并校验规范 bump
+    // （take 及各变体的账户约束不再重复推导，默认构建不产生 find_program_address 开销）
+    #[cfg(feature = "strict-pda")]
+    require_keys_eq!(
+        escrow.verify_pda(&escrow.creator, &crate::ID)?,
+        escrow.key(),
+        EscrowError::InvalidEscrowAccount
+    );
+
+    // 报价过期后不允许成交
+    let now = Clock::get()?.unix_timestamp;
+    require!(!escrow.is_expired(now), EscrowError::OfferExpired);
+
+    // maker 自己成交没有意义，且会污染事件与统计
+    require_keys_neq!(*taker, escrow.maker, EscrowError::MakerCannotTake);
+
+    // 指定了 taker 的报价（OTC）只能由该 taker 成交
+    require!(
+        escrow.is_taker_allowed(taker),
+        EscrowError::UnauthorizedTaker
+    );
+
+    // 持币门槛：taker 必须持有不少于 gate_min_balance 的 gate_mint
+    if escrow.is_gated() {
+        let gate = gate_ata.ok_or(error!(EscrowError::GateRequirementNotMet))?;
+        require!(
+            gate.owner == *taker
+                && gate.mint == escrow.gate_mint
+                && gate.amount >= escrow.gate_min_balance,
+            EscrowError::GateRequirementNotMet
+        );
+    }
+
+    // 成交数量必须在 (0, 剩余存入] 之间
+    require!(
+        fill_amount > 0 && fill_amount <= escrow.deposit,
+        EscrowError::InvalidFillAmount
+    );
+
+    // 被 reserve 锁定的部分只能由对应 taker 通过 claim 成交
+    require!(
+        fill_amount <= escrow.available(),
+        EscrowError::InsufficientAvailable
+    );
+
+    // 单次成交数量限制（防止粉尘成交 / 限制单笔规模）
+    require!(
+        escrow.meets_min_fill(fill_amount),
+        EscrowError::FillBelowMinimum
+    );
+    require!(
+        escrow.within_max_fill(fill_amount),
+        EscrowError::FillAboveMaximum
+    );
+
+    // 按比例计算 Token B（荷兰拍按当前时间插值）；
+    // 向下取整为 0 时拒绝，防止 taker 免费拿走 Token A
+    let amount_b = escrow.receive_for(fill_amount, now, alt)?;
+    require_gt!(amount_b, 0, EscrowError::InvalidFillAmount);
+
+    // 防止 maker 在 taker 签名后抢先 update 提高价格
+    require!(amount_b <= max_receive, EscrowError::SlippageExceeded);
+
+    Ok(amount_b)
+}
+
+/// 计算使收款方净得 net 个 Token 所需转出的毛额
+///
+/// mint 启用了 Token-2022 转账费扩展时，转账途中会被扣除手续费；
+/// 未启用时毛额即净额
+pub fn gross_up_transfer_fee(mint: &AccountInfo, net: u64) -> Result<u64> {
+    let Ok(fee_config) = get_mint_extension_data::<TransferFeeConfig>(mint) else {
+        return Ok(net);
+    };
+
+    let epoch = Clock::get()?.epoch;
+    fee_config
+        .get_epoch_fee(epoch)
+        .calculate_pre_fee_amount(net)
+        .ok_or(error!(EscrowError::FeeCalculationOverflow))
+}
+
+/// ATA 程序为 mint 创建 ATA 时的账户大小
+///
+/// SPL Token 为固定大小；Token-2022 额外包含 mint 扩展要求的账户扩展与 ImmutableOwner
+pub fn ata_len(mint: &AccountInfo) -> Result<usize> {
+    let data = mint.try_borrow_data()?;
+    let state = StateWithExtensions::<SplMint>::unpack(&data)?;
+    let mut extensions =
+        ExtensionType::get_required_init_account_extensions(&state.get_extension_types()?);
+    if *mint.owner == anchor_spl::token_2022::ID {
+        extensions.push(ExtensionType::ImmutableOwner);
+    }
+
+    Ok(ExtensionType::try_calculate_account_len::<SplAccount>(&extensions)?)
+}
+
+/// 支持 Token-2022 transfer hook 的 transfer_checked
+///
+/// mint 配置了 transfer hook 时，hook 程序 ID 取自 mint 的扩展数据，
+/// 并从 ctx.remaining_accounts 中解析该程序及其 ExtraAccountMetaList 声明的账户一并传入 CPI；
+/// remaining_accounts 缺少 mint 配置的 hook 程序或其所需账户时 CPI 失败。
+/// 未配置 hook 的 mint（含 SPL Token）等价于普通 transfer_checked
+pub fn transfer_checked_with_hook<'info>(
+    ctx: CpiContext<'_, '_, '_, 'info, TransferChecked<'info>>,
+    amount: u64,
+    decimals: u8,
+) -> Result<()> {
+    invoke_transfer_checked(
+        ctx.program.key,
+        ctx.accounts.from,
+        ctx.accounts.mint,
+        ctx.accounts.to,
+        ctx.accounts.authority,
+        &ctx.remaining_accounts,
+        amount,
+        decimals,
+        ctx.signer_seeds,
+    )
+    .map_err(Into::into)
+}
+
+/// 关闭 escrow 的 vault：先将剩余余额转给 sweep_to，再 close_account
+///
+/// 成交只转出 escrow.deposit，他人向 vault 额外转入的 Token A 会留在 vault 中；
+/// close_account 要求余额为 0，不先转出这部分余额，最后一次成交将永远无法关闭 vault。
+/// ctx.remaining_accounts 同时用于转出余额时的 transfer hook
+pub fn sweep_and_close_vault<'info>(
+    ctx: CpiContext<'_, '_, '_, 'info, CloseAccount<'info>>,
+    mint: AccountInfo<'info>,
+    decimals: u8,
+    sweep_to: AccountInfo<'info>,
+) -> Result<()> {
+    let stray = load_token_account(&ctx.accounts.account)?.amount;
+    if stray > 0 {
+        msg!("close_vault: sweeping {} stray tokens", stray);
+        transfer_checked_with_hook(
             CpiContext::new_with_signer(
-                self.token_program.to_account_info(),
-                CloseAccount {
-                    account: self.vault.to_account_info(),
-                    authority: self.escrow.to_account_info(),
-                    destination: self.maker.to_account_info(),
+                ctx.program.clone(),
+                TransferChecked {
+                    from: ctx.accounts.account.clone(),
+                    to: sweep_to,
+                    mint,
+                    authority: ctx.accounts.authority.clone(),
                 },
-                &signer_seeds,
+                ctx.signer_seeds,
             )
+            .with_remaining_accounts(ctx.remaining_accounts.clone()),
+            stray,
+            decimals,
         )?;
-
-        Ok(())
     }
+
+    close_account(ctx)
+}
+
+/// 从账户数据读取 token 账户的当前状态
+///
+/// 用于 UncheckedAccount 形式的 ATA（可能在 handler 中才创建），
+/// 以及 CPI 之后的余额（InterfaceAccount 不会自动刷新）
+fn load_token_account(account: &AccountInfo) -> Result<TokenAccount> {
+    let data = account.try_borrow_data()?;
+
+    TokenAccount::try_deserialize(&mut &data[..])
 }
 
 /// Take 指令入口
-pub fn handler(ctx: Context<Take>) -> Result<()> {
-    // 1️⃣ taker -> maker（Token B）
-    ctx.accounts.transfer_to_maker()?;
+///
+/// 参数说明见 Take::quote_fill；
+/// - require_maker_ata_exists: 为 true 时 maker_ata_b 不存在即失败，taker 不代付其租金
+/// - valid_until: 交易有效截止时间（unix 秒，i64::MAX 表示不限制），上链晚于该时间即失败
+/// - memo: 附言（最多 Config::MAX_TAKE_MEMO_LEN 字节，config.require_take_memo 时不能为空），
+///   写入日志与 EscrowTaken 事件
+///
+/// transfer hook 所需账户通过 remaining_accounts 传入
+pub fn handler<'info>(
+    ctx: Context<'_, '_, 'info, 'info, Take<'info>>,
+    fill_amount: u64,
+    max_receive: u64,
+    require_maker_ata_exists: bool,
+    valid_until: i64,
+    memo: Vec<u8>,
+) -> Result<()> {
+    // taker 侧的截止时间（与 maker 的 expiry 对应），防止交易延迟上链后按过时的判断成交
+    require!(
+        Clock::get()?.unix_timestamp <= valid_until,
+        EscrowError::TransactionExpired
+    );
+
+    ctx.accounts.ensure_atas(require_maker_ata_exists)?;
+
+    let amount_b = ctx.accounts.quote_fill(fill_amount, max_receive)?;
+
+    ctx.accounts
+        .execute_fill(fill_amount, amount_b, &memo, ctx.remaining_accounts)
+}
+
+#[cfg(test)]
+mod tests {
+    use anchor_spl::token_2022::spl_token_2022::{
+        self,
+        extension::{
+            immutable_owner::ImmutableOwner,
+            transfer_fee::{TransferFee, TransferFeeConfig},
+            BaseStateWithExtensionsMut, ExtensionType, StateWithExtensionsMut,
+        },
+        state::Mint as Token2022Mint,
+    };
+
+    use super::*;
+    use crate::test_utils::*;
+
+    /// 转账费为 fee_bps、上限为 maximum_fee 的 Token-2022 mint
+    fn transfer_fee_mint(fee_bps: u16, maximum_fee: u64) -> AccountInfo<'static> {
+        let len = ExtensionType::try_calculate_account_len::<Token2022Mint>(&[
+            ExtensionType::TransferFeeConfig,
+        ])
+        .unwrap();
+        let mut data = vec![0u8; len];
+        let mut state =
+            StateWithExtensionsMut::<Token2022Mint>::unpack_uninitialized(&mut data).unwrap();
+        let fee = TransferFee {
+            epoch: 0.into(),
+            maximum_fee: maximum_fee.into(),
+            transfer_fee_basis_points: fee_bps.into(),
+        };
+        let config = state.init_extension::<TransferFeeConfig>(true).unwrap();
+        config.older_transfer_fee = fee;
+        config.newer_transfer_fee = fee;
+        state.base.decimals = 6;
+        state.base.is_initialized = true;
+        state.pack_base();
+        state.init_account_type().unwrap();
+
+        TestAccount::new(Pubkey::new_unique(), spl_token_2022::ID, data).into_info()
+    }
+
+    #[test]
+    fn gross_up_covers_transfer_fee() {
+        set_clock(1_000, 1);
+
+        // 普通 mint 没有转账费
+        let plain = mint_account(6).into_info();
+        assert_eq!(gross_up_transfer_fee(&plain, 990).unwrap(), 990);
+
+        // 1% 转账费：转出 1000，扣除 10 后收款方净得 990
+        let mint = transfer_fee_mint(100, u64::MAX);
+        assert_eq!(gross_up_transfer_fee(&mint, 990).unwrap(), 1_000);
+        assert_eq!(gross_up_transfer_fee(&mint, 0).unwrap(), 0);
+
+        // 转账费封顶时只需多转出 maximum_fee
+        let capped = transfer_fee_mint(100, 5);
+        assert_eq!(gross_up_transfer_fee(&capped, 990).unwrap(), 995);
+    }
+
+    const TAKE: u8 = 1;
+
+    /// 100 Token A 换 50 Token B 的 escrow，taker 持有 1_000 Token B
+    struct Fixture {
+        taker: Pubkey,
+        escrow: Escrow,
+        config: Config,
+        mint_a: TestAccount,
+        mint_b: TestAccount,
+    }
+
+    impl Fixture {
+        fn new() -> Self {
+            let (mint_a, mint_b) = setup_mints(6, 6);
+            let escrow = make_escrow(&Pubkey::new_unique(), 1, &mint_a, &mint_b, 100, 50);
+
+            Self {
+                taker: Pubkey::new_unique(),
+                escrow,
+                config: default_config(),
+                mint_a,
+                mint_b,
+            }
+        }
+
+        fn accounts(&self) -> TakeAccounts {
+            take_accounts(
+                &self.taker,
+                &self.escrow,
+                &self.config,
+                &self.mint_a,
+                &self.mint_b,
+                1_000,
+            )
+        }
+
+        /// 执行 take（宿主机上成功路径会在 CPI 处 panic，只能用于预期失败的调用）
+        fn take(&self, fill_amount: u64, max_receive: u64) -> ProgramResult {
+            self.take_with(self.accounts(), fill_amount, max_receive)
+        }
+
+        fn take_with(
+            &self,
+            accounts: TakeAccounts,
+            fill_amount: u64,
+            max_receive: u64,
+        ) -> ProgramResult {
+            self.take_with_memo(accounts, fill_amount, max_receive, Vec::new())
+        }
+
+        fn take_with_memo(
+            &self,
+            accounts: TakeAccounts,
+            fill_amount: u64,
+            max_receive: u64,
+            memo: Vec<u8>,
+        ) -> ProgramResult {
+            let args = (fill_amount, max_receive, false, i64::MAX, memo);
+
+            run(&ix_data(TAKE, args), accounts.into_vec()).0
+        }
+
+        /// 只做账户校验，返回构造好的 Take（用于在 CPI 之前检查 handler 的各步骤）
+        fn load(
+            &self,
+            accounts: TakeAccounts,
+            fill_amount: u64,
+            max_receive: u64,
+        ) -> Take<'static> {
+            let args = (fill_amount, max_receive, false, i64::MAX, Vec::<u8>::new());
+
+            load_accounts::<Take>(accounts.into_vec(), &ix_data(TAKE, args)).unwrap().0
+        }
+
+        fn quote(&self, fill_amount: u64) -> Result<u64> {
+            quote(&self.escrow, &self.config, &self.taker, None, fill_amount, u64::MAX)
+        }
+    }
+
+    fn quote(
+        escrow: &Escrow,
+        config: &Config,
+        taker: &Pubkey,
+        gate_ata: Option<TestAccount>,
+        fill_amount: u64,
+        max_receive: u64,
+    ) -> Result<u64> {
+        let escrow = load_account::<Escrow>(escrow_account(escrow));
+        let gate_ata = gate_ata.map(load_interface_account::<TokenAccount>);
+
+        quote_fill(
+            &escrow,
+            config,
+            taker,
+            gate_ata.as_ref(),
+            fill_amount,
+            max_receive,
+            false,
+        )
+    }
+
+    #[test]
+    fn quotes_partial_fill_within_slippage() {
+        set_clock(1_000, 1);
+        let (mint_a, mint_b) = setup_mints(6, 6);
+        let escrow = make_escrow(&Pubkey::new_unique(), 1, &mint_a, &mint_b, 100, 50);
+        let config = default_config();
+        let taker = Pubkey::new_unique();
+
+        assert_eq!(quote(&escrow, &config, &taker, None, 40, 20).unwrap(), 20);
+        assert_err(
+            quote(&escrow, &config, &taker, None, 40, 19),
+            EscrowError::SlippageExceeded,
+        );
+        assert_err(
+            quote(&escrow, &config, &taker, None, 101, u64::MAX),
+            EscrowError::InvalidFillAmount,
+        );
+    }
+
+    #[test]
+    fn rejects_escrow_mid_fill() {
+        set_clock(1_000, 1);
+        let (mint_a, mint_b) = setup_mints(6, 6);
+        let mut escrow = make_escrow(&Pubkey::new_unique(), 1, &mint_a, &mint_b, 100, 50);
+        escrow.in_progress = true;
+
+        assert_err(
+            quote(&escrow, &default_config(), &Pubkey::new_unique(), None, 100, u64::MAX),
+            EscrowError::ReentrancyDetected,
+        );
+    }
+
+    #[test]
+    fn gated_escrow_requires_gate_account() {
+        set_clock(1_000, 1);
+        let (mint_a, mint_b) = setup_mints(6, 6);
+        let gate_mint = mint_account(0);
+        let mut escrow = make_escrow(&Pubkey::new_unique(), 1, &mint_a, &mint_b, 100, 50);
+        escrow.gate_mint = gate_mint.key;
+        escrow.gate_min_balance = 10;
+        let config = default_config();
+        let taker = Pubkey::new_unique();
+
+        // take_delegated / take_lamports 不接受门槛账户
+        assert_err(
+            quote(&escrow, &config, &taker, None, 100, u64::MAX),
+            EscrowError::GateRequirementNotMet,
+        );
+        let below = fund_ata(&taker, &gate_mint, 9);
+        assert_err(
+            quote(&escrow, &config, &taker, Some(below), 100, u64::MAX),
+            EscrowError::GateRequirementNotMet,
+        );
+        let enough = fund_ata(&taker, &gate_mint, 10);
+        assert_eq!(
+            quote(&escrow, &config, &taker, Some(enough), 100, u64::MAX).unwrap(),
+            50
+        );
+    }
+
+    #[test]
+    fn expired_offer_cannot_be_taken() {
+        let mut fixture = Fixture::new();
+        fixture.escrow.expiry = 1_000;
+
+        // 截止时间当秒仍可成交
+        set_clock(1_000, 1);
+        assert_eq!(fixture.quote(100).unwrap(), 50);
+
+        set_clock(1_001, 1);
+        assert_error(fixture.take(100, u64::MAX), EscrowError::OfferExpired);
+
+        // expiry = 0 表示永不过期
+        fixture.escrow.expiry = 0;
+        assert_eq!(fixture.quote(100).unwrap(), 50);
+    }
+
+    #[test]
+    fn only_allowed_taker_can_take() {
+        set_clock(1_000, 1);
+        let mut fixture = Fixture::new();
+        fixture.escrow.allowed_taker = Pubkey::new_unique();
+
+        assert_error(fixture.take(100, u64::MAX), EscrowError::UnauthorizedTaker);
+
+        fixture.taker = fixture.escrow.allowed_taker;
+        assert_eq!(fixture.quote(100).unwrap(), 50);
+    }
+
+    #[test]
+    fn take_respects_max_receive() {
+        set_clock(1_000, 1);
+        let fixture = Fixture::new();
+
+        // 成交 40 个 Token A 需支付 20 个 Token B
+        assert_error(fixture.take(40, 19), EscrowError::SlippageExceeded);
+        assert_eq!(
+            quote(&fixture.escrow, &fixture.config, &fixture.taker, None, 40, 20).unwrap(),
+            20
+        );
+    }
+
+    #[test]
+    fn mints_must_share_token_program() {
+        set_clock(1_000, 1);
+        let fixture = Fixture::new();
+        // token 账户均属于 SPL Token，只有 mint_b 归 Token-2022 所有
+        let mut accounts = fixture.accounts();
+        accounts.mint_b.owner = spl_token_2022::ID;
+
+        assert_error(
+            fixture.take_with(accounts, 100, u64::MAX),
+            ErrorCode::ConstraintMintTokenProgram,
+        );
+    }
+
+    #[test]
+    fn frozen_taker_account_is_rejected() {
+        set_clock(1_000, 1);
+        let fixture = Fixture::new();
+        // taker_ata_b 被 Token B 的 freeze authority 冻结
+        let mut accounts = fixture.accounts();
+        accounts.taker_ata_b = freeze(accounts.taker_ata_b);
+
+        assert_error(
+            fixture.take_with(accounts, 100, u64::MAX),
+            EscrowError::AccountFrozen,
+        );
+    }
+
+    #[test]
+    fn gross_up_overflow_is_reported() {
+        set_clock(1_000, 1);
+        let mint = transfer_fee_mint(100, u64::MAX);
+
+        assert_err(
+            gross_up_transfer_fee(&mint, u64::MAX),
+            EscrowError::FeeCalculationOverflow,
+        );
+        // 转账费封顶时不会溢出：净额加上 maximum_fee 仍在 u64 范围内
+        let capped = transfer_fee_mint(100, 5);
+        assert_eq!(gross_up_transfer_fee(&capped, u64::MAX - 5).unwrap(), u64::MAX);
+    }
+
+    #[test]
+    fn auction_take_quotes_current_price() {
+        let mut fixture = Fixture::new();
+        fixture.escrow.is_auction = true;
+        fixture.escrow.start_receive = 80;
+        fixture.escrow.end_receive = 40;
+        fixture.escrow.auction_start = 1_000;
+        fixture.escrow.auction_end = 2_000;
+
+        set_clock(1_000, 1);
+        assert_eq!(fixture.quote(100).unwrap(), 80);
+        set_clock(1_500, 1);
+        assert_eq!(fixture.quote(100).unwrap(), 60);
+        assert_error(fixture.take(100, 59), EscrowError::SlippageExceeded);
+        set_clock(3_000, 1);
+        assert_eq!(fixture.quote(100).unwrap(), 40);
+    }
+
+    #[test]
+    fn paused_program_rejects_take() {
+        set_clock(1_000, 1);
+        let mut fixture = Fixture::new();
+        fixture.config.paused = true;
+
+        assert_error(fixture.take(100, u64::MAX), EscrowError::ProgramPaused);
+    }
+
+    #[test]
+    fn maker_cannot_take_own_escrow() {
+        set_clock(1_000, 1);
+        let mut fixture = Fixture::new();
+        fixture.taker = fixture.escrow.maker;
+
+        assert_error(fixture.take(100, u64::MAX), EscrowError::MakerCannotTake);
+        assert_err(fixture.quote(1), EscrowError::MakerCannotTake);
+    }
+
+    #[test]
+    fn taker_may_pay_in_either_mint() {
+        set_clock(1_000, 1);
+        let mut fixture = Fixture::new();
+        let alt = mint_account(6);
+        fixture.escrow.mint_b_alt = alt.key;
+        fixture.escrow.receive_alt = 70;
+
+        // 主 mint 按 receive 报价
+        assert_error(fixture.take(100, 49), EscrowError::SlippageExceeded);
+        assert_eq!(fixture.quote(100).unwrap(), 50);
+
+        // 备选 mint 按 receive_alt 报价
+        fixture.mint_b = alt;
+        assert_error(fixture.take(100, 69), EscrowError::SlippageExceeded);
+        let escrow = load_account::<Escrow>(escrow_account(&fixture.escrow));
+        assert_eq!(
+            quote_fill(&escrow, &fixture.config, &fixture.taker, None, 40, u64::MAX, true).unwrap(),
+            28
+        );
+
+        // 两者之外的 mint 被拒绝
+        fixture.mint_b = mint_account(6);
+        assert_error(fixture.take(100, u64::MAX), EscrowError::InvalidMintB);
+    }
+
+    #[test]
+    fn rent_accounts_must_match_rent_collector() {
+        set_clock(1_000, 1);
+        let mut fixture = Fixture::new();
+        fixture.escrow.rent_collector = Pubkey::new_unique();
+
+        // escrow 与 vault 的租金都不能再返还给 maker
+        let maker = || TestAccount::wallet(fixture.escrow.maker, 0).writable();
+        let to_rent_collector = TakeAccounts { rent_collector: maker(), ..fixture.accounts() };
+        let to_vault_rent_payer = TakeAccounts { vault_rent_payer: maker(), ..fixture.accounts() };
+        for accounts in [to_rent_collector, to_vault_rent_payer] {
+            assert_error(
+                fixture.take_with(accounts, 100, u64::MAX),
+                ErrorCode::ConstraintAddress,
+            );
+        }
+        assert_error(fixture.take(100, 49), EscrowError::SlippageExceeded);
+    }
+
+    #[test]
+    fn mint_decimals_must_match_escrow() {
+        set_clock(1_000, 1);
+        let mut fixture = Fixture::new();
+
+        // 地址正确但精度与 make 时记录的不同
+        fixture.mint_a = TestAccount {
+            key: fixture.mint_a.key,
+            ..mint_account(9)
+        };
+        assert_error(fixture.take(100, u64::MAX), EscrowError::DecimalsMismatch);
+
+        let mut fixture = Fixture::new();
+        fixture.mint_b = TestAccount {
+            key: fixture.mint_b.key,
+            ..mint_account(9)
+        };
+        assert_error(fixture.take(100, u64::MAX), EscrowError::DecimalsMismatch);
+    }
+
+    #[test]
+    fn drained_vault_is_rejected() {
+        set_clock(1_000, 1);
+        let fixture = Fixture::new();
+        let vault_owner = escrow_account(&fixture.escrow).key;
+
+        // vault 被带外清空：taker 付款前即失败
+        let mut accounts = fixture.accounts();
+        accounts.vault = fund_ata(&vault_owner, &fixture.mint_a, 0).writable();
+        assert_error(
+            fixture.take_with(accounts, 100, u64::MAX),
+            EscrowError::VaultEmpty,
+        );
+
+        // 余额不足本次成交
+        let mut accounts = fixture.accounts();
+        accounts.vault = fund_ata(&vault_owner, &fixture.mint_a, 30).writable();
+        assert_error(
+            fixture.take_with(accounts, 40, u64::MAX),
+            EscrowError::VaultBalanceMismatch,
+        );
+    }
+
+    #[test]
+    fn non_canonical_token_accounts_are_rejected() {
+        set_clock(1_000, 1);
+        let fixture = Fixture::new();
+
+        // 数据合法（mint / owner 正确）但地址不是规范 ATA 的 token 账户
+        let moved = |account: TestAccount| TestAccount { key: Pubkey::new_unique(), ..account };
+        let accounts = || fixture.accounts();
+
+        for accounts in [
+            TakeAccounts { taker_ata_a: moved(accounts().taker_ata_a), ..accounts() },
+            TakeAccounts { taker_ata_b: moved(accounts().taker_ata_b), ..accounts() },
+            TakeAccounts { maker_ata_b: moved(accounts().maker_ata_b), ..accounts() },
+            TakeAccounts { treasury_ata_b: moved(accounts().treasury_ata_b), ..accounts() },
+        ] {
+            assert_error(
+                fixture.take_with(accounts, 100, u64::MAX),
+                EscrowError::NonCanonicalAta,
+            );
+        }
+    }
+
+    #[test]
+    fn nested_take_is_rejected() {
+        set_clock(1_000, 1);
+        let mut fixture = Fixture::new();
+        // 外层 take 在第一次 CPI 之前写入 in_progress，transfer hook 回调时看到的即为此状态
+        fixture.escrow.in_progress = true;
+
+        assert_error(fixture.take(40, u64::MAX), EscrowError::ReentrancyDetected);
+    }
+
+    #[test]
+    fn transfer_requires_mint_hook_program() {
+        // spl-transfer-hook-interface 的 TransferHookError::IncorrectAccount
+        const INCORRECT_ACCOUNT: u32 = 2_110_272_652;
+        let hook_program = Pubkey::new_unique();
+        let mint = transfer_hook_mint(hook_program);
+        let owner = Pubkey::new_unique();
+        let from = fund_ata(&owner, &mint, 100).into_info();
+        let to = fund_ata(&Pubkey::new_unique(), &mint, 0).into_info();
+        let token_program = TestAccount::program(spl_token_2022::ID).into_info();
+        let transfer = |remaining: Vec<AccountInfo<'static>>| {
+            let accounts = TransferChecked {
+                from: from.clone(),
+                to: to.clone(),
+                mint: mint.clone().into_info(),
+                authority: TestAccount::wallet(owner, 0).signer().into_info(),
+            };
+            let ctx = CpiContext::new(token_program.clone(), accounts)
+                .with_remaining_accounts(remaining);
+
+            transfer_checked_with_hook(ctx, 10, 6)
+        };
+
+        // 未传入 hook 程序，或传入的程序不是 mint 配置的 hook，在 CPI 之前即失败
+        assert_err(transfer(vec![]), ProgramError::Custom(INCORRECT_ACCOUNT));
+        let other_program = TestAccount::program(Pubkey::new_unique()).into_info();
+        assert_err(transfer(vec![other_program]), ProgramError::Custom(INCORRECT_ACCOUNT));
+    }
+
+    #[test]
+    fn ata_len_matches_ata_program() {
+        set_clock(1_000, 1);
+
+        assert_eq!(ata_len(&mint_account(6).into_info()).unwrap(), 165);
+        // Token-2022：ImmutableOwner，转账费 mint 另需 TransferFeeAmount
+        let plain_2022 = TestAccount {
+            owner: spl_token_2022::ID,
+            ..mint_account(6)
+        };
+        assert_eq!(ata_len(&plain_2022.into_info()).unwrap(), 170);
+        assert_eq!(ata_len(&transfer_fee_mint(100, 5)).unwrap(), 182);
+    }
+
+    #[test]
+    fn taker_must_cover_maker_ata_rent() {
+        set_clock(1_000, 1);
+        let fixture = Fixture::new();
+        let rent = Rent::default().minimum_balance(165);
+        let proceeds = fixture.escrow.proceeds_destination();
+        let maker_ata_b = get_associated_token_address_with_program_id(
+            &proceeds,
+            &fixture.mint_b.key,
+            &fixture.mint_b.owner,
+        );
+
+        // maker_ata_b 尚不存在，taker 的 lamports 差 1 不够支付其租金
+        let mut accounts = fixture.accounts();
+        accounts.taker = TestAccount::wallet(fixture.taker, rent - 1).signer();
+        accounts.maker_ata_b = TestAccount::wallet(maker_ata_b, 0).writable();
+
+        assert_error(
+            fixture.take_with(accounts, 100, u64::MAX),
+            EscrowError::InsufficientLamportsForRent,
+        );
+    }
 
-    // 2️⃣ vault -> taker（Token A）+ 关闭 vault
-    ctx.accounts.withdraw_and_close_vault()?;
+    #[test]
+    fn taker_chooses_whether_to_fund_maker_ata() {
+        set_clock(1_000, 1);
+        let mut fixture = Fixture::new();
+        let proceeds = fixture.escrow.proceeds_destination();
+        let maker_ata_b = get_associated_token_address_with_program_id(
+            &proceeds,
+            &fixture.mint_b.key,
+            &fixture.mint_b.owner,
+        );
+        let take = |fixture: &Fixture, require_maker_ata_exists: bool| {
+            let mut accounts = fixture.accounts();
+            accounts.taker = TestAccount::wallet(fixture.taker, 0).signer();
+            accounts.maker_ata_b = TestAccount::wallet(maker_ata_b, 0).writable();
+            let args = (100u64, u64::MAX, require_maker_ata_exists, i64::MAX, Vec::<u8>::new());
 
-    // escrow 会因 close = maker 自动关闭
-    Ok(())
+            run(&ix_data(TAKE, args), accounts.into_vec()).0
+        };
+
+        // 要求已存在：直接失败，taker 不代付租金
+        assert_error(take(&fixture, true), EscrowError::MakerAtaMissing);
+        // 允许代付：进入创建流程（此处 taker 余额不足以支付租金）
+        assert_error(take(&fixture, false), EscrowError::InsufficientLamportsForRent);
+        // make 时预先创建过、之后被 maker 关闭的 ATA 不会由 taker 重建
+        fixture.escrow.maker_ata_b_ready = true;
+        assert_error(take(&fixture, false), EscrowError::MakerAtaMissing);
+    }
+
+    #[test]
+    fn pre_created_maker_ata_needs_no_taker_rent() {
+        set_clock(1_000, 1);
+        let mut fixture = Fixture::new();
+        fixture.escrow.maker_ata_b_ready = true;
+
+        // make 时已创建 maker_ata_b：taker 没有 lamports 也无需代付租金
+        let mut accounts = fixture.accounts();
+        accounts.taker = TestAccount::wallet(fixture.taker, 0).signer();
+        let mut take = fixture.load(accounts, 100, 50);
+
+        take.ensure_atas(false).unwrap();
+        assert_eq!(take.quote_fill(100, 50).unwrap(), 50);
+
+        // 转账之后的结算：全部成交，escrow 关闭，租金返还给 rent_collector
+        let rent = take.escrow.to_account_info().lamports();
+        take.settle_escrow(100, 50).unwrap();
+        assert!(matches!(take.escrow.status, EscrowStatus::Filled));
+        assert_eq!((take.escrow.deposit, take.escrow.receive), (0, 0));
+        assert_eq!(take.rent_collector.lamports(), rent);
+    }
+
+    #[test]
+    fn taker_cannot_pay_into_own_account() {
+        set_clock(1_000, 1);
+        let mut fixture = Fixture::new();
+        // proceeds_recipient 为 taker 时 maker_ata_b 与 taker_ata_b 是同一个账户
+        fixture.escrow.proceeds_recipient = fixture.taker;
+
+        let accounts = fixture.accounts();
+        assert_eq!(accounts.taker_ata_b.key, accounts.maker_ata_b.key);
+        assert_error(
+            fixture.take_with(accounts, 100, u64::MAX),
+            EscrowError::DuplicateTokenAccount,
+        );
+    }
+
+    #[test]
+    fn vault_must_be_owned_by_escrow() {
+        set_clock(1_000, 1);
+        let fixture = Fixture::new();
+        let mut take = fixture.load(fixture.accounts(), 100, u64::MAX);
+        assert!(take.check_vault_authority().is_ok());
+
+        // 账户约束被放宽时，handler 内的检查仍拒绝非 escrow 持有的 vault
+        let foreign_vault = fund_ata(&Pubkey::new_unique(), &fixture.mint_a, 100);
+        take.vault = load_interface_account::<TokenAccount>(foreign_vault);
+        assert_err(take.check_vault_authority(), EscrowError::InvalidVaultAuthority);
+    }
+
+    #[test]
+    fn underfunded_taker_is_rejected_before_transfer() {
+        set_clock(1_000, 1);
+        let fixture = Fixture::new();
+        let underfunded = |taker_b| {
+            take_accounts(
+                &fixture.taker,
+                &fixture.escrow,
+                &fixture.config,
+                &fixture.mint_a,
+                &fixture.mint_b,
+                taker_b,
+            )
+        };
+
+        // 全部成交需 50 Token B，部分成交 40 Token A 需 20
+        assert_error(
+            fixture.take_with(underfunded(49), 100, u64::MAX),
+            EscrowError::InsufficientFunds,
+        );
+        assert_error(
+            fixture.take_with(underfunded(19), 40, u64::MAX),
+            EscrowError::InsufficientFunds,
+        );
+    }
+
+    #[test]
+    fn vault_rent_account_must_match_vault_rent_payer() {
+        set_clock(1_000, 1);
+        let mut fixture = Fixture::new();
+        let vault_rent_payer = |fixture: &Fixture, key| {
+            let accounts = TakeAccounts {
+                vault_rent_payer: TestAccount::wallet(key, 0).writable(),
+                ..fixture.accounts()
+            };
+            fixture.take_with(accounts, 100, 49)
+        };
+
+        // 默认返还给 maker
+        let maker = fixture.escrow.maker;
+        assert_error(vault_rent_payer(&fixture, maker), EscrowError::SlippageExceeded);
+        assert_error(
+            vault_rent_payer(&fixture, Pubkey::new_unique()),
+            ErrorCode::ConstraintAddress,
+        );
+
+        // 单独指定时只能返还给该账户（例如代付租金的 taker），escrow 租金仍归 maker
+        fixture.escrow.vault_rent_payer = fixture.taker;
+        let taker = fixture.taker;
+        assert_error(vault_rent_payer(&fixture, taker), EscrowError::SlippageExceeded);
+        assert_error(vault_rent_payer(&fixture, maker), ErrorCode::ConstraintAddress);
+        assert_eq!(fixture.accounts().rent_collector.key, maker);
+    }
+
+    #[test]
+    fn permanent_delegate_drain_is_detected() {
+        set_clock(1_000, 1);
+        let mut fixture = Fixture::new();
+        fixture.mint_a = permanent_delegate_mint(&Pubkey::new_unique());
+        fixture.mint_b.owner = spl_token_2022::ID;
+        fixture.escrow.mint_a = fixture.mint_a.key;
+        let vault_owner = escrow_account(&fixture.escrow).key;
+
+        // 永久代理人在链外转走 vault 中的 70 Token A，只剩 30
+        let mut accounts = fixture.accounts();
+        accounts.vault = fund_ata(&vault_owner, &fixture.mint_a, 30).writable();
+        assert_error(
+            fixture.take_with(accounts, 40, u64::MAX),
+            EscrowError::VaultBalanceMismatch,
+        );
+    }
+
+    #[test]
+    fn maker_ata_b_must_belong_to_proceeds_recipient() {
+        set_clock(1_000, 1);
+        let mut fixture = Fixture::new();
+        let maker_ata_b = fixture.accounts().maker_ata_b;
+
+        // 默认付款到 maker 的 ATA
+        assert_eq!(maker_ata_b.key, fund_ata(&fixture.escrow.maker, &fixture.mint_b, 0).key);
+        assert_error(fixture.take(100, 49), EscrowError::SlippageExceeded);
+
+        // 设置收款钱包后只接受其 ATA，maker 的 ATA 被拒绝
+        fixture.escrow.proceeds_recipient = Pubkey::new_unique();
+        assert_error(fixture.take(100, 49), EscrowError::SlippageExceeded);
+        let accounts = TakeAccounts { maker_ata_b, ..fixture.accounts() };
+        assert_error(
+            fixture.take_with(accounts, 100, u64::MAX),
+            EscrowError::NonCanonicalAta,
+        );
+    }
+
+    #[test]
+    fn associated_token_program_is_pinned() {
+        set_clock(1_000, 1);
+        let fixture = Fixture::new();
+        let accounts = TakeAccounts {
+            associated_token_program: TestAccount::program(Pubkey::new_unique()),
+            ..fixture.accounts()
+        };
+
+        assert_error(
+            fixture.take_with(accounts, 100, u64::MAX),
+            ErrorCode::InvalidProgramId,
+        );
+    }
+
+    #[test]
+    fn taker_deadline_must_not_have_passed() {
+        set_clock(1_000, 1);
+        let fixture = Fixture::new();
+        let take = |valid_until: i64| {
+            let args = (100u64, 49u64, false, valid_until, Vec::<u8>::new());
+            run(&ix_data(TAKE, args), fixture.accounts().into_vec()).0
+        };
+
+        assert_error(take(1), EscrowError::TransactionExpired);
+        assert_error(take(999), EscrowError::TransactionExpired);
+        // 截止时间当秒仍然有效（之后因滑点保护失败）
+        assert_error(take(1_000), EscrowError::SlippageExceeded);
+    }
+
+    #[test]
+    fn memo_is_required_when_enabled() {
+        set_clock(1_000, 1);
+        let mut fixture = Fixture::new();
+        fixture.config.require_take_memo = true;
+
+        assert_error(fixture.take(100, 50), EscrowError::MemoRequired);
+        assert!(fixture.config.check_take_memo(b"audit ref 42").is_ok());
+    }
+
+    #[test]
+    fn memo_is_optional_when_disabled() {
+        set_clock(1_000, 1);
+        let fixture = Fixture::new();
+
+        assert!(fixture.config.check_take_memo(&[]).is_ok());
+        assert!(fixture.config.check_take_memo(b"audit ref 42").is_ok());
+
+        // 长度上限在两种模式下都生效
+        let long = vec![b'x'; Config::MAX_TAKE_MEMO_LEN + 1];
+        assert_err(fixture.config.check_take_memo(&long), EscrowError::MemoTooLong);
+        assert_error(
+            fixture.take_with_memo(fixture.accounts(), 100, 50, long),
+            EscrowError::MemoTooLong,
+        );
+    }
+
+    #[test]
+    fn partial_fill_pays_proportional_fee() {
+        set_clock(1_000, 1);
+        let (mint_a, mint_b) = setup_mints(6, 6);
+        let escrow = make_escrow(&Pubkey::new_unique(), 1, &mint_a, &mint_b, 100, 1_000_000);
+        let config = Config { fee_bps: 30, ..default_config() };
+        let taker = Pubkey::new_unique();
+
+        let full_b = quote(&escrow, &config, &taker, None, 100, u64::MAX).unwrap();
+        let quarter_b = quote(&escrow, &config, &taker, None, 25, u64::MAX).unwrap();
+        let full_fee = config.fee_for(&escrow, full_b).unwrap();
+        let quarter_fee = config.fee_for(&escrow, quarter_b).unwrap();
+
+        // 手续费以本次成交的 Token B 为基数，而不是整单 receive
+        assert_eq!((full_b, full_fee), (1_000_000, 3_000));
+        assert_eq!((quarter_b, quarter_fee), (250_000, 750));
+        assert_eq!(quarter_fee * 4, full_fee);
+    }
+
+    #[test]
+    fn ratio_escrow_fills_at_unit_price() {
+        set_clock(1_000, 1);
+        let (mint_a, mint_b) = setup_mints(6, 6);
+        // 每 2 个 Token A 需支付 3 个 Token B
+        let mut escrow = Escrow {
+            is_ratio: true,
+            price_num: 3,
+            price_den: 2,
+            ..make_escrow(&Pubkey::new_unique(), 1, &mint_a, &mint_b, 100, 150)
+        };
+        let config = default_config();
+        let taker = Pubkey::new_unique();
+
+        for (fill_amount, amount_b) in [(2, 3), (5, 7), (40, 60), (100, 150)] {
+            let quoted = quote(&escrow, &config, &taker, None, fill_amount, u64::MAX).unwrap();
+            assert_eq!(quoted, amount_b);
+        }
+
+        // 部分成交后单价不变
+        escrow.apply_fill(40, 60, false).unwrap();
+        assert_eq!((escrow.deposit, escrow.receive), (60, 90));
+        assert_eq!(quote(&escrow, &config, &taker, None, 60, 90).unwrap(), 90);
+        assert_err(
+            quote(&escrow, &config, &taker, None, 60, 89),
+            EscrowError::SlippageExceeded,
+        );
+    }
+
+    #[test]
+    fn identical_mints_are_rejected() {
+        set_clock(1_000, 1);
+        let mut fixture = Fixture::new();
+        // 绕过 make 的校验，直接构造 mint_a == mint_b 的 escrow
+        fixture.mint_b = fixture.mint_a.clone();
+        fixture.escrow = make_escrow(
+            &fixture.escrow.maker,
+            1,
+            &fixture.mint_a,
+            &fixture.mint_a,
+            100,
+            50,
+        );
+
+        assert_error(fixture.take(100, 50), EscrowError::InvalidMintA);
+    }
+
+    /// 按 Token-2022 ATA 程序的布局重建 token 账户：基础数据之后带 ImmutableOwner 扩展
+    fn with_immutable_owner(account: TestAccount) -> TestAccount {
+        let base = StateWithExtensions::<SplAccount>::unpack(&account.data).unwrap().base;
+        let len = ExtensionType::try_calculate_account_len::<SplAccount>(&[
+            ExtensionType::ImmutableOwner,
+        ])
+        .unwrap();
+        let mut data = vec![0u8; len];
+        let mut state =
+            StateWithExtensionsMut::<SplAccount>::unpack_uninitialized(&mut data).unwrap();
+        state.base = base;
+        state.pack_base();
+        state.init_account_type().unwrap();
+        state.init_extension::<ImmutableOwner>(true).unwrap();
+
+        TestAccount {
+            lamports: Rent::default().minimum_balance(len),
+            data,
+            ..account
+        }
+    }
+
+    #[test]
+    fn immutable_owner_atas_pass_take_checks() {
+        set_clock(1_000, 1);
+        let token_2022_mint = || TestAccount {
+            owner: spl_token_2022::ID,
+            ..mint_account(6)
+        };
+        let mut fixture = Fixture::new();
+        fixture.mint_a = token_2022_mint();
+        fixture.mint_b = token_2022_mint();
+        fixture.escrow = make_escrow(
+            &fixture.escrow.maker,
+            1,
+            &fixture.mint_a,
+            &fixture.mint_b,
+            100,
+            50,
+        );
+
+        // vault、taker_ata_a、taker_ata_b、maker_ata_b、treasury_ata_b 均为已存在的 Token-2022 ATA
+        let accounts = fixture.accounts();
+        let accounts = TakeAccounts {
+            vault: with_immutable_owner(accounts.vault),
+            taker_ata_a: with_immutable_owner(accounts.taker_ata_a),
+            taker_ata_b: with_immutable_owner(accounts.taker_ata_b),
+            maker_ata_b: with_immutable_owner(accounts.maker_ata_b),
+            treasury_ata_b: with_immutable_owner(accounts.treasury_ata_b),
+            ..accounts
+        };
+        let expected_len = ata_len(&fixture.mint_b.clone().into_info()).unwrap();
+        assert_eq!(accounts.maker_ata_b.data.len(), expected_len);
+
+        let mut take = fixture.load(accounts, 100, 50);
+
+        take.ensure_atas(true).unwrap();
+        assert_eq!(take.quote_fill(100, 50).unwrap(), 50);
+    }
+
+    #[cfg(feature = "strict-pda")]
+    #[test]
+    fn strict_pda_rejects_escrow_at_wrong_address() {
+        set_clock(1_000, 1);
+        let fixture = Fixture::new();
+        let config = fixture.config.clone();
+        let quote_at = |key: Pubkey| {
+            let escrow = load_account::<Escrow>(TestAccount {
+                key,
+                ..escrow_account(&fixture.escrow)
+            });
+
+            quote_fill(&escrow, &config, &fixture.taker, None, 100, u64::MAX, false)
+        };
+
+        assert_eq!(quote_at(escrow_account(&fixture.escrow).key).unwrap(), 50);
+        assert_err(quote_at(Pubkey::new_unique()), EscrowError::InvalidEscrowAccount);
+    }
+
+    #[test]
+    fn take_checks_gate_balance() {
+        set_clock(1_000, 1);
+        let gate_mint = mint_account(0);
+        let mut fixture = Fixture::new();
+        fixture.escrow.gate_mint = gate_mint.key;
+        fixture.escrow.gate_min_balance = 10;
+        let with_gate = |gate_ata: TestAccount| TakeAccounts { gate_ata, ..fixture.accounts() };
+
+        // 余额不足、不属于 taker 的门槛账户都被拒绝
+        let below = fund_ata(&fixture.taker, &gate_mint, 9);
+        assert_error(
+            fixture.take_with(with_gate(below), 100, 50),
+            EscrowError::GateRequirementNotMet,
+        );
+        let foreign = fund_ata(&Pubkey::new_unique(), &gate_mint, 10);
+        assert_error(
+            fixture.take_with(with_gate(foreign), 100, 50),
+            EscrowError::GateRequirementNotMet,
+        );
+
+        // 余额恰好达到门槛时可以成交
+        let enough = fund_ata(&fixture.taker, &gate_mint, 10);
+        let mut take = fixture.load(with_gate(enough), 40, 20);
+        assert_eq!(take.gate_ata.as_ref().map(|gate| gate.amount), Some(10));
+        assert_eq!(take.quote_fill(40, 20).unwrap(), 20);
+
+        // 转账之后的结算：部分成交，escrow 保留剩余条款
+        take.settle_escrow(40, 20).unwrap();
+        assert!(matches!(take.escrow.status, EscrowStatus::PartiallyFilled));
+        assert_eq!((take.escrow.deposit, take.escrow.receive), (60, 30));
+    }
+
+    #[test]
+    fn stray_vault_tokens_are_swept_before_close() {
+        set_clock(1_000, 1);
+        let fixture = Fixture::new();
+        let vault_owner = escrow_account(&fixture.escrow).key;
+
+        // 有人向 vault 额外转入 7 Token A：最后一次成交的转账前检查照常通过
+        let accounts = TakeAccounts {
+            vault: fund_ata(&vault_owner, &fixture.mint_a, 107).writable(),
+            ..fixture.accounts()
+        };
+        let take = fixture.load(accounts, 100, 50);
+        assert_eq!(take.quote_fill(100, 50).unwrap(), 50);
+
+        // 转出 deposit 之后 vault 剩余的 7 个由 sweep_and_close_vault 读取
+        let leftover = fund_ata(&vault_owner, &fixture.mint_a, 7).into_info();
+        assert_eq!(load_token_account(&leftover).unwrap().amount, 7);
+
+        // 宿主机上无法执行 CPI：用缺少 hook 程序的 transfer hook mint 让转账在 CPI 之前失败，
+        // 证明 close_account 之前先发起了余额转账（余额为 0 时会直接进入 close_account）
+        const INCORRECT_ACCOUNT: u32 = 2_110_272_652;
+        let hook_mint = transfer_hook_mint(Pubkey::new_unique());
+        let close = CpiContext::new_with_signer(
+            TestAccount::program(spl_token_2022::ID).into_info(),
+            CloseAccount {
+                account: fund_ata(&vault_owner, &hook_mint, 7).writable().into_info(),
+                authority: escrow_account(&fixture.escrow).into_info(),
+                destination: TestAccount::wallet(fixture.escrow.maker, 0).into_info(),
+            },
+            &[],
+        );
+        assert_err(
+            sweep_and_close_vault(
+                close,
+                hook_mint.into_info(),
+                6,
+                fund_ata(&fixture.taker, &fixture.mint_a, 0).into_info(),
+            ),
+            ProgramError::Custom(INCORRECT_ACCOUNT),
+        );
+    }
+
+    #[test]
+    fn rent_preflight_covers_every_missing_ata() {
+        set_clock(1_000, 1);
+        let fixture = Fixture::new();
+        let rent = Rent::default().minimum_balance(165);
+        let missing = |account: TestAccount| TestAccount::wallet(account.key, 0).writable();
+        let accounts = fixture.accounts();
+        let accounts = TakeAccounts {
+            taker_ata_a: missing(accounts.taker_ata_a),
+            maker_ata_b: missing(accounts.maker_ata_b),
+            treasury_ata_b: missing(accounts.treasury_ata_b),
+            ..accounts
+        };
+
+        // 三个 ATA 的租金合计在创建任何一个之前一次预检
+        let take = fixture.load(accounts.clone(), 100, 50);
+        assert_eq!(take.missing_atas().len(), 3);
+        assert_eq!(take.missing_ata_rent().unwrap(), 3 * rent);
+
+        // 够付其中两个也不会开始创建
+        let short = TakeAccounts {
+            taker: TestAccount::wallet(fixture.taker, 3 * rent - 1).signer(),
+            ..accounts
+        };
+        assert_error(
+            fixture.take_with(short, 100, 50),
+            EscrowError::InsufficientLamportsForRent,
+        );
+    }
+
+    #[test]
+    fn shared_ata_address_is_created_once() {
+        set_clock(1_000, 1);
+        let mut fixture = Fixture::new();
+        // treasury 即 taker：treasury_ata_b 与 taker_ata_b 是同一个地址
+        fixture.config.treasury = fixture.taker;
+        let accounts = fixture.accounts();
+        let taker_ata_b = TestAccount::wallet(accounts.taker_ata_b.key, 0).writable();
+        let accounts = TakeAccounts {
+            taker_ata_b: taker_ata_b.clone(),
+            treasury_ata_b: taker_ata_b,
+            ..accounts
+        };
+
+        let take = fixture.load(accounts, 100, 50);
+        assert_eq!(take.missing_atas().len(), 1);
+        assert_eq!(take.missing_ata_rent().unwrap(), Rent::default().minimum_balance(165));
+    }
+
+    #[test]
+    fn account_structs_fit_stack_frame() {
+        // 反序列化后的账户结构需远小于 SBF 的 4KB 栈帧，防止再把大账户直接展开
+        assert!(std::mem::size_of::<Take>() <= 2048);
+        assert!(std::mem::size_of::<crate::instructions::Refund>() <= 2048);
+        assert!(std::mem::size_of::<crate::instructions::Make>() <= 2048);
+    }
 }
@@ -0,0 +1,463 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_interface::{
+        close_account,
+        transfer_checked,
+        CloseAccount,
+        Mint,
+        TokenAccount,
+        TokenInterface,
+        TransferChecked,
+    },
+};
+
+use crate::state::{Config, Escrow, EscrowStatus, MakerStats};
+use crate::errors::EscrowError;
+use crate::events::EscrowTaken;
+use crate::instructions::take::gross_up_transfer_fee;
+
+/// TakeBasket 指令：
+///
+/// 整单成交 make_basket 创建的组合报价：
+/// - Token B：taker -> maker（扣除协议费）+ taker -> treasury（协议费）
+/// - 两种 Token A：vault / vault2 -> taker
+/// - 关闭两个 vault 与 escrow（lamports 返还给 rent_collector，默认 maker）
+#[derive(Accounts)]
+pub struct TakeBasket<'info> {
+    /// 接受报价的用户（支付 Token B）
+    #[account(mut)]
+    pub taker: Signer<'info>,
+
+    /// 创建 escrow 的用户（接收 Token B）
+    #[account(mut)]
+    pub maker: SystemAccount<'info>,
+
+    /// Escrow 状态账户（必须是组合报价）
+    #[account(
+        mut,
+        seeds = [b"escrow", escrow.creator.as_ref(), escrow.seed.to_le_bytes().as_ref()],
+        bump = escrow.bump,
+        constraint = escrow.has_canonical_bump() @ EscrowError::InvalidBump,
+        has_one = maker @ EscrowError::InvalidMaker,
+        has_one = mint_a @ EscrowError::InvalidMintA,
+        has_one = mint_a2 @ EscrowError::InvalidMintA,
+        has_one = mint_b @ EscrowError::InvalidMintB,
+        constraint = escrow.is_basket() @ EscrowError::BasketMismatch,
+        constraint = escrow.is_active() @ EscrowError::EscrowNotActive,
+    )]
+    pub escrow: Box<Account<'info, Escrow>>,
+
+    /// CHECK: 仅接收关闭 vault / escrow 返还的 lamports，
+    /// 地址为 escrow.rent_collector（未设置时为 maker）
+    #[account(
+        mut,
+        address = escrow.rent_destination(escrow.maker),
+    )]
+    pub rent_collector: UncheckedAccount<'info>,
+
+    /// CHECK: 接收 Token B 的钱包（仅作为 maker_ata_b 的 authority），
+    /// 地址为 escrow.proceeds_recipient（未设置时为 maker）
+    #[account(address = escrow.proceeds_destination())]
+    pub proceeds_recipient: UncheckedAccount<'info>,
+
+    /// 全局配置（协议费率与 treasury）
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+    )]
+    pub config: Box<Account<'info, Config>>,
+
+    /// maker 统计 PDA（可选，传入时累加 total_taken；config 限制挂单数时必须传入）
+    #[account(
+        mut,
+        seeds = [b"stats", maker.key().as_ref()],
+        bump = maker_stats.bump,
+    )]
+    pub maker_stats: Option<Box<Account<'info, MakerStats>>>,
+
+    /// CHECK: 仅作为 treasury ATA 的 authority，地址由 config.treasury 约束
+    #[account(address = config.treasury)]
+    pub treasury: UncheckedAccount<'info>,
+
+    /// ===== Token Mints =====
+
+    #[account(
+        mint::token_program = token_program,
+        constraint = mint_a.decimals == escrow.mint_a_decimals @ EscrowError::DecimalsMismatch,
+    )]
+    pub mint_a: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        mint::token_program = token_program
+    )]
+    pub mint_a2: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        mint::token_program = token_program,
+        constraint = mint_b.decimals == escrow.mint_b_decimals @ EscrowError::DecimalsMismatch,
+    )]
+    pub mint_b: Box<InterfaceAccount<'info, Mint>>,
+
+    /// ===== Token Accounts =====
+
+    /// Vault：escrow 持有的 mint_a
+    #[account(
+        mut,
+        associated_token::mint = mint_a,
+        associated_token::authority = escrow,
+        associated_token::token_program = token_program
+    )]
+    pub vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Vault2：escrow 持有的 mint_a2
+    #[account(
+        mut,
+        associated_token::mint = mint_a2,
+        associated_token::authority = escrow,
+        associated_token::token_program = token_program
+    )]
+    pub vault2: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Taker 的 mint_a ATA
+    #[account(
+        init_if_needed,
+        payer = taker,
+        associated_token::mint = mint_a,
+        associated_token::authority = taker,
+        associated_token::token_program = token_program
+    )]
+    pub taker_ata_a: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Taker 的 mint_a2 ATA
+    #[account(
+        init_if_needed,
+        payer = taker,
+        associated_token::mint = mint_a2,
+        associated_token::authority = taker,
+        associated_token::token_program = token_program
+    )]
+    pub taker_ata_a2: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Taker 的 Token B ATA（支付给 maker）
+    #[account(
+        mut,
+        associated_token::mint = mint_b,
+        associated_token::authority = taker,
+        associated_token::token_program = token_program
+    )]
+    pub taker_ata_b: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// proceeds_recipient 的 Token B ATA（接收 taker 的 Token B）
+    #[account(
+        init_if_needed,
+        payer = taker,
+        associated_token::mint = mint_b,
+        associated_token::authority = proceeds_recipient,
+        associated_token::token_program = token_program
+    )]
+    pub maker_ata_b: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Treasury 的 Token B ATA（接收协议费）
+    #[account(
+        init_if_needed,
+        payer = taker,
+        associated_token::mint = mint_b,
+        associated_token::authority = treasury,
+        associated_token::token_program = token_program
+    )]
+    pub treasury_ata_b: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// ===== Programs =====
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> TakeBasket<'info> {
+    /// Token B：taker -> to
+    fn transfer_from_taker(&self, to: AccountInfo<'info>, amount: u64) -> Result<()> {
+        transfer_checked(
+            CpiContext::new(
+                self.token_program.to_account_info(),
+                TransferChecked {
+                    from: self.taker_ata_b.to_account_info(),
+                    to,
+                    mint: self.mint_b.to_account_info(),
+                    authority: self.taker.to_account_info(),
+                },
+            ),
+            amount,
+            self.mint_b.decimals,
+        )?;
+
+        Ok(())
+    }
+
+    /// 将 vault 全部余额转给 taker 并关闭 vault
+    fn withdraw_and_close(
+        &self,
+        vault: &InterfaceAccount<'info, TokenAccount>,
+        mint: &InterfaceAccount<'info, Mint>,
+        to: &InterfaceAccount<'info, TokenAccount>,
+    ) -> Result<()> {
+        let seed_bytes = self.escrow.seed.to_le_bytes();
+        let seeds = self.escrow.signer_seeds(&seed_bytes);
+        let signer_seeds: [&[&[u8]]; 1] = [&seeds];
+
+        transfer_checked(
+            CpiContext::new_with_signer(
+                self.token_program.to_account_info(),
+                TransferChecked {
+                    from: vault.to_account_info(),
+                    to: to.to_account_info(),
+                    mint: mint.to_account_info(),
+                    authority: self.escrow.to_account_info(),
+                },
+                &signer_seeds,
+            ),
+            vault.amount,
+            mint.decimals,
+        )?;
+
+        close_account(CpiContext::new_with_signer(
+            self.token_program.to_account_info(),
+            CloseAccount {
+                account: vault.to_account_info(),
+                authority: self.escrow.to_account_info(),
+                destination: self.rent_collector.to_account_info(),
+            },
+            &signer_seeds,
+        ))?;
+
+        Ok(())
+    }
+}
+
+/// TakeBasket 指令入口
+///
+/// 参数说明：
+/// - max_receive: taker 愿意支付的 Token B 上限（滑点保护，u64::MAX 表示不检查）
+pub fn handler(ctx: Context<TakeBasket>, max_receive: u64) -> Result<()> {
+    // =======================
+    // 成交条件校验
+    // =======================
+    require!(!ctx.accounts.config.paused, EscrowError::ProgramPaused);
+
+    // 不接受附言，config.require_take_memo 时只能通过 take 成交
+    ctx.accounts.config.check_take_memo(&[])?;
+
+    // 不接受门槛账户，设置了 gate_mint 的 escrow 只能通过 take 成交
+    require!(
+        !ctx.accounts.escrow.is_gated(),
+        EscrowError::GateRequirementNotMet
+    );
+
+    let now = Clock::get()?.unix_timestamp;
+    let escrow = &ctx.accounts.escrow;
+    require!(!escrow.is_expired(now), EscrowError::OfferExpired);
+    require_keys_neq!(
+        ctx.accounts.taker.key(),
+        escrow.maker,
+        EscrowError::MakerCannotTake
+    );
+    require!(
+        escrow.is_taker_allowed(ctx.accounts.taker.key),
+        EscrowError::UnauthorizedTaker
+    );
+
+    require!(
+        ctx.accounts.vault.amount > 0 && ctx.accounts.vault2.amount > 0,
+        EscrowError::VaultEmpty
+    );
+    require!(
+        !ctx.accounts.vault.is_frozen()
+            && !ctx.accounts.vault2.is_frozen()
+            && !ctx.accounts.taker_ata_a.is_frozen()
+            && !ctx.accounts.taker_ata_a2.is_frozen()
+            && !ctx.accounts.taker_ata_b.is_frozen()
+            && !ctx.accounts.maker_ata_b.is_frozen(),
+        EscrowError::AccountFrozen
+    );
+
+    // 整单成交，价格即 receive
+    let amount_b = escrow.current_receive(now)?;
+    require!(amount_b <= max_receive, EscrowError::SlippageExceeded);
+
+    // =======================
+    // 交割
+    // =======================
+    let fee = ctx.accounts.config.fee_for(&ctx.accounts.escrow, amount_b)?;
+    let maker_net = amount_b
+        .checked_sub(fee)
+        .ok_or(error!(EscrowError::MathOverflow))?;
+    let maker_gross = gross_up_transfer_fee(&ctx.accounts.mint_b.to_account_info(), maker_net)?;
+
+    // 1️⃣ taker -> maker / treasury（Token B）
+    let accounts = &ctx.accounts;
+    accounts.transfer_from_taker(accounts.maker_ata_b.to_account_info(), maker_gross)?;
+    if fee > 0 {
+        require!(
+            !accounts.treasury_ata_b.is_frozen(),
+            EscrowError::AccountFrozen
+        );
+        accounts.transfer_from_taker(accounts.treasury_ata_b.to_account_info(), fee)?;
+    }
+
+    // 2️⃣ vault / vault2 -> taker（两种 Token A），并关闭两个 vault
+    accounts.withdraw_and_close(&accounts.vault, &accounts.mint_a, &accounts.taker_ata_a)?;
+    accounts.withdraw_and_close(&accounts.vault2, &accounts.mint_a2, &accounts.taker_ata_a2)?;
+
+    // 3️⃣ 关闭 escrow
+    let accounts = &mut *ctx.accounts;
+    let amount_a = accounts.escrow.deposit;
+    accounts.escrow.deposit = 0;
+    accounts.escrow.deposit2 = 0;
+    accounts.escrow.status = EscrowStatus::Filled;
+    accounts.escrow.close(accounts.rent_collector.to_account_info())?;
+
+    require!(
+        accounts.maker_stats.is_some() || !accounts.config.tracks_open_escrows(),
+        EscrowError::MakerStatsRequired
+    );
+    if let Some(stats) = accounts.maker_stats.as_mut() {
+        stats.record_taken()?;
+    }
+
+    emit!(EscrowTaken {
+        escrow: accounts.escrow.key(),
+        taker: accounts.taker.key(),
+        amount_a,
+        amount_b,
+        remaining_deposit: 0,
+        memo: Vec::new(),
+    });
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::*;
+
+    const TAKE_BASKET: u8 = 19;
+
+    /// 10 个 mint_a + 5 个 mint_a2 换 100 个 Token B 的组合报价
+    struct Basket {
+        taker: Pubkey,
+        escrow: Escrow,
+        config: Config,
+        mint_a: TestAccount,
+        mint_a2: TestAccount,
+        mint_b: TestAccount,
+    }
+
+    impl Basket {
+        fn new() -> Self {
+            let (mint_a, mint_b) = setup_mints(6, 6);
+            let mint_a2 = mint_account(9);
+            let mut escrow = make_escrow(&Pubkey::new_unique(), 6, &mint_a, &mint_b, 10, 100);
+            escrow.mint_a2 = mint_a2.key;
+            escrow.deposit2 = 5;
+
+            Self {
+                taker: Pubkey::new_unique(),
+                escrow,
+                config: default_config(),
+                mint_a,
+                mint_a2,
+                mint_b,
+            }
+        }
+
+        fn accounts(&self) -> Vec<TestAccount> {
+            let escrow_key = escrow_account(&self.escrow).key;
+            let maker = self.escrow.maker;
+
+            vec![
+                TestAccount::wallet(self.taker, 1_000_000_000).signer(),
+                TestAccount::wallet(maker, 0).writable(),
+                escrow_account(&self.escrow).writable(),
+                TestAccount::wallet(maker, 0).writable(),
+                TestAccount::wallet(maker, 0),
+                config_account(&self.config),
+                none(),
+                TestAccount::wallet(self.config.treasury, 0),
+                self.mint_a.clone(),
+                self.mint_a2.clone(),
+                self.mint_b.clone(),
+                fund_ata(&escrow_key, &self.mint_a, self.escrow.deposit).writable(),
+                fund_ata(&escrow_key, &self.mint_a2, self.escrow.deposit2).writable(),
+                fund_ata(&self.taker, &self.mint_a, 0).writable(),
+                fund_ata(&self.taker, &self.mint_a2, 0).writable(),
+                fund_ata(&self.taker, &self.mint_b, 1_000).writable(),
+                fund_ata(&maker, &self.mint_b, 0).writable(),
+                fund_ata(&self.config.treasury, &self.mint_b, 0).writable(),
+                TestAccount::program(anchor_spl::associated_token::ID),
+                TestAccount::program(anchor_spl::token::ID),
+                TestAccount::program(anchor_lang::system_program::ID),
+            ]
+        }
+
+        fn take_with(&self, accounts: Vec<TestAccount>, max_receive: u64) -> ProgramResult {
+            run(&ix_data(TAKE_BASKET, max_receive), accounts).0
+        }
+    }
+
+    #[test]
+    fn basket_is_taken_as_a_whole() {
+        set_clock(1_000, 1);
+        let basket = Basket::new();
+
+        // 两个 vault 均校验通过，整单按 receive 报价
+        assert_error(
+            basket.take_with(basket.accounts(), 99),
+            EscrowError::SlippageExceeded,
+        );
+    }
+
+    #[test]
+    fn both_vaults_must_hold_tokens() {
+        set_clock(1_000, 1);
+        let basket = Basket::new();
+        let escrow_key = escrow_account(&basket.escrow).key;
+
+        let mut accounts = basket.accounts();
+        accounts[12] = fund_ata(&escrow_key, &basket.mint_a2, 0).writable();
+
+        assert_error(basket.take_with(accounts, u64::MAX), EscrowError::VaultEmpty);
+    }
+
+    #[test]
+    fn basket_requires_both_mints() {
+        set_clock(1_000, 1);
+        let mut basket = Basket::new();
+
+        // 传入的第二种 Token A（及其 token 账户）与 escrow 不符
+        basket.mint_a2 = mint_account(9);
+        assert_error(
+            basket.take_with(basket.accounts(), u64::MAX),
+            EscrowError::InvalidMintA,
+        );
+    }
+
+    #[test]
+    fn basket_cannot_be_taken_as_single_asset() {
+        set_clock(1_000, 1);
+        let basket = Basket::new();
+        let accounts = take_accounts(
+            &basket.taker,
+            &basket.escrow,
+            &basket.config,
+            &basket.mint_a,
+            &basket.mint_b,
+            1_000,
+        )
+        .into_vec();
+        let args = (10u64, u64::MAX, false, i64::MAX, Vec::<u8>::new());
+
+        // take 只会转出 vault 中的 mint_a，组合报价必须整单通过 take_basket 成交
+        assert_error(run(&ix_data(1, args), accounts).0, EscrowError::BasketMismatch);
+    }
+}
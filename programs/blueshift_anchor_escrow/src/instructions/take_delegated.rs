@@ -0,0 +1,421 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_interface::{
+        Mint,
+        TokenAccount,
+        TokenInterface,
+        TransferChecked,
+    },
+};
+
+use crate::state::{Config, Escrow, EscrowStatus, MakerStats};
+use crate::errors::EscrowError;
+use crate::events::EscrowTaken;
+use crate::instructions::take::{gross_up_transfer_fee, quote_fill, transfer_checked_with_hook};
+
+/// TakeDelegated 指令：
+///
+/// 成交由 make_delegated 创建的 escrow（无 vault）：
+/// - Token B：taker -> maker（扣除协议费）+ taker -> treasury（协议费）
+/// - Token A：maker_ata_a -> taker（escrow PDA 以 delegate 身份签名）
+/// - 全部成交后关闭 escrow（lamports 返还给 rent_collector，默认 maker）
+///
+/// mint_a / mint_b 的 transfer hook 所需账户通过 remaining_accounts 传入（与 take 相同）
+#[derive(Accounts)]
+pub struct TakeDelegated<'info> {
+    /// 接受报价的用户（支付 Token B）
+    #[account(mut)]
+    pub taker: Signer<'info>,
+
+    /// 创建 escrow 的用户（接收 Token B）
+    #[account(mut)]
+    pub maker: SystemAccount<'info>,
+
+    /// Escrow 状态账户（必须是 delegated escrow）
+    #[account(
+        mut,
+        seeds = [b"escrow", escrow.creator.as_ref(), escrow.seed.to_le_bytes().as_ref()],
+        bump = escrow.bump,
+        has_one = maker @ EscrowError::InvalidMaker,
+        has_one = mint_a @ EscrowError::InvalidMintA,
+        constraint = escrow.is_active() @ EscrowError::EscrowNotActive,
+        constraint = escrow.is_delegated @ EscrowError::DelegateRevoked,
+    )]
+    pub escrow: Box<Account<'info, Escrow>>,
+
+    /// CHECK: 仅接收关闭 escrow 返还的 lamports，
+    /// 地址为 escrow.rent_collector（未设置时为 maker）
+    #[account(
+        mut,
+        address = escrow.rent_destination(escrow.maker),
+    )]
+    pub rent_collector: UncheckedAccount<'info>,
+
+    /// CHECK: 接收 Token B 的钱包（仅作为 maker_ata_b 的 authority），
+    /// 地址为 escrow.proceeds_recipient（未设置时为 maker）
+    #[account(address = escrow.proceeds_destination())]
+    pub proceeds_recipient: UncheckedAccount<'info>,
+
+    /// 全局配置（协议费率与 treasury）
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+    )]
+    pub config: Box<Account<'info, Config>>,
+
+    /// maker 统计 PDA（可选，传入时全部成交会累加 total_taken；config 限制挂单数时必须传入）
+    #[account(
+        mut,
+        seeds = [b"stats", maker.key().as_ref()],
+        bump = maker_stats.bump,
+    )]
+    pub maker_stats: Option<Box<Account<'info, MakerStats>>>,
+
+    /// CHECK: 仅作为 treasury ATA 的 authority，地址由 config.treasury 约束
+    #[account(address = config.treasury)]
+    pub treasury: UncheckedAccount<'info>,
+
+    /// ===== Token Mints =====
+
+    /// Token A（从 maker_ata_a 转给 taker）
+    #[account(
+        mint::token_program = token_program,
+        constraint = mint_a.decimals == escrow.mint_a_decimals @ EscrowError::DecimalsMismatch,
+    )]
+    pub mint_a: Box<InterfaceAccount<'info, Mint>>,
+
+    /// Token B（从 taker 转给 maker），可以是 escrow.mint_b 或 escrow.mint_b_alt
+    #[account(
+        mint::token_program = token_program,
+        constraint = escrow.mint_b_decimals_match(&mint_b.key(), mint_b.decimals) @ EscrowError::DecimalsMismatch,
+    )]
+    pub mint_b: Box<InterfaceAccount<'info, Mint>>,
+
+    /// ===== Token Accounts =====
+
+    /// Maker 的 Token A ATA（delegate = escrow PDA）
+    #[account(
+        mut,
+        associated_token::mint = mint_a,
+        associated_token::authority = maker,
+        associated_token::token_program = token_program
+    )]
+    pub maker_ata_a: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Taker 的 Token A ATA（接收 Token A）
+    #[account(
+        init_if_needed,
+        payer = taker,
+        associated_token::mint = mint_a,
+        associated_token::authority = taker,
+        associated_token::token_program = token_program
+    )]
+    pub taker_ata_a: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Taker 的 Token B ATA（支付给 maker）
+    #[account(
+        mut,
+        associated_token::mint = mint_b,
+        associated_token::authority = taker,
+        associated_token::token_program = token_program
+    )]
+    pub taker_ata_b: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// proceeds_recipient 的 Token B ATA（接收 taker 的 Token B）
+    #[account(
+        init_if_needed,
+        payer = taker,
+        associated_token::mint = mint_b,
+        associated_token::authority = proceeds_recipient,
+        associated_token::token_program = token_program
+    )]
+    pub maker_ata_b: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Treasury 的 Token B ATA（接收协议费）
+    #[account(
+        init_if_needed,
+        payer = taker,
+        associated_token::mint = mint_b,
+        associated_token::authority = treasury,
+        associated_token::token_program = token_program
+    )]
+    pub treasury_ata_b: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// ===== Programs =====
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> TakeDelegated<'info> {
+    /// Token B：taker -> to
+    fn transfer_from_taker(
+        &self,
+        to: AccountInfo<'info>,
+        amount: u64,
+        remaining: &[AccountInfo<'info>],
+    ) -> Result<()> {
+        transfer_checked_with_hook(
+            CpiContext::new(
+                self.token_program.to_account_info(),
+                TransferChecked {
+                    from: self.taker_ata_b.to_account_info(),
+                    to,
+                    mint: self.mint_b.to_account_info(),
+                    authority: self.taker.to_account_info(),
+                },
+            )
+            .with_remaining_accounts(remaining.to_vec()),
+            amount,
+            self.mint_b.decimals,
+        )?;
+
+        Ok(())
+    }
+
+    /// Token A：maker_ata_a -> taker，escrow PDA 以 delegate 身份签名
+    fn pull_from_maker(&self, amount: u64, remaining: &[AccountInfo<'info>]) -> Result<()> {
+        let seed_bytes = self.escrow.seed.to_le_bytes();
+        let seeds = self.escrow.signer_seeds(&seed_bytes);
+        let signer_seeds: [&[&[u8]]; 1] = [&seeds];
+
+        transfer_checked_with_hook(
+            CpiContext::new_with_signer(
+                self.token_program.to_account_info(),
+                TransferChecked {
+                    from: self.maker_ata_a.to_account_info(),
+                    to: self.taker_ata_a.to_account_info(),
+                    mint: self.mint_a.to_account_info(),
+                    authority: self.escrow.to_account_info(),
+                },
+                &signer_seeds,
+            )
+            .with_remaining_accounts(remaining.to_vec()),
+            amount,
+            self.mint_a.decimals,
+        )?;
+
+        Ok(())
+    }
+
+    /// 校验 delegate 授权与 token 账户状态（成交条件由 take::quote_fill 校验）
+    fn check_fill_accounts(&self, fill_amount: u64) -> Result<()> {
+        // 与 take 相同：付款账户与收款账户相同时转账是空操作，对方实际收不到 token
+        require_keys_neq!(
+            self.taker_ata_b.key(),
+            self.maker_ata_b.key(),
+            EscrowError::DuplicateTokenAccount
+        );
+        require_keys_neq!(
+            self.taker_ata_a.key(),
+            self.maker_ata_a.key(),
+            EscrowError::DuplicateTokenAccount
+        );
+
+        // maker 可能已 revoke 授权或转走 Token A
+        if self.maker_ata_a.delegate != Some(self.escrow.key()).into()
+            || self.maker_ata_a.delegated_amount < fill_amount
+        {
+            msg!(
+                "DelegateRevoked: expected delegate {} for {}, got {:?} for {}",
+                self.escrow.key(),
+                fill_amount,
+                Option::<Pubkey>::from(self.maker_ata_a.delegate),
+                self.maker_ata_a.delegated_amount
+            );
+            return err!(EscrowError::DelegateRevoked);
+        }
+        require!(
+            self.maker_ata_a.amount >= fill_amount,
+            EscrowError::InsufficientFunds
+        );
+
+        require!(
+            !self.maker_ata_a.is_frozen()
+                && !self.taker_ata_a.is_frozen()
+                && !self.taker_ata_b.is_frozen()
+                && !self.maker_ata_b.is_frozen(),
+            EscrowError::AccountFrozen
+        );
+
+        Ok(())
+    }
+}
+
+/// TakeDelegated 指令入口
+///
+/// 参数说明：
+/// - fill_amount: taker 希望获得的 Token A 数量（支持部分成交）
+/// - max_receive: taker 本次愿意支付的 Token B 上限（滑点保护）
+///
+/// transfer hook 所需账户通过 remaining_accounts 传入
+pub fn handler<'info>(
+    ctx: Context<'_, '_, 'info, 'info, TakeDelegated<'info>>,
+    fill_amount: u64,
+    max_receive: u64,
+) -> Result<()> {
+    let alt = ctx.accounts.escrow.check_mint_b(&ctx.accounts.mint_b.key())?;
+
+    // 不接受附言与门槛账户：config.require_take_memo 或设置了 gate_mint 时只能通过 take 成交
+    ctx.accounts.config.check_take_memo(&[])?;
+    let amount_b = quote_fill(
+        &ctx.accounts.escrow,
+        &ctx.accounts.config,
+        ctx.accounts.taker.key,
+        None,
+        fill_amount,
+        max_receive,
+        alt,
+    )?;
+    ctx.accounts.check_fill_accounts(fill_amount)?;
+
+    // 协议费从本次成交的 Token B 中扣除，Token-2022 转账费由 taker 承担
+    let fee = ctx.accounts.config.fee_for(&ctx.accounts.escrow, amount_b)?;
+    let maker_net = amount_b
+        .checked_sub(fee)
+        .ok_or(error!(EscrowError::MathOverflow))?;
+    let maker_gross = gross_up_transfer_fee(&ctx.accounts.mint_b.to_account_info(), maker_net)?;
+
+    // 1️⃣ taker -> maker / treasury（Token B）
+    let remaining = ctx.remaining_accounts;
+    ctx.accounts.transfer_from_taker(
+        ctx.accounts.maker_ata_b.to_account_info(),
+        maker_gross,
+        remaining,
+    )?;
+    if fee > 0 {
+        require!(
+            !ctx.accounts.treasury_ata_b.is_frozen(),
+            EscrowError::AccountFrozen
+        );
+        ctx.accounts
+            .transfer_from_taker(ctx.accounts.treasury_ata_b.to_account_info(), fee, remaining)?;
+    }
+
+    // 2️⃣ maker_ata_a -> taker（Token A，delegate 签名）
+    ctx.accounts.pull_from_maker(fill_amount, remaining)?;
+
+    // 3️⃣ 更新剩余条款，全部成交时关闭 escrow
+    let accounts = &mut *ctx.accounts;
+    accounts.escrow.apply_fill(fill_amount, amount_b, alt)?;
+    if accounts.escrow.deposit > 0 {
+        accounts.escrow.status = EscrowStatus::PartiallyFilled;
+    } else {
+        accounts.escrow.status = EscrowStatus::Filled;
+        accounts.escrow.close(accounts.rent_collector.to_account_info())?;
+
+        require!(
+            accounts.maker_stats.is_some() || !accounts.config.tracks_open_escrows(),
+            EscrowError::MakerStatsRequired
+        );
+        if let Some(stats) = accounts.maker_stats.as_mut() {
+            stats.record_taken()?;
+        }
+    }
+
+    emit!(EscrowTaken {
+        escrow: accounts.escrow.key(),
+        taker: accounts.taker.key(),
+        amount_a: fill_amount,
+        amount_b,
+        remaining_deposit: accounts.escrow.deposit,
+        memo: Vec::new(),
+    });
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use anchor_spl::token_2022::spl_token_2022;
+
+    use super::*;
+    use crate::test_utils::*;
+
+    const TAKE_DELEGATED: u8 = 15;
+
+    /// spl-transfer-hook-interface 的 TransferHookError::IncorrectAccount
+    const INCORRECT_ACCOUNT: u32 = 2_110_272_652;
+
+    /// maker 授权 escrow 动用 100 Token A、换 50 Token B 的 delegated escrow
+    struct Fixture {
+        taker: Pubkey,
+        escrow: Escrow,
+        config: Config,
+        mint_a: TestAccount,
+        mint_b: TestAccount,
+    }
+
+    impl Fixture {
+        fn new(mint_a: TestAccount, mint_b: TestAccount) -> Self {
+            let escrow = Escrow {
+                is_delegated: true,
+                ..make_escrow(&Pubkey::new_unique(), 1, &mint_a, &mint_b, 100, 50)
+            };
+
+            Self {
+                taker: Pubkey::new_unique(),
+                escrow,
+                config: default_config(),
+                mint_a,
+                mint_b,
+            }
+        }
+
+        /// TakeDelegated 的全部账户（按结构体字段顺序），taker 持有 1_000 Token B
+        fn take(&self) -> ProgramResult {
+            let Self { taker, escrow, config, mint_a, mint_b } = self;
+            let escrow_key = escrow_account(escrow).key;
+            let proceeds = escrow.proceeds_destination();
+            let maker_ata_a = approve(fund_ata(&escrow.maker, mint_a, 100), &escrow_key, 100);
+            let accounts = vec![
+                TestAccount::wallet(*taker, 1_000_000_000).signer(),
+                TestAccount::wallet(escrow.maker, 0).writable(),
+                escrow_account(escrow).writable(),
+                TestAccount::wallet(escrow.rent_destination(escrow.maker), 0).writable(),
+                TestAccount::wallet(proceeds, 0),
+                config_account(config),
+                none(),
+                TestAccount::wallet(config.treasury, 0),
+                mint_a.clone(),
+                mint_b.clone(),
+                maker_ata_a.writable(),
+                fund_ata(taker, mint_a, 0).writable(),
+                fund_ata(taker, mint_b, 1_000).writable(),
+                fund_ata(&proceeds, mint_b, 0).writable(),
+                fund_ata(&config.treasury, mint_b, 0).writable(),
+                TestAccount::program(anchor_spl::associated_token::ID),
+                TestAccount::program(mint_a.owner),
+                TestAccount::program(anchor_lang::system_program::ID),
+            ];
+
+            run(&ix_data(TAKE_DELEGATED, (100u64, 50u64)), accounts).0
+        }
+    }
+
+    #[test]
+    fn taker_cannot_pay_into_own_account() {
+        set_clock(1_000, 1);
+        let (mint_a, mint_b) = setup_mints(6, 6);
+        let mut fixture = Fixture::new(mint_a, mint_b);
+        // proceeds_recipient 为 taker 时 maker_ata_b 与 taker_ata_b 是同一个账户
+        fixture.escrow.proceeds_recipient = fixture.taker;
+
+        assert_error(fixture.take(), EscrowError::DuplicateTokenAccount);
+    }
+
+    #[test]
+    fn transfers_resolve_mint_transfer_hooks() {
+        set_clock(1_000, 1);
+        let token_2022_mint = TestAccount {
+            owner: spl_token_2022::ID,
+            ..mint_account(6)
+        };
+        let fixture = Fixture::new(token_2022_mint, transfer_hook_mint(Pubkey::new_unique()));
+
+        // mint_b 配置了 hook 但 remaining_accounts 中没有 hook 程序：
+        // 第一笔 Token B 转账在 CPI 之前即失败，而不是绕过 hook 直接转账
+        assert_error(fixture.take(), ProgramError::Custom(INCORRECT_ACCOUNT));
+    }
+}
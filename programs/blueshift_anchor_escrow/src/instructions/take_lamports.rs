@@ -0,0 +1,364 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::{get_associated_token_address_with_program_id, AssociatedToken},
+    token::spl_token::native_mint,
+    token_interface::{
+        Mint,
+        TokenAccount,
+        TokenInterface,
+        TransferChecked,
+        CloseAccount,
+    },
+};
+
+use crate::state::{transfer_lamports_rent_safe, Config, Escrow, EscrowStatus, MakerStats};
+use crate::errors::EscrowError;
+use crate::events::EscrowTaken;
+use crate::instructions::take::{quote_fill, sweep_and_close_vault, transfer_checked_with_hook};
+
+/// TakeLamports 指令：
+///
+/// escrow.mint_b 为 native mint（wSOL）时，taker 直接用 lamports 支付，
+/// 不创建 / 关闭任何 wSOL 账户（与 take_with_sol 相比省去包装与解包装）：
+/// - lamports：taker -> proceeds_recipient（扣除协议费）+ taker -> treasury（协议费）
+/// - Token A：vault -> taker
+/// - 全部成交后关闭 vault 和 escrow（与 take 相同）
+///
+/// 只按 escrow.mint_b 报价成交，不支持 mint_b_alt 与推荐人分成；
+/// treasury / proceeds_recipient 为系统账户，收到的 lamports 需满足免租要求
+#[derive(Accounts)]
+pub struct TakeLamports<'info> {
+    /// 接受报价的用户（支付 lamports）
+    #[account(mut)]
+    pub taker: Signer<'info>,
+
+    /// 创建 escrow 的用户
+    #[account(mut)]
+    pub maker: SystemAccount<'info>,
+
+    /// Escrow 状态账户（mint_b 必须是 native mint）
+    #[account(
+        mut,
+        seeds = [b"escrow", escrow.creator.as_ref(), escrow.seed.to_le_bytes().as_ref()],
+        bump = escrow.bump,
+        has_one = maker @ EscrowError::InvalidMaker,
+        has_one = mint_a @ EscrowError::InvalidMintA,
+        constraint = escrow.mint_b == native_mint::ID @ EscrowError::NotNativeMint,
+        constraint = !escrow.is_basket() @ EscrowError::BasketMismatch,
+        constraint = escrow.is_active() @ EscrowError::EscrowNotActive,
+        constraint = !escrow.in_progress @ EscrowError::ReentrancyDetected,
+    )]
+    pub escrow: Box<Account<'info, Escrow>>,
+
+    /// CHECK: 仅接收关闭 escrow 返还的 lamports，
+    /// 地址为 escrow.rent_collector（未设置时为 maker）
+    #[account(
+        mut,
+        address = escrow.rent_destination(escrow.maker),
+    )]
+    pub rent_collector: UncheckedAccount<'info>,
+
+    /// CHECK: 仅接收关闭 vault 返还的 lamports，
+    /// 地址为 escrow.vault_rent_payer（未设置时与 rent_collector 相同）
+    #[account(
+        mut,
+        address = escrow.vault_rent_destination(escrow.maker),
+    )]
+    pub vault_rent_payer: UncheckedAccount<'info>,
+
+    /// CHECK: 接收 lamports 货款的钱包，
+    /// 地址为 escrow.proceeds_recipient（未设置时为 maker）
+    #[account(
+        mut,
+        address = escrow.proceeds_destination(),
+    )]
+    pub proceeds_recipient: UncheckedAccount<'info>,
+
+    /// 全局配置（协议费率与 treasury）
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+    )]
+    pub config: Box<Account<'info, Config>>,
+
+    /// maker 统计 PDA（可选，传入时全部成交会累加 total_taken；config 限制挂单数时必须传入）
+    #[account(
+        mut,
+        seeds = [b"stats", maker.key().as_ref()],
+        bump = maker_stats.bump,
+    )]
+    pub maker_stats: Option<Box<Account<'info, MakerStats>>>,
+
+    /// CHECK: 接收 lamports 协议费，地址由 config.treasury 约束
+    #[account(
+        mut,
+        address = config.treasury,
+    )]
+    pub treasury: UncheckedAccount<'info>,
+
+    /// Token A（从 vault 转给 taker）
+    #[account(
+        mint::token_program = token_program,
+        constraint = mint_a.decimals == escrow.mint_a_decimals @ EscrowError::DecimalsMismatch,
+    )]
+    pub mint_a: Box<InterfaceAccount<'info, Mint>>,
+
+    /// Vault：escrow 持有的 Token A
+    #[account(
+        mut,
+        associated_token::mint = mint_a,
+        associated_token::authority = escrow,
+        associated_token::token_program = token_program
+    )]
+    pub vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Taker 的 Token A ATA（接收 vault 的 Token A）
+    #[account(
+        init_if_needed,
+        payer = taker,
+        associated_token::mint = mint_a,
+        associated_token::authority = taker,
+        associated_token::token_program = token_program,
+        address = get_associated_token_address_with_program_id(
+            &taker.key(),
+            &mint_a.key(),
+            &token_program.key(),
+        ) @ EscrowError::NonCanonicalAta,
+    )]
+    pub taker_ata_a: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Programs
+    #[account(address = anchor_spl::associated_token::ID)]
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> TakeLamports<'info> {
+    /// lamports：taker -> to
+    fn pay_lamports(&self, to: AccountInfo<'info>, amount: u64) -> Result<()> {
+        transfer_lamports_rent_safe(
+            &self.system_program,
+            self.taker.to_account_info(),
+            to,
+            amount,
+        )
+    }
+
+    /// 校验 vault 与 token 账户状态（成交条件由 take::quote_fill 校验）
+    fn check_fill_accounts(&self, fill_amount: u64) -> Result<()> {
+        require!(
+            !self.vault.is_frozen() && !self.taker_ata_a.is_frozen(),
+            EscrowError::AccountFrozen
+        );
+        require!(
+            self.vault.amount >= fill_amount,
+            EscrowError::VaultBalanceMismatch
+        );
+
+        Ok(())
+    }
+
+    /// 从 vault 提取 Token A 给 taker，全部成交时关闭 vault
+    fn withdraw(&mut self, amount: u64, remaining: &[AccountInfo<'info>]) -> Result<()> {
+        let seed_bytes = self.escrow.seed.to_le_bytes();
+        let seeds = self.escrow.signer_seeds(&seed_bytes);
+        let signer_seeds: [&[&[u8]]; 1] = [&seeds];
+
+        transfer_checked_with_hook(
+            CpiContext::new_with_signer(
+                self.token_program.to_account_info(),
+                TransferChecked {
+                    from: self.vault.to_account_info(),
+                    to: self.taker_ata_a.to_account_info(),
+                    mint: self.mint_a.to_account_info(),
+                    authority: self.escrow.to_account_info(),
+                },
+                &signer_seeds,
+            )
+            .with_remaining_accounts(remaining.to_vec()),
+            amount,
+            self.mint_a.decimals,
+        )?;
+
+        if amount < self.escrow.deposit {
+            return Ok(());
+        }
+
+        // 额外转入 vault 的 Token A 一并转给 taker
+        sweep_and_close_vault(
+            CpiContext::new_with_signer(
+                self.token_program.to_account_info(),
+                CloseAccount {
+                    account: self.vault.to_account_info(),
+                    authority: self.escrow.to_account_info(),
+                    destination: self.vault_rent_payer.to_account_info(),
+                },
+                &signer_seeds,
+            )
+            .with_remaining_accounts(remaining.to_vec()),
+            self.mint_a.to_account_info(),
+            self.mint_a.decimals,
+            self.taker_ata_a.to_account_info(),
+        )
+    }
+}
+
+/// TakeLamports 指令入口
+///
+/// 参数说明：
+/// - fill_amount: taker 希望获得的 Token A 数量（支持部分成交）
+/// - max_receive: taker 本次愿意支付的 lamports 上限（滑点保护）
+///
+/// mint_a 的 transfer hook 所需账户通过 remaining_accounts 传入
+pub fn handler<'info>(
+    ctx: Context<'_, '_, 'info, 'info, TakeLamports<'info>>,
+    fill_amount: u64,
+    max_receive: u64,
+) -> Result<()> {
+    // 不接受附言与门槛账户：config.require_take_memo 或设置了 gate_mint 时只能通过 take 成交；
+    // 只按 escrow.mint_b（native mint）报价
+    ctx.accounts.config.check_take_memo(&[])?;
+    let amount_b = quote_fill(
+        &ctx.accounts.escrow,
+        &ctx.accounts.config,
+        ctx.accounts.taker.key,
+        None,
+        fill_amount,
+        max_receive,
+        false,
+    )?;
+    ctx.accounts.check_fill_accounts(fill_amount)?;
+
+    let accounts = &mut *ctx.accounts;
+
+    // 与 take 相同：在任何 CPI 之前写入 in_progress，防止 transfer hook 回调重入
+    accounts.escrow.in_progress = true;
+    accounts.escrow.exit(&crate::ID)?;
+
+    // 协议费从本次成交的 lamports 中扣除
+    let fee = accounts.config.fee_for(&accounts.escrow, amount_b)?;
+    let maker_net = amount_b
+        .checked_sub(fee)
+        .ok_or(error!(EscrowError::MathOverflow))?;
+    require!(
+        accounts.taker.lamports() >= amount_b,
+        EscrowError::InsufficientFunds
+    );
+
+    // 1️⃣ taker -> proceeds_recipient / treasury（lamports）
+    accounts.pay_lamports(accounts.proceeds_recipient.to_account_info(), maker_net)?;
+    if fee > 0 {
+        accounts.pay_lamports(accounts.treasury.to_account_info(), fee)?;
+    }
+
+    // 2️⃣ vault -> taker（Token A），全部成交时关闭 vault
+    accounts.withdraw(fill_amount, ctx.remaining_accounts)?;
+
+    // 3️⃣ 更新剩余条款，全部成交时关闭 escrow
+    accounts.escrow.in_progress = false;
+    accounts.escrow.apply_fill(fill_amount, amount_b, false)?;
+    if accounts.escrow.deposit > 0 {
+        accounts.escrow.status = EscrowStatus::PartiallyFilled;
+    } else {
+        accounts.escrow.status = EscrowStatus::Filled;
+        accounts.escrow.close(accounts.rent_collector.to_account_info())?;
+
+        require!(
+            accounts.maker_stats.is_some() || !accounts.config.tracks_open_escrows(),
+            EscrowError::MakerStatsRequired
+        );
+        if let Some(stats) = accounts.maker_stats.as_mut() {
+            stats.record_taken()?;
+        }
+    }
+
+    emit!(EscrowTaken {
+        escrow: accounts.escrow.key(),
+        taker: accounts.taker.key(),
+        amount_a: fill_amount,
+        amount_b,
+        remaining_deposit: accounts.escrow.deposit,
+        memo: Vec::new(),
+    });
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use anchor_spl::token::spl_token;
+
+    use super::*;
+    use crate::test_utils::*;
+
+    const TAKE_LAMPORTS: u8 = 34;
+
+    /// 100 Token A 换 50_000 lamports 的 escrow
+    struct Fixture {
+        taker: Pubkey,
+        escrow: Escrow,
+        config: Config,
+        mint_a: TestAccount,
+    }
+
+    impl Fixture {
+        fn new(mint_b: Pubkey) -> Self {
+            let mint_a = mint_account(6);
+            let mint_b = TestAccount {
+                key: mint_b,
+                ..mint_account(9)
+            };
+
+            Self {
+                taker: Pubkey::new_unique(),
+                escrow: make_escrow(&Pubkey::new_unique(), 1, &mint_a, &mint_b, 100, 50_000),
+                config: default_config(),
+                mint_a,
+            }
+        }
+
+        fn take(&self, taker_lamports: u64, max_receive: u64) -> ProgramResult {
+            let Self { taker, escrow, config, mint_a } = self;
+            let escrow_account = escrow_account(escrow);
+            let accounts = vec![
+                TestAccount::wallet(*taker, taker_lamports).signer(),
+                TestAccount::wallet(escrow.maker, 0).writable(),
+                escrow_account.clone().writable(),
+                TestAccount::wallet(escrow.maker, 0).writable(),
+                TestAccount::wallet(escrow.maker, 0).writable(),
+                TestAccount::wallet(escrow.maker, 0).writable(),
+                config_account(config),
+                none(),
+                TestAccount::wallet(config.treasury, 0).writable(),
+                mint_a.clone(),
+                fund_ata(&escrow_account.key, mint_a, escrow.deposit).writable(),
+                fund_ata(taker, mint_a, 0).writable(),
+                TestAccount::program(anchor_spl::associated_token::ID),
+                TestAccount::program(spl_token::ID),
+                TestAccount::program(anchor_lang::system_program::ID),
+            ];
+
+            run(&ix_data(TAKE_LAMPORTS, (100u64, max_receive)), accounts).0
+        }
+    }
+
+    #[test]
+    fn requires_native_mint_b() {
+        set_clock(1_000, 1);
+        let fixture = Fixture::new(Pubkey::new_unique());
+
+        assert_error(fixture.take(1_000_000, u64::MAX), EscrowError::NotNativeMint);
+    }
+
+    #[test]
+    fn taker_pays_exactly_receive_in_lamports() {
+        set_clock(1_000, 1);
+        let fixture = Fixture::new(native_mint::ID);
+
+        // 报价为 receive 本身：低 1 lamport 即触发滑点保护
+        assert_error(fixture.take(1_000_000, 49_999), EscrowError::SlippageExceeded);
+        // lamports 不足 receive 时在转账之前失败
+        assert_error(fixture.take(49_999, 50_000), EscrowError::InsufficientFunds);
+    }
+}
@@ -0,0 +1,144 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::TokenAccount;
+
+use crate::state::mul_div;
+use crate::errors::EscrowError;
+use crate::instructions::take::*;
+
+/// TakeSplit 指令：
+///
+/// 与 take 相同，但本次成交的 Token A 按 split_bps 分给两个接收账户（例如聚合器的下游用户）：
+/// - destination_a1 获得 fill_amount * split_bps / 10000（向下取整）
+/// - destination_a2 获得剩余部分（含取整余数）
+#[derive(Accounts)]
+pub struct TakeSplit<'info> {
+    /// 与 take 完全相同的账户（take.taker_ata_a 不接收 Token A）
+    pub take: Take<'info>,
+
+    /// 第一个 Token A 接收账户
+    #[account(
+        mut,
+        constraint = destination_a1.mint == take.mint_a.key() @ EscrowError::InvalidSplit,
+        token::token_program = take.token_program,
+    )]
+    pub destination_a1: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// 第二个 Token A 接收账户
+    #[account(
+        mut,
+        constraint = destination_a2.mint == take.mint_a.key() @ EscrowError::InvalidSplit,
+        token::token_program = take.token_program,
+    )]
+    pub destination_a2: Box<InterfaceAccount<'info, TokenAccount>>,
+}
+
+/// fill_amount 按 split_bps 拆分为 (destination_a1, destination_a2) 的数量，取整余数归 destination_a2
+pub fn split_fill(fill_amount: u64, split_bps: u16) -> Result<(u64, u64)> {
+    require!(split_bps <= 10_000, EscrowError::InvalidSplit);
+
+    let amount_a1 = mul_div(fill_amount, split_bps as u64, 10_000)?;
+    let amount_a2 = fill_amount
+        .checked_sub(amount_a1)
+        .ok_or(error!(EscrowError::MathOverflow))?;
+
+    Ok((amount_a1, amount_a2))
+}
+
+/// TakeSplit 指令入口
+///
+/// 参数说明：
+/// - fill_amount / max_receive: 见 Take::quote_fill
+/// - split_bps: destination_a1 分得的比例（基点，不超过 10000）
+///
+/// transfer hook 所需账户通过 remaining_accounts 传入
+pub fn handler<'info>(
+    ctx: Context<'_, '_, 'info, 'info, TakeSplit<'info>>,
+    fill_amount: u64,
+    max_receive: u64,
+    split_bps: u16,
+) -> Result<()> {
+    let (amount_a1, amount_a2) = split_fill(fill_amount, split_bps)?;
+
+    require!(
+        !ctx.accounts.destination_a1.is_frozen() && !ctx.accounts.destination_a2.is_frozen(),
+        EscrowError::AccountFrozen
+    );
+
+    let legs = [
+        (ctx.accounts.destination_a1.to_account_info(), amount_a1),
+        (ctx.accounts.destination_a2.to_account_info(), amount_a2),
+    ];
+
+    let take = &mut ctx.accounts.take;
+    take.ensure_atas(false)?;
+
+    let amount_b = take.quote_fill(fill_amount, max_receive)?;
+
+    take.execute_fill_to(fill_amount, amount_b, &legs, &[], ctx.remaining_accounts)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::*;
+
+    const TAKE_SPLIT: u8 = 32;
+
+    #[test]
+    fn split_gives_remainder_to_second_destination() {
+        // 101 * 33.33% = 33.66，向下取整为 33，余数归 destination_a2
+        assert_eq!(split_fill(101, 3_333).unwrap(), (33, 68));
+        assert_eq!(split_fill(100, 5_000).unwrap(), (50, 50));
+        assert_eq!(split_fill(100, 10_000).unwrap(), (100, 0));
+        assert_eq!(split_fill(100, 0).unwrap(), (0, 100));
+        assert_err(split_fill(100, 10_001), EscrowError::InvalidSplit);
+    }
+
+    #[test]
+    fn destinations_must_hold_mint_a() {
+        set_clock(1_000, 1);
+        let (mint_a, mint_b) = setup_mints(6, 6);
+        let escrow = make_escrow(&Pubkey::new_unique(), 1, &mint_a, &mint_b, 100, 50);
+        let taker = Pubkey::new_unique();
+        let config = default_config();
+        let take_split = |destination_a2: TestAccount, split_bps: u16| {
+            let mut accounts =
+                take_accounts(&taker, &escrow, &config, &mint_a, &mint_b, 1_000).into_vec();
+            accounts.push(fund_ata(&Pubkey::new_unique(), &mint_a, 0).writable());
+            accounts.push(destination_a2.writable());
+
+            run(&ix_data(TAKE_SPLIT, (100u64, u64::MAX, split_bps)), accounts).0
+        };
+
+        assert_error(
+            take_split(fund_ata(&Pubkey::new_unique(), &mint_b, 0), 5_000),
+            EscrowError::InvalidSplit,
+        );
+        assert_error(
+            take_split(fund_ata(&Pubkey::new_unique(), &mint_a, 0), 10_001),
+            EscrowError::InvalidSplit,
+        );
+    }
+
+    #[test]
+    fn split_fill_settles_escrow_like_take() {
+        set_clock(1_000, 1);
+        let (mint_a, mint_b) = setup_mints(6, 6);
+        let escrow = make_escrow(&Pubkey::new_unique(), 1, &mint_a, &mint_b, 100, 50);
+        let taker = Pubkey::new_unique();
+        let config = default_config();
+        let mut accounts =
+            take_accounts(&taker, &escrow, &config, &mint_a, &mint_b, 1_000).into_vec();
+        accounts.push(fund_ata(&Pubkey::new_unique(), &mint_a, 0).writable());
+        accounts.push(fund_ata(&Pubkey::new_unique(), &mint_a, 0).writable());
+        let data = ix_data(TAKE_SPLIT, (40u64, 20u64, 2_500u16));
+        let (mut take_split, _) = load_accounts::<TakeSplit>(accounts, &data).unwrap();
+
+        // 宿主机上无法执行转账 CPI：这里只覆盖 handler 在转账之前的计算，以及转账之后的结算
+        assert_eq!(split_fill(40, 2_500).unwrap(), (10, 30));
+        let take = &mut take_split.take;
+        assert_eq!(take.quote_fill(40, 20).unwrap(), 20);
+        take.escrow.apply_fill(40, 20, false).unwrap();
+        assert_eq!((take.escrow.deposit, take.escrow.receive), (60, 30));
+    }
+}
@@ -0,0 +1,81 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    token::spl_token::native_mint,
+    token_interface::{close_account, sync_native, CloseAccount, SyncNative},
+};
+
+use crate::state::transfer_lamports_rent_safe;
+use crate::errors::EscrowError;
+use crate::instructions::take::Take;
+
+impl<'info> Take<'info> {
+    /// 将 taker 的 lamports 包装为 wSOL，存入 taker_ata_b
+    fn wrap_sol(&mut self, amount: u64) -> Result<()> {
+        transfer_lamports_rent_safe(
+            &self.system_program,
+            self.taker.to_account_info(),
+            self.taker_ata_b.to_account_info(),
+            amount,
+        )?;
+
+        // 同步 wSOL 账户的 token 余额与 lamports
+        sync_native(CpiContext::new(
+            self.token_program.to_account_info(),
+            SyncNative {
+                account: self.taker_ata_b.to_account_info(),
+            },
+        ))?;
+
+        Ok(())
+    }
+
+    /// 关闭临时 wSOL 账户，剩余 lamports 返还给 taker
+    fn close_wsol(&mut self) -> Result<()> {
+        close_account(CpiContext::new(
+            self.token_program.to_account_info(),
+            CloseAccount {
+                account: self.taker_ata_b.to_account_info(),
+                destination: self.taker.to_account_info(),
+                authority: self.taker.to_account_info(),
+            },
+        ))?;
+
+        Ok(())
+    }
+}
+
+/// TakeWithSol 指令入口
+///
+/// 当 mint_b 为 native mint（wSOL）时，taker 直接用 SOL 支付：
+/// 1. 将所需 lamports 包装进 taker 的 wSOL ATA
+/// 2. 按普通 take 流程完成 Token B 交割
+/// 3. 关闭 wSOL ATA，lamports 返还给 taker
+///
+/// 参数说明见 Take::quote_fill
+pub fn handler<'info>(
+    ctx: Context<'_, '_, 'info, 'info, Take<'info>>,
+    fill_amount: u64,
+    max_receive: u64,
+) -> Result<()> {
+    require_keys_eq!(
+        ctx.accounts.mint_b.key(),
+        native_mint::ID,
+        EscrowError::NotNativeMint
+    );
+
+    ctx.accounts.ensure_atas(false)?;
+
+    let amount_b = ctx.accounts.quote_fill(fill_amount, max_receive)?;
+
+    // 1️⃣ SOL -> wSOL
+    ctx.accounts.wrap_sol(amount_b)?;
+
+    // 2️⃣ 与 take 相同的交割流程
+    ctx.accounts
+        .execute_fill(fill_amount, amount_b, &[], ctx.remaining_accounts)?;
+
+    // 3️⃣ 关闭临时 wSOL 账户
+    ctx.accounts.close_wsol()?;
+
+    Ok(())
+}
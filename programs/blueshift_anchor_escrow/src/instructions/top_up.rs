@@ -0,0 +1,105 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{
+    Mint,
+    TokenAccount,
+    TokenInterface,
+    transfer_checked,
+    TransferChecked,
+};
+
+use crate::state::Escrow;
+use crate::errors::EscrowError;
+
+/// TopUp 指令：
+///
+/// maker 向已有 escrow 的 Vault 追加 Token A，扩大报价规模
+/// （receive 不变，如需调整价格请另行调用 update）
+#[derive(Accounts)]
+pub struct TopUp<'info> {
+    /// Maker：只有创建者可以追加存入
+    #[account(mut)]
+    pub maker: Signer<'info>,
+
+    /// Escrow PDA：存储交易条款
+    #[account(
+        mut,
+        seeds = [b"escrow", escrow.creator.as_ref(), escrow.seed.to_le_bytes().as_ref()],
+        bump = escrow.bump,
+        has_one = maker @ EscrowError::InvalidMaker,
+        has_one = mint_a @ EscrowError::InvalidMintA,
+        constraint = !escrow.is_basket() @ EscrowError::BasketMismatch,
+        constraint = escrow.is_active() @ EscrowError::EscrowNotActive,
+    )]
+    pub escrow: Account<'info, Escrow>,
+
+    /// Token A 的 mint
+    #[account(
+        mint::token_program = token_program
+    )]
+    pub mint_a: InterfaceAccount<'info, Mint>,
+
+    /// Maker 的 Token A ATA（追加存入的来源）
+    #[account(
+        mut,
+        associated_token::mint = mint_a,
+        associated_token::authority = maker,
+        associated_token::token_program = token_program
+    )]
+    pub maker_ata_a: InterfaceAccount<'info, TokenAccount>,
+
+    /// Vault：escrow PDA 持有的 Token A
+    #[account(
+        mut,
+        associated_token::mint = mint_a,
+        associated_token::authority = escrow,
+        associated_token::token_program = token_program
+    )]
+    pub vault: InterfaceAccount<'info, TokenAccount>,
+
+    /// Token Program（SPL Token 或 Token-2022）
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+impl<'info> TopUp<'info> {
+    /// 将 maker 追加的 Token A 存入 Vault，并累加 escrow.deposit
+    pub fn deposit_tokens(&mut self, additional: u64) -> Result<()> {
+        transfer_checked(
+            CpiContext::new(
+                self.token_program.to_account_info(),
+                TransferChecked {
+                    from: self.maker_ata_a.to_account_info(),
+                    mint: self.mint_a.to_account_info(),
+                    to: self.vault.to_account_info(),
+                    authority: self.maker.to_account_info(),
+                },
+            ),
+            additional,
+            self.mint_a.decimals,
+        )?;
+
+        self.escrow.deposit = self
+            .escrow
+            .deposit
+            .checked_add(additional)
+            .ok_or(error!(EscrowError::MathOverflow))?;
+
+        Ok(())
+    }
+}
+
+/// TopUp 指令入口
+///
+/// 参数说明：
+/// - additional: 追加存入的 Token A 数量
+pub fn handler(ctx: Context<TopUp>, additional: u64) -> Result<()> {
+    require_gt!(additional, 0, EscrowError::InvalidAmount);
+
+    require!(
+        ctx.accounts.maker_ata_a.amount >= additional,
+        EscrowError::InsufficientFunds
+    );
+
+    ctx.accounts.deposit_tokens(additional)?;
+
+    Ok(())
+}
@@ -0,0 +1,216 @@
+use anchor_lang::prelude::*;
+
+use crate::instructions::make::load_config;
+use crate::state::{increment, Escrow, MakerStats};
+use crate::errors::EscrowError;
+
+/// TransferOwnership 指令：
+///
+/// maker 将未成交的报价（及其 refund / 收款权利）转让给 new_maker。
+///
+/// 注意：
+/// - new_maker 必须共同签名，不能把挂单（及其计数）强加给他人
+/// - escrow PDA 地址不变，seeds 仍使用创建时的 maker（escrow.creator）
+/// - 之后的 take / refund / update 等均以 escrow.maker（新 maker）为准
+/// - delegated escrow 的授权绑定在原 maker 的 Token A 账户上，转让后无法再被成交，
+///   新 maker 只能通过 close_escrow 关闭
+/// - 未关闭的 escrow 计数随之从 maker 的 MakerStats 转到 new_maker 的 MakerStats
+///   （按需创建，由当前 maker 支付租金），之后的关闭指令扣减的是 new_maker 的计数；
+///   与 make 相同，new_maker 的计数不能超过 config.max_open_per_maker
+#[derive(Accounts)]
+pub struct TransferOwnership<'info> {
+    /// 当前 maker
+    #[account(mut)]
+    pub maker: Signer<'info>,
+
+    /// 新的 maker（签名表示接受转让）
+    pub new_maker: Signer<'info>,
+
+    /// Escrow PDA
+    #[account(
+        mut,
+        seeds = [b"escrow", escrow.creator.as_ref(), escrow.seed.to_le_bytes().as_ref()],
+        bump = escrow.bump,
+        has_one = maker @ EscrowError::InvalidMaker,
+        constraint = escrow.is_active() @ EscrowError::EscrowNotActive,
+    )]
+    pub escrow: Account<'info, Escrow>,
+
+    /// 当前 maker 的统计 PDA
+    #[account(
+        mut,
+        seeds = [b"stats", maker.key().as_ref()],
+        bump = maker_stats.bump,
+    )]
+    pub maker_stats: Box<Account<'info, MakerStats>>,
+
+    /// new_maker 的统计 PDA（不存在时创建）
+    #[account(
+        init_if_needed,
+        payer = maker,
+        space = MakerStats::INIT_SPACE + MakerStats::DISCRIMINATOR.len(),
+        seeds = [b"stats", new_maker.key().as_ref()],
+        bump,
+    )]
+    pub new_maker_stats: Box<Account<'info, MakerStats>>,
+
+    /// CHECK: 全局 Config PDA（与 make 相同，未初始化时为空账户），仅读取挂单数上限
+    #[account(
+        seeds = [b"config"],
+        bump,
+    )]
+    pub config: UncheckedAccount<'info>,
+
+    /// System Program（创建 new_maker 的统计 PDA）
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> TransferOwnership<'info> {
+    /// 将一个未关闭的 escrow 计数从 maker 转到 new_maker（首次创建时写入 maker 与 bump）
+    ///
+    /// 接收方已达到 config.max_open_per_maker 时拒绝（TooManyOpenEscrows）
+    fn move_open_count(&mut self, bump: u8) -> Result<()> {
+        if let Some(config) = load_config(&self.config)? {
+            require!(
+                config.within_open_limit(self.new_maker_stats.open_escrows),
+                EscrowError::TooManyOpenEscrows
+            );
+        }
+
+        self.maker_stats.record_closed();
+
+        let stats = &mut self.new_maker_stats;
+        if stats.maker == Pubkey::default() {
+            stats.maker = self.new_maker.key();
+            stats.bump = bump;
+        }
+
+        increment(&mut stats.open_escrows)
+    }
+}
+
+/// TransferOwnership 指令入口（新 maker 为 new_maker 账户）
+pub fn handler(ctx: Context<TransferOwnership>) -> Result<()> {
+    let new_maker = ctx.accounts.new_maker.key();
+    require_keys_neq!(new_maker, ctx.accounts.maker.key(), EscrowError::InvalidMaker);
+
+    // 未单独设置收款方时 Token B 改由新 maker 接收，预先创建的 ATA 不再适用
+    if ctx.accounts.escrow.proceeds_recipient == Pubkey::default() {
+        ctx.accounts.escrow.maker_ata_b_ready = false;
+    }
+    ctx.accounts.escrow.maker = new_maker;
+
+    ctx.accounts.move_open_count(ctx.bumps.new_maker_stats)?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::Config;
+    use crate::test_utils::*;
+
+    const TRANSFER_OWNERSHIP: u8 = 17;
+
+    /// new_maker 已有 new_open 个未关闭 escrow 时的 TransferOwnership 账户（按结构体字段顺序）
+    fn transfer_accounts(
+        escrow: &Escrow,
+        signer: Pubkey,
+        new_maker: TestAccount,
+        new_open: u64,
+        config: TestAccount,
+    ) -> Vec<TestAccount> {
+        let new_maker_stats = maker_stats_account(&new_maker.key, new_open).writable();
+
+        vec![
+            TestAccount::wallet(signer, 1_000_000_000).signer(),
+            new_maker,
+            escrow_account(escrow).writable(),
+            maker_stats_account(&signer, 1).writable(),
+            new_maker_stats,
+            config,
+            TestAccount::program(anchor_lang::system_program::ID),
+        ]
+    }
+
+    fn transfer(
+        escrow: &Escrow,
+        signer: Pubkey,
+        new_maker: Pubkey,
+    ) -> (ProgramResult, Vec<AccountInfo<'static>>) {
+        let new_maker = TestAccount::wallet(new_maker, 0).signer();
+
+        run(
+            &ix_data(TRANSFER_OWNERSHIP, ()),
+            transfer_accounts(escrow, signer, new_maker, 0, no_config()),
+        )
+    }
+    fn escrow() -> Escrow {
+        let (mint_a, mint_b) = setup_mints(6, 6);
+
+        make_escrow(&Pubkey::new_unique(), 4, &mint_a, &mint_b, 100, 50)
+    }
+
+    #[test]
+    fn moves_escrow_and_open_count_to_new_maker() {
+        set_clock(1_000, 1);
+        let escrow = escrow();
+        let new_maker = Pubkey::new_unique();
+
+        let (result, infos) = transfer(&escrow, escrow.maker, new_maker);
+
+        assert_eq!(result, Ok(()));
+        let stored = Escrow::try_deserialize(&mut &infos[2].data.borrow()[..]).unwrap();
+        assert_eq!(stored.maker, new_maker);
+        // PDA 仍由 creator 派生，地址不变
+        assert_eq!(stored.creator, escrow.maker);
+        assert_eq!(infos[2].key, &escrow_account(&escrow).key);
+        let old_stats = MakerStats::try_deserialize(&mut &infos[3].data.borrow()[..]).unwrap();
+        let new_stats = MakerStats::try_deserialize(&mut &infos[4].data.borrow()[..]).unwrap();
+        assert_eq!((old_stats.open_escrows, new_stats.open_escrows), (0, 1));
+    }
+
+    #[test]
+    fn only_maker_can_transfer() {
+        set_clock(1_000, 1);
+        let escrow = escrow();
+
+        let (result, _) = transfer(&escrow, Pubkey::new_unique(), Pubkey::new_unique());
+
+        assert_error(result, EscrowError::InvalidMaker);
+    }
+
+    #[test]
+    fn new_maker_must_sign() {
+        set_clock(1_000, 1);
+        let escrow = escrow();
+        let new_maker = TestAccount::wallet(Pubkey::new_unique(), 0);
+        let accounts = transfer_accounts(&escrow, escrow.maker, new_maker, 0, no_config());
+
+        let (result, _) = run(&ix_data(TRANSFER_OWNERSHIP, ()), accounts);
+
+        assert_error(result, ErrorCode::AccountNotSigner);
+    }
+
+    #[test]
+    fn receiving_maker_is_held_to_open_limit() {
+        set_clock(1_000, 1);
+        let escrow = escrow();
+        let config = config_account(&Config {
+            max_open_per_maker: 3,
+            ..default_config()
+        });
+        let transfer_to = |new_open: u64| {
+            let new_maker = TestAccount::wallet(Pubkey::new_unique(), 0).signer();
+            let accounts =
+                transfer_accounts(&escrow, escrow.maker, new_maker, new_open, config.clone());
+
+            run(&ix_data(TRANSFER_OWNERSHIP, ()), accounts).0
+        };
+
+        // 接收方已有 3 个未关闭的 escrow，再转入一个会超过上限
+        assert_error(transfer_to(3), EscrowError::TooManyOpenEscrows);
+        assert_eq!(transfer_to(2), Ok(()));
+    }
+}
@@ -0,0 +1,91 @@
+use anchor_lang::prelude::*;
+
+use crate::state::Escrow;
+use crate::errors::EscrowError;
+
+/// Update 指令：
+///
+/// maker 修改未成交部分的报价（receive），无需 refund 后重新 make
+/// （荷兰拍的价格由 start_receive / end_receive 决定，不受 receive 影响）
+#[derive(Accounts)]
+pub struct Update<'info> {
+    /// Maker：只有创建者可以修改报价
+    pub maker: Signer<'info>,
+
+    /// Escrow PDA：存储交易条款
+    #[account(
+        mut,
+        seeds = [b"escrow", escrow.creator.as_ref(), escrow.seed.to_le_bytes().as_ref()],
+        bump = escrow.bump,
+        has_one = maker @ EscrowError::InvalidMaker,
+        constraint = escrow.is_active() @ EscrowError::EscrowNotActive,
+    )]
+    pub escrow: Account<'info, Escrow>,
+}
+
+/// Update 指令入口
+///
+/// 参数说明：
+/// - new_receive: maker 希望收到的新 Token B 数量（针对剩余存入）
+pub fn handler(ctx: Context<Update>, new_receive: u64) -> Result<()> {
+    require_gt!(new_receive, 0, EscrowError::InvalidAmount);
+
+    ctx.accounts.escrow.receive = new_receive;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::*;
+
+    const UPDATE: u8 = 4;
+
+    fn update(escrow: &Escrow, signer: Pubkey, new_receive: u64) -> (ProgramResult, Escrow) {
+        let (result, infos) = run(
+            &ix_data(UPDATE, new_receive),
+            vec![
+                TestAccount::wallet(signer, 0).signer(),
+                escrow_account(escrow).writable(),
+            ],
+        );
+        let stored = Escrow::try_deserialize(&mut &infos[1].data.borrow()[..]).unwrap();
+
+        (result, stored)
+    }
+
+    fn escrow() -> Escrow {
+        let (mint_a, mint_b) = setup_mints(6, 6);
+
+        make_escrow(&Pubkey::new_unique(), 1, &mint_a, &mint_b, 100, 50)
+    }
+
+    #[test]
+    fn maker_updates_receive() {
+        let escrow = escrow();
+
+        let (result, stored) = update(&escrow, escrow.maker, 80);
+
+        assert_eq!(result, Ok(()));
+        assert_eq!(stored.receive, 80);
+        assert_eq!(stored.deposit, 100);
+    }
+
+    #[test]
+    fn only_maker_can_update() {
+        let escrow = escrow();
+
+        let (result, stored) = update(&escrow, Pubkey::new_unique(), 80);
+
+        assert_error(result, EscrowError::InvalidMaker);
+        assert_eq!(stored.receive, 50);
+    }
+
+    #[test]
+    fn receive_must_be_positive() {
+        let escrow = escrow();
+
+        assert_error(update(&escrow, escrow.maker, 0).0, EscrowError::InvalidAmount);
+    }
+}
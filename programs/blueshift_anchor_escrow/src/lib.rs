@@ -1,11 +1,18 @@
 use anchor_lang::prelude::*;
 
+#[macro_use]
+mod profile;
 mod state;
 mod errors;
+mod events;
 mod instructions;
+#[cfg(test)]
+mod test_utils;
 
 use instructions::*;
 
+pub use state::{auto_seed, escrow_pda, MAKER_OFFSET};
+
     declare_id!("22222222222222222222222222222222222222222222");
 
 #[program]
@@ -14,24 +21,328 @@ pub mod blueshift_anchor_escrow {
 
     /// discriminator = 0
     #[instruction(discriminator = 0)]
-    pub fn make(
-        ctx: Context<Make>,
-        seed: u64,
-        receive: u64,
-        amount: u64,
-    ) -> Result<()> {
-        instructions::make::handler(ctx, seed, receive, amount)
+    pub fn make(ctx: Context<Make>, args: MakeArgs) -> Result<MadeEscrow> {
+        instructions::make::handler(ctx, args)
     }
 
     /// discriminator = 1
     #[instruction(discriminator = 1)]
-    pub fn take(ctx: Context<Take>) -> Result<()> {
-        instructions::take::handler(ctx)
+    pub fn take<'info>(
+        ctx: Context<'_, '_, 'info, 'info, Take<'info>>,
+        fill_amount: u64,
+        max_receive: u64,
+        require_maker_ata_exists: bool,
+        valid_until: i64,
+        memo: Vec<u8>,
+    ) -> Result<()> {
+        instructions::take::handler(
+            ctx,
+            fill_amount,
+            max_receive,
+            require_maker_ata_exists,
+            valid_until,
+            memo,
+        )
     }
 
     /// discriminator = 2
     #[instruction(discriminator = 2)]
-    pub fn refund(ctx: Context<Refund>) -> Result<()> {
+    pub fn refund<'info>(ctx: Context<'_, '_, 'info, 'info, Refund<'info>>) -> Result<()> {
         instructions::refund::handler(ctx)
     }
+
+    /// discriminator = 3
+    #[instruction(discriminator = 3)]
+    pub fn initialize_config(
+        ctx: Context<InitializeConfig>,
+        args: InitializeConfigArgs,
+    ) -> Result<()> {
+        instructions::initialize_config::handler(ctx, args)
+    }
+
+    /// discriminator = 4
+    #[instruction(discriminator = 4)]
+    pub fn update(ctx: Context<Update>, new_receive: u64) -> Result<()> {
+        instructions::update::handler(ctx, new_receive)
+    }
+
+    /// discriminator = 5
+    #[instruction(discriminator = 5)]
+    pub fn top_up(ctx: Context<TopUp>, additional: u64) -> Result<()> {
+        instructions::top_up::handler(ctx, additional)
+    }
+
+    /// discriminator = 6
+    #[instruction(discriminator = 6)]
+    pub fn take_with_sol<'info>(
+        ctx: Context<'_, '_, 'info, 'info, Take<'info>>,
+        fill_amount: u64,
+        max_receive: u64,
+    ) -> Result<()> {
+        instructions::take_with_sol::handler(ctx, fill_amount, max_receive)
+    }
+
+    /// discriminator = 7
+    #[instruction(discriminator = 7)]
+    pub fn refund_to<'info>(ctx: Context<'_, '_, 'info, 'info, RefundTo<'info>>) -> Result<()> {
+        instructions::refund_to::handler(ctx)
+    }
+
+    /// discriminator = 8
+    #[instruction(discriminator = 8)]
+    pub fn batch_take<'info>(
+        ctx: Context<'_, '_, 'info, 'info, BatchTake<'info>>,
+        escrow_count: u8,
+        max_total_receive: u64,
+    ) -> Result<()> {
+        instructions::batch_take::handler(ctx, escrow_count, max_total_receive)
+    }
+
+    /// discriminator = 9
+    #[instruction(discriminator = 9)]
+    pub fn sweep_stranded(ctx: Context<SweepStranded>, seed: u64) -> Result<()> {
+        instructions::sweep_stranded::handler(ctx, seed)
+    }
+
+    /// discriminator = 10
+    #[instruction(discriminator = 10)]
+    pub fn pause(ctx: Context<SetPaused>) -> Result<()> {
+        instructions::pause::handler(ctx, true)
+    }
+
+    /// discriminator = 11
+    #[instruction(discriminator = 11)]
+    pub fn unpause(ctx: Context<SetPaused>) -> Result<()> {
+        instructions::pause::handler(ctx, false)
+    }
+
+    /// discriminator = 12
+    #[instruction(discriminator = 12)]
+    pub fn quote(ctx: Context<Quote>, fill_amount: u64) -> Result<FillQuote> {
+        instructions::quote::handler(ctx, fill_amount)
+    }
+
+    /// discriminator = 13
+    #[instruction(discriminator = 13)]
+    pub fn close_escrow(ctx: Context<CloseEscrow>) -> Result<()> {
+        instructions::close_escrow::handler(ctx)
+    }
+
+    /// discriminator = 14
+    #[instruction(discriminator = 14)]
+    pub fn make_delegated(ctx: Context<MakeDelegated>, args: MakeArgs) -> Result<()> {
+        instructions::make_delegated::handler(ctx, args)
+    }
+
+    /// discriminator = 15
+    #[instruction(discriminator = 15)]
+    pub fn take_delegated<'info>(
+        ctx: Context<'_, '_, 'info, 'info, TakeDelegated<'info>>,
+        fill_amount: u64,
+        max_receive: u64,
+    ) -> Result<()> {
+        instructions::take_delegated::handler(ctx, fill_amount, max_receive)
+    }
+
+    /// discriminator = 16
+    #[instruction(discriminator = 16)]
+    pub fn cancel_remainder<'info>(
+        ctx: Context<'_, '_, 'info, 'info, Refund<'info>>,
+    ) -> Result<()> {
+        instructions::cancel_remainder::handler(ctx)
+    }
+
+    /// discriminator = 17
+    #[instruction(discriminator = 17)]
+    pub fn transfer_ownership(ctx: Context<TransferOwnership>) -> Result<()> {
+        instructions::transfer_ownership::handler(ctx)
+    }
+
+    /// discriminator = 18
+    #[instruction(discriminator = 18)]
+    pub fn make_basket(ctx: Context<MakeBasket>, args: MakeArgs, amount2: u64) -> Result<()> {
+        instructions::make_basket::handler(ctx, args, amount2)
+    }
+
+    /// discriminator = 19
+    #[instruction(discriminator = 19)]
+    pub fn take_basket(ctx: Context<TakeBasket>, max_receive: u64) -> Result<()> {
+        instructions::take_basket::handler(ctx, max_receive)
+    }
+
+    /// discriminator = 20
+    #[instruction(discriminator = 20)]
+    pub fn refund_basket(ctx: Context<RefundBasket>) -> Result<()> {
+        instructions::refund_basket::handler(ctx)
+    }
+
+    /// discriminator = 21
+    #[instruction(discriminator = 21)]
+    pub fn crank_refund<'info>(
+        ctx: Context<'_, '_, 'info, 'info, CrankRefund<'info>>,
+    ) -> Result<()> {
+        instructions::crank_refund::handler(ctx)
+    }
+
+    /// discriminator = 22
+    #[instruction(discriminator = 22)]
+    pub fn refund_native<'info>(
+        ctx: Context<'_, '_, 'info, 'info, Refund<'info>>,
+    ) -> Result<()> {
+        instructions::refund_native::handler(ctx)
+    }
+
+    /// discriminator = 23
+    #[instruction(discriminator = 23)]
+    pub fn make_checked(ctx: Context<MakeChecked>, args: MakeArgs) -> Result<()> {
+        instructions::make_checked::handler(ctx, args)
+    }
+
+    /// discriminator = 24
+    #[instruction(discriminator = 24)]
+    pub fn get_terms(ctx: Context<GetTerms>) -> Result<EscrowTerms> {
+        instructions::get_terms::handler(ctx)
+    }
+
+    /// discriminator = 25
+    #[instruction(discriminator = 25)]
+    pub fn partial_refund<'info>(
+        ctx: Context<'_, '_, 'info, 'info, PartialRefund<'info>>,
+        amount: u64,
+    ) -> Result<()> {
+        instructions::partial_refund::handler(ctx, amount)
+    }
+
+    /// discriminator = 26
+    #[instruction(discriminator = 26)]
+    pub fn add_pair(ctx: Context<AddPair>) -> Result<()> {
+        instructions::add_pair::handler(ctx)
+    }
+
+    /// discriminator = 27
+    #[instruction(discriminator = 27)]
+    pub fn remove_pair(ctx: Context<RemovePair>) -> Result<()> {
+        instructions::remove_pair::handler(ctx)
+    }
+
+    /// discriminator = 28
+    #[instruction(discriminator = 28)]
+    pub fn set_recipient(ctx: Context<SetRecipient>, new_recipient: Pubkey) -> Result<()> {
+        instructions::set_recipient::handler(ctx, new_recipient)
+    }
+
+    /// discriminator = 29
+    #[instruction(discriminator = 29)]
+    pub fn make_idempotent(ctx: Context<MakeChecked>, args: MakeArgs) -> Result<MadeEscrow> {
+        instructions::make_idempotent::handler(ctx, args)
+    }
+
+    /// discriminator = 30
+    #[instruction(discriminator = 30)]
+    pub fn commit_take(ctx: Context<CommitTake>, commitment: [u8; 32]) -> Result<()> {
+        instructions::commit_take::handler(ctx, commitment)
+    }
+
+    /// discriminator = 31
+    #[instruction(discriminator = 31)]
+    pub fn reveal_take<'info>(
+        ctx: Context<'_, '_, 'info, 'info, RevealTake<'info>>,
+        nonce: [u8; 32],
+        fill_amount: u64,
+        max_receive: u64,
+    ) -> Result<()> {
+        instructions::reveal_take::handler(ctx, nonce, fill_amount, max_receive)
+    }
+
+    /// discriminator = 32
+    #[instruction(discriminator = 32)]
+    pub fn take_split<'info>(
+        ctx: Context<'_, '_, 'info, 'info, TakeSplit<'info>>,
+        fill_amount: u64,
+        max_receive: u64,
+        split_bps: u16,
+    ) -> Result<()> {
+        instructions::take_split::handler(ctx, fill_amount, max_receive, split_bps)
+    }
+
+    /// discriminator = 33
+    #[instruction(discriminator = 33)]
+    pub fn make_on_behalf(ctx: Context<MakeOnBehalf>, args: MakeArgs) -> Result<()> {
+        instructions::make_on_behalf::handler(ctx, args)
+    }
+
+    /// discriminator = 34
+    #[instruction(discriminator = 34)]
+    pub fn take_lamports<'info>(
+        ctx: Context<'_, '_, 'info, 'info, TakeLamports<'info>>,
+        fill_amount: u64,
+        max_receive: u64,
+    ) -> Result<()> {
+        instructions::take_lamports::handler(ctx, fill_amount, max_receive)
+    }
+
+    /// discriminator = 35
+    #[instruction(discriminator = 35)]
+    pub fn make_resume(ctx: Context<MakeChecked>, args: MakeArgs) -> Result<()> {
+        instructions::make_resume::handler(ctx, args)
+    }
+
+    /// discriminator = 36
+    #[instruction(discriminator = 36)]
+    pub fn can_take(ctx: Context<CanTake>, taker: Pubkey) -> Result<TakeStatus> {
+        instructions::can_take::handler(ctx, taker)
+    }
+
+    /// discriminator = 37
+    #[instruction(discriminator = 37)]
+    pub fn make_from_account(ctx: Context<MakeFromAccount>, args: MakeArgs) -> Result<()> {
+        instructions::make_from_account::handler(ctx, args)
+    }
+
+    /// discriminator = 38
+    #[instruction(discriminator = 38)]
+    pub fn refund_all<'info>(
+        ctx: Context<'_, '_, 'info, 'info, RefundAll<'info>>,
+    ) -> Result<()> {
+        instructions::refund_all::handler(ctx)
+    }
+
+    /// discriminator = 39
+    #[instruction(discriminator = 39)]
+    pub fn make_auto_seed(ctx: Context<Make>, args: MakeArgs) -> Result<u64> {
+        instructions::make_auto_seed::handler(ctx, args)
+    }
+
+    /// discriminator = 40
+    #[instruction(discriminator = 40)]
+    pub fn reserve(ctx: Context<Reserve>, amount: u64, expires_at: i64) -> Result<()> {
+        instructions::reserve::handler(ctx, amount, expires_at)
+    }
+
+    /// discriminator = 41
+    #[instruction(discriminator = 41)]
+    pub fn claim<'info>(
+        ctx: Context<'_, '_, 'info, 'info, Claim<'info>>,
+        max_receive: u64,
+    ) -> Result<()> {
+        instructions::claim::handler(ctx, max_receive)
+    }
+
+    /// discriminator = 42
+    #[instruction(discriminator = 42)]
+    pub fn release_reservation(ctx: Context<ReleaseReservation>) -> Result<()> {
+        instructions::release_reservation::handler(ctx)
+    }
+
+    /// discriminator = 43
+    #[instruction(discriminator = 43)]
+    pub fn migrate_escrow(ctx: Context<MigrateEscrow>) -> Result<()> {
+        instructions::migrate_escrow::handler(ctx)
+    }
+
+    /// discriminator = 44
+    #[instruction(discriminator = 44)]
+    pub fn cancel_commit(ctx: Context<CancelCommit>) -> Result<()> {
+        instructions::cancel_commit::handler(ctx)
+    }
 }
@@ -0,0 +1,55 @@
+/// 统计一次 CPI 消耗的计算单元（CU）
+///
+/// 启用 `cu-profile` feature 时，在表达式前后读取剩余 CU，并输出日志：
+/// `cu_profile: <label> consumed <n> CU`；
+/// 未启用时展开为表达式本身，不产生任何额外开销
+macro_rules! cu_profile {
+    ($label:expr, $cpi:expr) => {{
+        #[cfg(feature = "cu-profile")]
+        let cu_before = solana_program::compute_units::sol_remaining_compute_units();
+
+        let result = $cpi;
+
+        #[cfg(feature = "cu-profile")]
+        msg!(
+            "cu_profile: {} consumed {} CU",
+            $label,
+            cu_before.saturating_sub(solana_program::compute_units::sol_remaining_compute_units())
+        );
+
+        result
+    }};
+}
+
+#[cfg(test)]
+mod tests {
+    use anchor_lang::prelude::*;
+
+    #[cfg(feature = "cu-profile")]
+    use crate::test_utils::*;
+
+    #[test]
+    fn returns_the_wrapped_result() {
+        let result: Result<u64> = cu_profile!("noop", Ok(7));
+
+        assert_eq!(result.unwrap(), 7);
+    }
+
+    #[cfg(feature = "cu-profile")]
+    #[test]
+    fn logs_consumed_compute_units() {
+        set_clock(1_000, 1);
+
+        let Some(logs) = program_logs("profile::tests::logs_consumed_compute_units", || {
+            let result: Result<()> = cu_profile!("take: transfer_to_maker", {
+                consume_compute_units(1_234);
+                Ok(())
+            });
+            assert!(result.is_ok());
+        }) else {
+            return;
+        };
+
+        assert!(logs.contains("cu_profile: take: transfer_to_maker consumed 1234 CU"));
+    }
+}
@@ -1,12 +1,1079 @@
 use anchor_lang::prelude::*;
+use anchor_lang::system_program::{transfer, Transfer};
+use solana_sha256_hasher::hashv;
 
-#[derive(InitSpace)]
+use crate::errors::EscrowError;
+
+/// Escrow 生命周期状态
+///
+/// Active -> PartiallyFilled -> Filled
+///        \-> Cancelled（refund）
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace)]
+pub enum EscrowStatus {
+    /// 已创建，尚未成交
+    Active,
+    /// 已部分成交，仍可继续成交
+    PartiallyFilled,
+    /// 已全部成交
+    Filled,
+    /// 已被 maker 撤回
+    Cancelled,
+}
+
+/// maker 字段在 Escrow 账户数据中的偏移（紧跟 1 字节 discriminator）
+///
+/// indexer 可用 getProgramAccounts + memcmp { offset: MAKER_OFFSET, bytes: maker } 按 maker 过滤
+pub const MAKER_OFFSET: usize = Escrow::DISCRIMINATOR.len();
+
+/// 字段顺序即账户布局：maker 固定放在最前，保证 MAKER_OFFSET 稳定
+#[derive(InitSpace, PartialEq)]
 #[account(discriminator = 1)]
 pub struct Escrow {
-    pub seed: u64,
     pub maker: Pubkey,
+    pub seed: u64,
     pub mint_a: Pubkey,
     pub mint_b: Pubkey,
     pub receive: u64,
+    pub deposit: u64,
+    pub expiry: i64,
+    pub min_hold_until: i64,
+    pub allowed_taker: Pubkey,
+    pub status: EscrowStatus,
+    pub created_at: i64,
+    pub is_auction: bool,
+    pub start_receive: u64,
+    pub end_receive: u64,
+    pub auction_start: i64,
+    pub auction_end: i64,
+    pub min_fill: u64,
+    pub max_fill: u64,
+    pub mint_b_alt: Pubkey,
+    pub receive_alt: u64,
+    pub rent_collector: Pubkey,
+    pub is_delegated: bool,
+    pub mint_a_decimals: u8,
+    pub mint_b_decimals: u8,
+    pub memo: [u8; 32],
+    pub creator: Pubkey,
+    pub mint_a2: Pubkey,
+    pub deposit2: u64,
+    pub in_progress: bool,
+    pub fee_bps_override: u16,
+    pub vault_rent_payer: Pubkey,
+    pub proceeds_recipient: Pubkey,
+    pub round_up_receive: bool,
+    pub is_ratio: bool,
+    pub price_num: u64,
+    pub price_den: u64,
+    pub maker_ata_b_ready: bool,
+    pub reserved: u64,
+    pub gate_mint: Pubkey,
+    pub gate_min_balance: u64,
+    pub version: u8,
+    pub bump: u8,
+}
+
+impl Escrow {
+    /// Escrow PDA 的 seed 前缀
+    pub const SEED_PREFIX: &'static [u8] = b"escrow";
+
+    /// fee_bps_override 取该值时使用全局 config.fee_bps
+    pub const FEE_BPS_USE_GLOBAL: u16 = u16::MAX;
+
+    /// 当前账户布局版本（0 表示引入 version 字段之前创建、尚未 migrate_escrow 的账户）
+    pub const CURRENT_VERSION: u8 = 1;
+
+    /// 构造 escrow PDA 的 signer seeds：["escrow", creator, seed, bump]
+    ///
+    /// PDA 始终由创建时的 maker（creator）推导，transfer_ownership 后地址不变；
+    /// seed_bytes 为 `self.seed.to_le_bytes()`，由调用方持有以保证生命周期
+    pub fn signer_seeds<'a>(&'a self, seed_bytes: &'a [u8; 8]) -> [&'a [u8]; 4] {
+        [
+            Self::SEED_PREFIX,
+            self.creator.as_ref(),
+            seed_bytes,
+            std::slice::from_ref(&self.bump),
+        ]
+    }
+
+    /// 存储的 bump 是否为规范 bump（find_program_address 推导结果）
+    ///
+    /// make 只会写入 ctx.bumps 中的规范 bump，此处防御未来可能写入自定义 bump 的指令。
+    /// 推导开销与 verify_pda 相同，只在 `strict-pda` 构建中执行，默认构建直接返回 true
+    pub fn has_canonical_bump(&self) -> bool {
+        if !cfg!(feature = "strict-pda") {
+            return true;
+        }
+
+        escrow_pda(&self.creator, self.seed, &crate::ID).1 == self.bump
+    }
+
+    /// 按 maker 与 seed 重新推导 escrow PDA，校验存储的 bump 为规范 bump，返回推导出的地址
+    ///
+    /// 需调用 find_program_address，CU 开销较大，仅在启用 `strict-pda` feature 的构建中
+    /// 由 take / refund 调用；默认构建依赖账户约束中的 seeds + bump 校验
+    pub fn verify_pda(&self, maker: &Pubkey, program_id: &Pubkey) -> Result<Pubkey> {
+        let (address, bump) = escrow_pda(maker, self.seed, program_id);
+        require_eq!(bump, self.bump, EscrowError::InvalidBump);
+
+        Ok(address)
+    }
+
+    /// escrow 是否仍可被修改 / 成交（Active 或 PartiallyFilled）
+    pub fn is_active(&self) -> bool {
+        matches!(
+            self.status,
+            EscrowStatus::Active | EscrowStatus::PartiallyFilled
+        )
+    }
+
+    /// 是否设置了持币门槛（gate_mint = Pubkey::default() 表示否）
+    pub fn is_gated(&self) -> bool {
+        self.gate_mint != Pubkey::default()
+    }
+
+    /// 是否为两种 Token A 的组合报价（make_basket 创建，mint_a2 = Pubkey::default() 表示否）
+    pub fn is_basket(&self) -> bool {
+        self.mint_a2 != Pubkey::default()
+    }
+
+    /// 报价是否已过期（expiry = 0 表示永不过期）
+    pub fn is_expired(&self, now: i64) -> bool {
+        self.expiry != 0 && now > self.expiry
+    }
+
+    /// taker 是否有权成交（allowed_taker = Pubkey::default() 表示不限制）
+    pub fn is_taker_allowed(&self, taker: &Pubkey) -> bool {
+        self.allowed_taker == Pubkey::default() || self.allowed_taker == *taker
+    }
+
+    /// 判断 taker 选择的支付 mint 是否为备选 mint（mint_b_alt）
+    ///
+    /// 既不是 mint_b 也不是已设置的 mint_b_alt 时返回 None
+    pub fn is_alt_mint_b(&self, mint: &Pubkey) -> Option<bool> {
+        if *mint == self.mint_b {
+            Some(false)
+        } else if self.mint_b_alt != Pubkey::default() && *mint == self.mint_b_alt {
+            Some(true)
+        } else {
+            None
+        }
+    }
+
+    /// 校验 taker 支付的 Token B mint，返回是否为备选 mint（mint_b_alt）
+    ///
+    /// 在 handler 中而非账户约束里校验：自定义 constraint 失败时 Anchor 只输出错误码，
+    /// 这里在返回 InvalidMintB 之前把期望与实际的 mint 写入日志
+    pub fn check_mint_b(&self, mint: &Pubkey) -> Result<bool> {
+        self.is_alt_mint_b(mint).ok_or_else(|| {
+            msg!(
+                "InvalidMintB: expected {} (alt {}), got {}",
+                self.mint_b,
+                self.mint_b_alt,
+                mint
+            );
+            error!(EscrowError::InvalidMintB)
+        })
+    }
+
+    /// 传入的 Token B mint 精度是否与 make 时记录的一致
+    ///
+    /// 备选 mint（mint_b_alt）未记录精度，由 transfer_checked 校验
+    pub fn mint_b_decimals_match(&self, mint: &Pubkey, decimals: u8) -> bool {
+        *mint != self.mint_b || decimals == self.mint_b_decimals
+    }
+
+    /// 单次成交是否不低于 min_fill（吃掉全部剩余存入时不受限制，保证 escrow 总能被清空）
+    pub fn meets_min_fill(&self, fill_amount: u64) -> bool {
+        fill_amount >= self.min_fill || fill_amount == self.deposit
+    }
+
+    /// 未被 reserve 锁定的剩余存入（deposit - reserved）
+    ///
+    /// 普通成交与撤回只能动用这部分，被锁定的部分只能由持有 Reservation 的 taker 通过 claim 成交
+    pub fn available(&self) -> u64 {
+        self.deposit.saturating_sub(self.reserved)
+    }
+
+    /// 单次成交是否不超过 max_fill（max_fill = 0 表示不限制）
+    pub fn within_max_fill(&self, fill_amount: u64) -> bool {
+        self.max_fill == 0 || fill_amount <= self.max_fill
+    }
+
+    /// 当前剩余存入对应的 Token B 总价
+    ///
+    /// - 普通 escrow：固定为 receive
+    /// - 荷兰拍：在 [auction_start, auction_end] 内从 start_receive
+    ///   线性下降到 end_receive，开始前取 start_receive，结束后取 end_receive
+    pub fn current_receive(&self, now: i64) -> Result<u64> {
+        if !self.is_auction {
+            return Ok(self.receive);
+        }
+        if now <= self.auction_start {
+            return Ok(self.start_receive);
+        }
+        if now >= self.auction_end {
+            return Ok(self.end_receive);
+        }
+
+        let elapsed = checked_elapsed(self.auction_start, now)?;
+        let duration = checked_elapsed(self.auction_start, self.auction_end)?;
+        let spread = self
+            .start_receive
+            .checked_sub(self.end_receive)
+            .ok_or(error!(EscrowError::MathOverflow))?;
+        let drop = mul_div(spread, elapsed, duration)?;
+
+        self.start_receive
+            .checked_sub(drop)
+            .ok_or(error!(EscrowError::MathOverflow))
+    }
+
+    /// 按比例计算成交 fill_amount 个 Token A 需支付的 Token B 数量
+    ///
+    /// 默认向下取整，round_up_receive 为 true 时向上取整（零头由 taker 承担）；
+    /// alt 为 true 时按备选 mint 的固定总价 receive_alt 计算；
+    /// 比例报价（is_ratio）按单价计算：fill_amount * price_num / price_den
+    pub fn receive_for(&self, fill_amount: u64, now: i64, alt: bool) -> Result<u64> {
+        let (num, den) = if alt {
+            (self.receive_alt, self.deposit)
+        } else if self.is_ratio {
+            (self.price_num, self.price_den)
+        } else {
+            (self.current_receive(now)?, self.deposit)
+        };
+
+        if self.round_up_receive {
+            mul_div_ceil(fill_amount, num, den)
+        } else {
+            mul_div(fill_amount, num, den)
+        }
+    }
+
+    /// 扣减成交部分后的剩余条款
+    ///
+    /// - 实际支付的那一侧报价直接扣减 amount_b
+    /// - 另一侧报价与荷兰拍起止价格按剩余存入等比例缩小，保持单价不变
+    /// - 比例报价的 receive 仅用于展示，同样按剩余存入等比例缩小
+    pub fn apply_fill(&mut self, fill_amount: u64, amount_b: u64, alt: bool) -> Result<()> {
+        let remaining = self
+            .deposit
+            .checked_sub(fill_amount)
+            .ok_or(error!(EscrowError::MathOverflow))?;
+
+        if self.is_auction {
+            self.start_receive = mul_div(self.start_receive, remaining, self.deposit)?;
+            self.end_receive = mul_div(self.end_receive, remaining, self.deposit)?;
+        } else if alt || self.is_ratio {
+            self.receive = mul_div(self.receive, remaining, self.deposit)?;
+        } else {
+            self.receive = self
+                .receive
+                .checked_sub(amount_b)
+                .ok_or(error!(EscrowError::MathOverflow))?;
+        }
+
+        if alt {
+            self.receive_alt = self
+                .receive_alt
+                .checked_sub(amount_b)
+                .ok_or(error!(EscrowError::MathOverflow))?;
+        } else {
+            self.receive_alt = mul_div(self.receive_alt, remaining, self.deposit)?;
+        }
+
+        self.deposit = remaining;
+
+        Ok(())
+    }
+
+    /// maker 撤回 amount 个 Token A 后的剩余条款
+    ///
+    /// 所有报价（receive / receive_alt / 荷兰拍起止价格）按剩余存入等比例缩小，保持单价不变
+    pub fn apply_partial_refund(&mut self, amount: u64) -> Result<()> {
+        let remaining = self
+            .deposit
+            .checked_sub(amount)
+            .ok_or(error!(EscrowError::InvalidAmount))?;
+
+        self.receive = mul_div(self.receive, remaining, self.deposit)?;
+        self.receive_alt = mul_div(self.receive_alt, remaining, self.deposit)?;
+        self.start_receive = mul_div(self.start_receive, remaining, self.deposit)?;
+        self.end_receive = mul_div(self.end_receive, remaining, self.deposit)?;
+        self.deposit = remaining;
+
+        Ok(())
+    }
+
+    /// 关闭 vault / escrow 时 lamports 的接收方
+    ///
+    /// rent_collector = Pubkey::default() 时返回 default（通常为 maker）
+    pub fn rent_destination(&self, default: Pubkey) -> Pubkey {
+        if self.rent_collector == Pubkey::default() {
+            default
+        } else {
+            self.rent_collector
+        }
+    }
+
+    /// take 全部成交关闭 vault 时租金的接收方
+    ///
+    /// vault_rent_payer = Pubkey::default() 时与 escrow 相同（rent_destination），
+    /// 因此 transfer_ownership 后默认跟随新 maker
+    pub fn vault_rent_destination(&self, default: Pubkey) -> Pubkey {
+        if self.vault_rent_payer == Pubkey::default() {
+            self.rent_destination(default)
+        } else {
+            self.vault_rent_payer
+        }
+    }
+
+    /// 接收 Token B 的钱包（proceeds_recipient = Pubkey::default() 时为当前 maker）
+    pub fn proceeds_destination(&self) -> Pubkey {
+        if self.proceeds_recipient == Pubkey::default() {
+            self.maker
+        } else {
+            self.proceeds_recipient
+        }
+    }
+
+    /// 是否仍处于承诺持有期内（min_hold_until = 0 表示不限制）
+    pub fn is_held(&self, now: i64) -> bool {
+        now < self.min_hold_until
+    }
+}
+
+/// 推导 escrow PDA：["escrow", maker, seed]，返回 (地址, 规范 bump)
+///
+/// maker 为创建时的 maker（transfer_ownership 后即 escrow.creator）
+pub fn escrow_pda(maker: &Pubkey, seed: u64, program_id: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[Escrow::SEED_PREFIX, maker.as_ref(), &seed.to_le_bytes()],
+        program_id,
+    )
+}
+
+/// 推导 maker 第 nonce 个 escrow 的自动 seed：sha256("auto_seed", maker, nonce) 的前 8 字节（小端）
+///
+/// nonce 取 make 前的 MakerStats.total_created（新 maker 为 0），每次创建后递增，
+/// 因此同一 maker 的自动 seed 不会重复；客户端读取 MakerStats 即可预先算出 escrow 地址
+pub fn auto_seed(maker: &Pubkey, nonce: u64) -> u64 {
+    let hash = hashv(&[b"auto_seed", maker.as_ref(), &nonce.to_le_bytes()]).to_bytes();
+    let mut seed = [0u8; 8];
+    seed.copy_from_slice(&hash[..8]);
+
+    u64::from_le_bytes(seed)
+}
+
+/// amount * num / den（u128 中间值，向下取整），除零或结果超出 u64 时返回 MathOverflow
+pub fn mul_div(amount: u64, num: u64, den: u64) -> Result<u64> {
+    (amount as u128)
+        .checked_mul(num as u128)
+        .and_then(|product| product.checked_div(den as u128))
+        .and_then(|quotient| u64::try_from(quotient).ok())
+        .ok_or(error!(EscrowError::MathOverflow))
+}
+
+/// amount * num / den（u128 中间值，向上取整），除零或结果超出 u64 时返回 MathOverflow
+pub fn mul_div_ceil(amount: u64, num: u64, den: u64) -> Result<u64> {
+    (amount as u128)
+        .checked_mul(num as u128)
+        .and_then(|product| product.checked_add((den as u128).checked_sub(1)?))
+        .and_then(|product| product.checked_div(den as u128))
+        .and_then(|quotient| u64::try_from(quotient).ok())
+        .ok_or(error!(EscrowError::MathOverflow))
+}
+
+/// 通过 System Program 转出 lamports，并保证 from 在转出后仍满足免租
+///
+/// 转出后余额为 0（账户被清空）或不低于 from 当前数据长度的免租下限时才允许，
+/// 否则返回 WouldBreakRentExemption，避免账户在交易中途变为需付租状态；
+/// 所有手动搬运 lamports 的路径（撤单费、租金转交、lamports 支付）都应使用本函数
+pub fn transfer_lamports_rent_safe<'info>(
+    system_program: &Program<'info, System>,
+    from: AccountInfo<'info>,
+    to: AccountInfo<'info>,
+    amount: u64,
+) -> Result<()> {
+    let remaining = from
+        .lamports()
+        .checked_sub(amount)
+        .ok_or(error!(EscrowError::InsufficientFunds))?;
+    require!(
+        remaining == 0 || Rent::get()?.is_exempt(remaining, from.data_len()),
+        EscrowError::WouldBreakRentExemption
+    );
+
+    transfer(
+        CpiContext::new(system_program.to_account_info(), Transfer { from, to }),
+        amount,
+    )
+}
+
+/// 计算 to - from（秒），结果为负或溢出时返回 MathOverflow
+fn checked_elapsed(from: i64, to: i64) -> Result<u64> {
+    to.checked_sub(from)
+        .and_then(|elapsed| u64::try_from(elapsed).ok())
+        .ok_or(error!(EscrowError::MathOverflow))
+}
+
+/// 全局配置 PDA（seeds = ["config"]）
+///
+/// 保存协议费率与 treasury，take 时按费率收取 Token B 手续费；
+/// paused 为 true 时暂停所有 take；refund 时 maker 需向 treasury 支付 cancel_fee_lamports；
+/// make 时存入与报价不得低于 min_deposit / min_receive（0 表示不限制）；
+/// take 传入推荐人账户时，协议费中 referral_bps 对应的部分转给推荐人；
+/// restrict_pairs 为 true 时 make 只接受已在 PairRegistry 中登记的交易对；
+/// max_ttl_seconds > 0 时 make 的 expiry 不得晚于 now + max_ttl_seconds
+#[derive(InitSpace)]
+#[account(discriminator = 2)]
+pub struct Config {
+    pub authority: Pubkey,
+    pub fee_bps: u16,
+    pub treasury: Pubkey,
+    pub paused: bool,
+    pub cancel_fee_lamports: u64,
+    pub min_deposit: u64,
+    pub min_receive: u64,
+    pub referral_bps: u16,
+    pub restrict_pairs: bool,
+    pub max_ttl_seconds: i64,
+    pub max_open_per_maker: u64,
+    pub discount_bps_per_day: u16,
+    pub max_discount_bps: u16,
+    pub cranker_bounty_lamports: u64,
+    pub require_take_memo: bool,
+    pub bump: u8,
+}
+
+impl Config {
+    /// 费率上限：10%
+    pub const MAX_FEE_BPS: u16 = 1_000;
+
+    /// take 附言的最大字节数
+    pub const MAX_TAKE_MEMO_LEN: usize = 64;
+
+    /// escrow 适用的费率：优先使用 escrow 自带的 fee_bps_override（不超过 MAX_FEE_BPS）
+    pub fn fee_bps_for(&self, escrow: &Escrow) -> u16 {
+        if escrow.fee_bps_override == Escrow::FEE_BPS_USE_GLOBAL {
+            self.fee_bps
+        } else {
+            escrow.fee_bps_override.min(Self::MAX_FEE_BPS)
+        }
+    }
+
+    /// 新 escrow 的存入 / 报价是否达到最小规模，防止粉尘挂单
+    pub fn meets_min_size(&self, deposit: u64, receive: u64) -> bool {
+        deposit >= self.min_deposit && receive >= self.min_receive
+    }
+
+    /// escrow 挂单 created_at 到 now 期间累积的费率折扣（基点）
+    ///
+    /// 每满一天折扣 discount_bps_per_day，上限 max_discount_bps
+    pub fn discount_bps_for(&self, created_at: i64, now: i64) -> u16 {
+        let days = now.saturating_sub(created_at).max(0) / 86_400;
+        let discount = (days as u64).saturating_mul(self.discount_bps_per_day as u64);
+
+        discount.min(self.max_discount_bps as u64) as u16
+    }
+
+    /// 计算 escrow 成交 amount 个 Token B 对应的协议费（向下取整）
+    ///
+    /// amount 为本次成交的 Token B（receive_for 按 fill_amount / deposit 比例计算），
+    /// 而不是整单 receive，因此部分成交只按成交部分收费。
+    /// 费率扣除挂单时长折扣后最低为 0
+    pub fn fee_for(&self, escrow: &Escrow, amount: u64) -> Result<u64> {
+        let now = Clock::get()?.unix_timestamp;
+        let fee_bps = self
+            .fee_bps_for(escrow)
+            .saturating_sub(self.discount_bps_for(escrow.created_at, now));
+
+        mul_div(amount, fee_bps as u64, 10_000)
+    }
+
+    /// expiry 是否在允许的存活时间内（max_ttl_seconds = 0 表示不限制）
+    ///
+    /// 限制存活时间时不允许永不过期（expiry = 0）的报价
+    pub fn within_max_ttl(&self, expiry: i64, now: i64) -> bool {
+        if self.max_ttl_seconds == 0 {
+            return true;
+        }
+
+        expiry != 0 && now.saturating_add(self.max_ttl_seconds) >= expiry
+    }
+
+    /// 协议费 fee 中分给推荐人的部分（按 referral_bps 计算，不超过 fee 本身）
+    ///
+    /// 推荐奖励从 treasury 的份额中扣除，不影响 maker 实收
+    pub fn referral_fee_for(&self, amount: u64, fee: u64) -> Result<u64> {
+        Ok(mul_div(amount, self.referral_bps as u64, 10_000)?.min(fee))
+    }
+
+    /// 校验 take 附言：长度不超过 MAX_TAKE_MEMO_LEN，require_take_memo 时不能为空
+    ///
+    /// 不接受附言的成交指令传入空附言，因此 require_take_memo 时只能通过 take 成交
+    pub fn check_take_memo(&self, memo: &[u8]) -> Result<()> {
+        require!(
+            memo.len() <= Self::MAX_TAKE_MEMO_LEN,
+            EscrowError::MemoTooLong
+        );
+        require!(
+            !self.require_take_memo || !memo.is_empty(),
+            EscrowError::MemoRequired
+        );
+
+        Ok(())
+    }
+
+    /// 是否限制每个 maker 同时未关闭的 escrow 数量（max_open_per_maker = 0 表示不限制）
+    ///
+    /// 限制时关闭 escrow 的指令必须传入 MakerStats，否则计数只增不减
+    pub fn tracks_open_escrows(&self) -> bool {
+        self.max_open_per_maker > 0
+    }
+
+    /// maker 当前有 open 个未关闭的 escrow 时，是否还能再创建一个
+    pub fn within_open_limit(&self, open: u64) -> bool {
+        !self.tracks_open_escrows() || open < self.max_open_per_maker
+    }
+}
+
+/// maker 统计 PDA（seeds = ["stats", maker]）
+///
+/// make 时按需创建；take 全部成交 / refund 时若传入则累加，供前端计算成交率。
+/// open_escrows 为 maker 当前未关闭的 escrow 数量，用于 config.max_open_per_maker 限制
+#[derive(InitSpace)]
+#[account(discriminator = 3)]
+pub struct MakerStats {
+    pub maker: Pubkey,
+    pub total_created: u64,
+    pub total_taken: u64,
+    pub total_refunded: u64,
+    pub open_escrows: u64,
+    pub bump: u8,
+}
+
+impl MakerStats {
+    /// MakerStats PDA 的 seed 前缀
+    pub const SEED_PREFIX: &'static [u8] = b"stats";
+
+    /// 新建 escrow
+    pub fn record_created(&mut self) -> Result<()> {
+        increment(&mut self.total_created)?;
+        increment(&mut self.open_escrows)
+    }
+
+    /// escrow 全部成交并关闭
+    pub fn record_taken(&mut self) -> Result<()> {
+        increment(&mut self.total_taken)?;
+        self.record_closed();
+
+        Ok(())
+    }
+
+    /// escrow 退款并关闭
+    pub fn record_refunded(&mut self) -> Result<()> {
+        increment(&mut self.total_refunded)?;
+        self.record_closed();
+
+        Ok(())
+    }
+
+    /// escrow 关闭（或转出给其他 maker）
+    ///
+    /// 饱和减法：计数上线前创建的 escrow 关闭时不会下溢导致指令失败
+    pub fn record_closed(&mut self) {
+        self.open_escrows = self.open_escrows.saturating_sub(1);
+    }
+}
+
+/// 交易对白名单 PDA（seeds = ["pair", mint_a, mint_b]）
+///
+/// 由配置管理员通过 add_pair / remove_pair 创建与关闭，账户存在即表示允许该方向的交易对
+#[derive(InitSpace)]
+#[account(discriminator = 4)]
+pub struct PairRegistry {
+    pub mint_a: Pubkey,
+    pub mint_b: Pubkey,
+    pub bump: u8,
+}
+
+impl PairRegistry {
+    /// PairRegistry PDA 的 seed 前缀
+    pub const SEED_PREFIX: &'static [u8] = b"pair";
+}
+
+/// take 承诺 PDA（seeds = ["commit", escrow, taker]）
+///
+/// commit_take 记录 commitment = sha256(taker || nonce) 与提交时的 slot，
+/// reveal_take 校验原像并在至少 MIN_REVEAL_SLOT_GAP 个 slot 之后成交
+#[derive(InitSpace)]
+#[account(discriminator = 5)]
+pub struct TakeCommit {
+    pub escrow: Pubkey,
+    pub taker: Pubkey,
+    pub commitment: [u8; 32],
+    pub slot: u64,
+    pub bump: u8,
+}
+
+impl TakeCommit {
+    /// TakeCommit PDA 的 seed 前缀
+    pub const SEED_PREFIX: &'static [u8] = b"commit";
+
+    /// commit 与 reveal 之间至少间隔的 slot 数
+    pub const MIN_REVEAL_SLOT_GAP: u64 = 2;
+
+    /// 计算 commitment：sha256(taker || escrow || fill_amount || max_receive || nonce)
+    ///
+    /// 数量以 u64 小端序参与哈希；reveal 时成交参数必须与提交时一致
+    pub fn commitment_for(
+        taker: &Pubkey,
+        escrow: &Pubkey,
+        fill_amount: u64,
+        max_receive: u64,
+        nonce: &[u8; 32],
+    ) -> [u8; 32] {
+        hashv(&[
+            taker.as_ref(),
+            escrow.as_ref(),
+            &fill_amount.to_le_bytes(),
+            &max_receive.to_le_bytes(),
+            nonce,
+        ])
+        .to_bytes()
+    }
+
+    /// 当前 slot 是否已满足最小间隔
+    pub fn can_reveal(&self, slot: u64) -> bool {
+        slot >= self.slot.saturating_add(Self::MIN_REVEAL_SLOT_GAP)
+    }
+}
+
+/// 成交预留 PDA（seeds = ["reservation", escrow, taker]）
+///
+/// reserve 锁定 escrow 剩余存入中的 amount 个 Token A（计入 escrow.reserved），
+/// expires_at 之前只有该 taker 能通过 claim 按当时价格成交；
+/// 过期后任何人都可以通过 release_reservation 释放锁定，租金返还给 taker
+#[derive(InitSpace)]
+#[account(discriminator = 6)]
+pub struct Reservation {
+    pub escrow: Pubkey,
+    pub taker: Pubkey,
+    pub amount: u64,
+    pub expires_at: i64,
     pub bump: u8,
-}
\ No newline at end of file
+}
+
+impl Reservation {
+    /// Reservation PDA 的 seed 前缀
+    pub const SEED_PREFIX: &'static [u8] = b"reservation";
+
+    /// 单个预留的最长有效期（秒），防止 taker 长期锁住 maker 的报价
+    pub const MAX_DURATION_SECONDS: i64 = 3600;
+
+    /// 预留是否已过期
+    pub fn is_expired(&self, now: i64) -> bool {
+        now >= self.expires_at
+    }
+}
+
+/// 计数器 +1，溢出时返回 MathOverflow
+pub fn increment(counter: &mut u64) -> Result<()> {
+    *counter = counter
+        .checked_add(1)
+        .ok_or(error!(EscrowError::MathOverflow))?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::*;
+
+    fn fixed(deposit: u64, receive: u64) -> Escrow {
+        let (mint_a, mint_b) = setup_mints(6, 6);
+
+        make_escrow(&Pubkey::new_unique(), 1, &mint_a, &mint_b, deposit, receive)
+    }
+
+    fn auction(deposit: u64, start_receive: u64, end_receive: u64) -> Escrow {
+        Escrow {
+            is_auction: true,
+            start_receive,
+            end_receive,
+            auction_start: 1_000,
+            auction_end: 2_000,
+            ..fixed(deposit, start_receive)
+        }
+    }
+
+    #[test]
+    fn mul_div_uses_wide_intermediate() {
+        assert_eq!(mul_div(u64::MAX, u64::MAX, u64::MAX).unwrap(), u64::MAX);
+        assert_eq!(mul_div(7, 3, 2).unwrap(), 10);
+        assert_eq!(mul_div_ceil(7, 3, 2).unwrap(), 11);
+        assert_eq!(mul_div_ceil(u64::MAX, u64::MAX, u64::MAX).unwrap(), u64::MAX);
+    }
+
+    #[test]
+    fn mul_div_overflow_returns_math_overflow() {
+        assert_err(mul_div(u64::MAX, 2, 1), EscrowError::MathOverflow);
+        assert_err(mul_div(u64::MAX, u64::MAX, u64::MAX - 1), EscrowError::MathOverflow);
+        assert_err(mul_div(1, 1, 0), EscrowError::MathOverflow);
+        assert_err(mul_div_ceil(u64::MAX, 2, 1), EscrowError::MathOverflow);
+        assert_err(mul_div_ceil(1, 1, 0), EscrowError::MathOverflow);
+    }
+
+    #[test]
+    fn receive_for_is_proportional() {
+        let escrow = fixed(1_000, 500);
+
+        assert_eq!(escrow.receive_for(1_000, 0, false).unwrap(), 500);
+        assert_eq!(escrow.receive_for(300, 0, false).unwrap(), 150);
+        assert_eq!(escrow.receive_for(1, 0, false).unwrap(), 0);
+        // 大额条款不会在中间乘积溢出
+        let large = fixed(u64::MAX, u64::MAX);
+        assert_eq!(large.receive_for(u64::MAX / 2, 0, false).unwrap(), u64::MAX / 2);
+    }
+
+    #[test]
+    fn round_up_receive_differs_by_one_unit() {
+        let floor = fixed(100, 50);
+        let ceil = Escrow {
+            round_up_receive: true,
+            ..fixed(100, 50)
+        };
+
+        // 3 * 50 / 100 = 1.5：向下取整偏向 taker，向上取整偏向 maker
+        assert_eq!(floor.receive_for(3, 0, false).unwrap(), 1);
+        assert_eq!(ceil.receive_for(3, 0, false).unwrap(), 2);
+        // 整除时两种策略一致
+        assert_eq!(floor.receive_for(4, 0, false).unwrap(), 2);
+        assert_eq!(ceil.receive_for(4, 0, false).unwrap(), 2);
+        assert_eq!(ceil.receive_for(100, 0, false).unwrap(), 50);
+    }
+
+    #[test]
+    fn verify_pda_rederives_escrow_address() {
+        let mut escrow = fixed(100, 50);
+        let address = escrow_account(&escrow).key;
+
+        assert_eq!(escrow.verify_pda(&escrow.creator, &crate::ID).unwrap(), address);
+        assert_ne!(escrow.verify_pda(&Pubkey::new_unique(), &crate::ID).ok(), Some(address));
+
+        escrow.bump = escrow.bump.wrapping_sub(1);
+        assert_err(escrow.verify_pda(&escrow.creator, &crate::ID), EscrowError::InvalidBump);
+    }
+
+    #[test]
+    fn current_receive_rejects_inverted_auction() {
+        // 起始价低于结束价时差价下溢，返回 MathOverflow 而不是回绕
+        let escrow = auction(100, 50, 80);
+
+        assert_err(escrow.current_receive(1_500), EscrowError::MathOverflow);
+    }
+
+    #[test]
+    fn apply_fill_keeps_unit_price() {
+        let mut escrow = fixed(1_000, 500);
+        escrow.receive_alt = 2_000;
+
+        escrow.apply_fill(400, 200, false).unwrap();
+
+        assert_eq!(escrow.deposit, 600);
+        assert_eq!(escrow.receive, 300);
+        assert_eq!(escrow.receive_alt, 1_200);
+    }
+
+    #[test]
+    fn apply_fill_rejects_overfill() {
+        let mut escrow = fixed(100, 50);
+
+        assert_err(escrow.apply_fill(101, 50, false), EscrowError::MathOverflow);
+        assert_err(escrow.apply_fill(100, 51, false), EscrowError::MathOverflow);
+    }
+
+    #[test]
+    fn signer_seeds_derive_escrow_pda() {
+        let mut escrow = fixed(100, 50);
+        escrow.seed = u64::MAX - 7;
+        escrow.bump = escrow_pda(&escrow.creator, escrow.seed, &crate::ID).1;
+        let seed_bytes = escrow.seed.to_le_bytes();
+
+        let key = Pubkey::create_program_address(&escrow.signer_seeds(&seed_bytes), &crate::ID)
+            .unwrap();
+
+        assert_eq!(key, escrow_account(&escrow).key);
+        // seeds 以 creator 派生：转让 maker 后 PDA 不变
+        escrow.maker = Pubkey::new_unique();
+        assert_eq!(
+            Pubkey::create_program_address(&escrow.signer_seeds(&seed_bytes), &crate::ID),
+            Ok(key)
+        );
+    }
+
+    #[test]
+    fn auction_price_declines_linearly() {
+        let escrow = auction(100, 1_000, 200);
+
+        // 开始前与开始时取起始价
+        assert_eq!(escrow.current_receive(500).unwrap(), 1_000);
+        assert_eq!(escrow.current_receive(1_000).unwrap(), 1_000);
+        // 中点下降一半差价
+        assert_eq!(escrow.current_receive(1_500).unwrap(), 600);
+        assert_eq!(escrow.current_receive(1_750).unwrap(), 400);
+        // 结束后保持最低价
+        assert_eq!(escrow.current_receive(2_000).unwrap(), 200);
+        assert_eq!(escrow.current_receive(i64::MAX).unwrap(), 200);
+        // 部分成交按当前总价等比例计算
+        assert_eq!(escrow.receive_for(25, 1_500, false).unwrap(), 150);
+
+        // 非荷兰拍忽略拍卖字段
+        let flat = Escrow { is_auction: false, ..escrow };
+        assert_eq!(flat.current_receive(1_500).unwrap(), 1_000);
+    }
+
+    #[test]
+    fn rent_destination_defaults_to_maker() {
+        let mut escrow = fixed(100, 50);
+        let maker = escrow.maker;
+
+        assert_eq!(escrow.rent_destination(maker), maker);
+        assert_eq!(escrow.vault_rent_destination(maker), maker);
+
+        // vault 租金默认跟随 rent_collector，也可单独指定
+        let collector = Pubkey::new_unique();
+        escrow.rent_collector = collector;
+        assert_eq!(escrow.rent_destination(maker), collector);
+        assert_eq!(escrow.vault_rent_destination(maker), collector);
+        let vault_payer = Pubkey::new_unique();
+        escrow.vault_rent_payer = vault_payer;
+        assert_eq!(escrow.rent_destination(maker), collector);
+        assert_eq!(escrow.vault_rent_destination(maker), vault_payer);
+    }
+
+    #[test]
+    fn escrow_pda_uses_creator_and_seed() {
+        let creator = Pubkey::new_unique();
+        let (key, bump) = escrow_pda(&creator, 42, &crate::ID);
+
+        assert_eq!(
+            Pubkey::create_program_address(
+                &[b"escrow", creator.as_ref(), &42u64.to_le_bytes(), &[bump]],
+                &crate::ID,
+            ),
+            Ok(key)
+        );
+        assert_ne!(escrow_pda(&creator, 43, &crate::ID).0, key);
+        assert_ne!(escrow_pda(&Pubkey::new_unique(), 42, &crate::ID).0, key);
+
+        // strict-pda 构建只接受规范 bump，默认构建不重新推导
+        let mut escrow = fixed(100, 50);
+        assert!(escrow.has_canonical_bump());
+        escrow.bump = escrow.bump.wrapping_sub(1);
+        assert_eq!(escrow.has_canonical_bump(), !cfg!(feature = "strict-pda"));
+    }
+
+    #[test]
+    fn fee_bps_for_prefers_escrow_override() {
+        set_clock(1_000, 1);
+        let config = Config {
+            fee_bps: 30,
+            ..default_config()
+        };
+        let mut escrow = fixed(100, 10_000);
+
+        // 默认使用全局费率
+        assert_eq!(config.fee_bps_for(&escrow), 30);
+        assert_eq!(config.fee_for(&escrow, 10_000).unwrap(), 30);
+
+        // 专属费率（包括 0）优先，且不超过 MAX_FEE_BPS
+        escrow.fee_bps_override = 5;
+        assert_eq!(config.fee_bps_for(&escrow), 5);
+        assert_eq!(config.fee_for(&escrow, 10_000).unwrap(), 5);
+        escrow.fee_bps_override = 0;
+        assert_eq!(config.fee_bps_for(&escrow), 0);
+        escrow.fee_bps_override = Config::MAX_FEE_BPS + 1;
+        assert_eq!(config.fee_bps_for(&escrow), Config::MAX_FEE_BPS);
+    }
+
+    #[test]
+    fn referral_splits_protocol_fee_three_ways() {
+        set_clock(1_000, 1);
+        let config = Config {
+            fee_bps: 100,
+            referral_bps: 40,
+            ..default_config()
+        };
+        let escrow = fixed(100, 10_000);
+
+        // 10_000 Token B：maker 9_900，推荐人 40，treasury 60
+        let fee = config.fee_for(&escrow, 10_000).unwrap();
+        let referral = config.referral_fee_for(10_000, fee).unwrap();
+        let (maker, treasury) = (10_000 - fee, fee - referral);
+        assert_eq!((maker, treasury, referral), (9_900, 60, 40));
+        assert_eq!(maker + treasury + referral, 10_000);
+
+        // 专属费率低于 referral_bps 时推荐奖励不超过协议费本身
+        let discounted = Escrow {
+            fee_bps_override: 10,
+            ..escrow
+        };
+        let fee = config.fee_for(&discounted, 10_000).unwrap();
+        assert_eq!(config.referral_fee_for(10_000, fee).unwrap(), fee);
+    }
+
+    #[test]
+    fn maker_sits_at_maker_offset() {
+        let mut escrow = fixed(100, 50);
+        escrow.seed = 42;
+        let fetched = escrow_account(&escrow).data;
+
+        // indexer 的 memcmp 过滤条件：MAKER_OFFSET 处的 32 字节即 maker
+        assert_eq!(MAKER_OFFSET, 1);
+        assert_eq!(&fetched[MAKER_OFFSET..MAKER_OFFSET + 32], escrow.maker.as_ref());
+        assert_eq!(fetched.len(), Escrow::DISCRIMINATOR.len() + Escrow::INIT_SPACE);
+
+        let decoded = Escrow::try_deserialize(&mut &fetched[..]).unwrap();
+        assert!(decoded == escrow);
+    }
+
+    #[test]
+    fn within_max_ttl_caps_expiry() {
+        let config = Config {
+            max_ttl_seconds: 3_600,
+            ..default_config()
+        };
+
+        // 恰好达到上限与超出上限
+        assert!(config.within_max_ttl(4_600, 1_000));
+        assert!(!config.within_max_ttl(4_601, 1_000));
+        // 限制存活时间时不允许永不过期
+        assert!(!config.within_max_ttl(0, 1_000));
+
+        // max_ttl_seconds = 0 表示不限制
+        let unlimited = default_config();
+        assert!(unlimited.within_max_ttl(0, 1_000));
+        assert!(unlimited.within_max_ttl(i64::MAX, 1_000));
+    }
+
+    #[test]
+    fn open_escrows_are_capped_per_maker() {
+        let config = Config {
+            max_open_per_maker: 3,
+            ..default_config()
+        };
+        let mut stats = MakerStats {
+            maker: Pubkey::new_unique(),
+            total_created: 0,
+            total_taken: 0,
+            total_refunded: 0,
+            open_escrows: 0,
+            bump: 255,
+        };
+
+        // 最多同时挂 3 个
+        while config.within_open_limit(stats.open_escrows) {
+            stats.record_created().unwrap();
+        }
+        assert_eq!(stats.open_escrows, 3);
+        assert!(!config.within_open_limit(3));
+
+        // 全部成交或退款关闭后名额释放
+        stats.record_taken().unwrap();
+        assert!(config.within_open_limit(stats.open_escrows));
+        stats.record_refunded().unwrap();
+        stats.record_closed();
+        assert_eq!(stats.open_escrows, 0);
+        assert_eq!(
+            (stats.total_created, stats.total_taken, stats.total_refunded),
+            (3, 1, 1)
+        );
+
+        // 计数上线前创建的 escrow 关闭时不会下溢
+        stats.record_refunded().unwrap();
+        assert_eq!(stats.open_escrows, 0);
+
+        // max_open_per_maker = 0 表示不限制
+        assert!(default_config().within_open_limit(u64::MAX - 1));
+    }
+
+    #[test]
+    fn invalid_mint_b_logs_expected_and_actual() {
+        let mut escrow = fixed(100, 50);
+        escrow.mint_b = Pubkey::new_from_array([1; 32]);
+        let wrong = Pubkey::new_from_array([2; 32]);
+
+        let Some(logs) = program_logs("state::tests::invalid_mint_b_logs_expected_and_actual", || {
+            assert_err(escrow.check_mint_b(&wrong), EscrowError::InvalidMintB);
+        }) else {
+            return;
+        };
+
+        assert!(logs.contains(&format!("expected {}", escrow.mint_b)));
+        assert!(logs.contains(&format!("got {wrong}")));
+    }
+
+    #[test]
+    fn fee_discount_grows_with_escrow_age() {
+        const DAY: i64 = 86_400;
+        let config = Config {
+            fee_bps: 30,
+            discount_bps_per_day: 2,
+            max_discount_bps: 10,
+            ..default_config()
+        };
+        let mut escrow = fixed(100, 100_000);
+        escrow.created_at = 0;
+
+        // (成交时间, 折扣, 100_000 Token B 的协议费)
+        for (now, discount, fee) in [
+            (0, 0, 300),
+            (DAY - 1, 0, 300),
+            (DAY, 2, 280),
+            (3 * DAY + 100, 6, 240),
+            (5 * DAY, 10, 200),
+            (30 * DAY, 10, 200),
+        ] {
+            set_clock(now, 1);
+            assert_eq!(config.discount_bps_for(escrow.created_at, now), discount);
+            assert_eq!(config.fee_for(&escrow, 100_000).unwrap(), fee);
+        }
+
+        // 时钟回拨时不产生折扣；折扣超过费率时费率为 0
+        assert_eq!(config.discount_bps_for(DAY, 0), 0);
+        escrow.fee_bps_override = 4;
+        set_clock(5 * DAY, 1);
+        assert_eq!(config.fee_for(&escrow, 100_000).unwrap(), 0);
+    }
+
+    #[test]
+    fn partial_drain_must_keep_source_rent_exempt() {
+        set_clock(1_000, 1);
+        let leak = |account: TestAccount| -> &'static AccountInfo<'static> {
+            Box::leak(Box::new(account.into_info()))
+        };
+        let system_program = Program::<System>::try_from(leak(TestAccount::program(
+            anchor_lang::system_program::ID,
+        )))
+        .unwrap();
+        let rent_floor = Rent::default().minimum_balance(165);
+        let from = TestAccount {
+            lamports: rent_floor + 1_000,
+            ..TestAccount::new(Pubkey::new_unique(), crate::ID, vec![0; 165])
+        };
+        let to = leak(TestAccount::wallet(Pubkey::new_unique(), 0));
+        let drain = |amount: u64| {
+            let from = leak(from.clone()).clone();
+
+            transfer_lamports_rent_safe(&system_program, from, to.clone(), amount)
+        };
+
+        // 转出后余额低于免租下限（且不为 0）
+        assert_err(drain(1_001), EscrowError::WouldBreakRentExemption);
+        assert_err(drain(rent_floor + 999), EscrowError::WouldBreakRentExemption);
+        assert_err(drain(rent_floor + 1_001), EscrowError::InsufficientFunds);
+    }
+}
@@ -0,0 +1,764 @@
+use std::cell::{Cell, RefCell};
+use std::sync::Once;
+
+use anchor_lang::prelude::*;
+use anchor_lang::{Bumps, CheckOwner};
+use anchor_lang::solana_program::{
+    entrypoint::MAX_PERMITTED_DATA_INCREASE, program_option::COption, program_pack::Pack,
+};
+use anchor_spl::{
+    associated_token::get_associated_token_address_with_program_id,
+    token::spl_token::{
+        self,
+        state::{Account as SplAccount, AccountState, Mint as SplMint},
+    },
+    token_2022::spl_token_2022::{
+        self,
+        extension::{
+            permanent_delegate::PermanentDelegate, transfer_hook::TransferHook,
+            BaseStateWithExtensionsMut, ExtensionType, StateWithExtensionsMut,
+        },
+        state::Mint as Token2022Mint,
+    },
+    token_interface::Mint,
+};
+pub use anchor_lang::solana_program::entrypoint::ProgramResult;
+use solana_program::program_stubs::{set_syscall_stubs, SyscallStubs};
+
+use crate::state::{escrow_pda, Config, Escrow, EscrowStatus, MakerStats, Reservation};
+use crate::instructions::make::MakeArgs;
+
+thread_local! {
+    /// 当前测试线程的 Clock（各测试线程互不影响）
+    static CLOCK: RefCell<Clock> = RefCell::new(Clock::default());
+
+    /// 当前测试线程剩余的计算单元（cu-profile 日志测试用）
+    static COMPUTE_UNITS: Cell<u64> = const { Cell::new(200_000) };
+}
+
+/// 宿主机上的 sysvar 桩：Clock 取自当前线程的 CLOCK，Rent 为默认值，
+/// 剩余计算单元取自当前线程的 COMPUTE_UNITS
+struct TestStubs;
+
+impl SyscallStubs for TestStubs {
+    fn sol_get_clock_sysvar(&self, var_addr: *mut u8) -> u64 {
+        let clock = CLOCK.with(|clock| clock.borrow().clone());
+        unsafe { *(var_addr as *mut Clock) = clock };
+
+        0
+    }
+
+    fn sol_get_rent_sysvar(&self, var_addr: *mut u8) -> u64 {
+        unsafe { *(var_addr as *mut Rent) = Rent::default() };
+
+        0
+    }
+
+    fn sol_remaining_compute_units(&self) -> u64 {
+        COMPUTE_UNITS.with(Cell::get)
+    }
+}
+
+/// 设置当前测试线程的时间与 slot（首次调用时安装 sysvar 桩）
+///
+/// 宿主机上不能执行 CPI（solana_invoke 直接 panic），指令级测试只能覆盖第一次 CPI 之前的路径
+pub fn set_clock(unix_timestamp: i64, slot: u64) {
+    static INSTALL: Once = Once::new();
+    INSTALL.call_once(|| {
+        set_syscall_stubs(Box::new(TestStubs));
+    });
+
+    CLOCK.with(|clock| {
+        let mut clock = clock.borrow_mut();
+        clock.unix_timestamp = unix_timestamp;
+        clock.slot = slot;
+    });
+}
+
+/// 模拟消耗 units 个计算单元（需先调用 set_clock 安装 sysvar 桩）
+#[cfg(feature = "cu-profile")]
+pub fn consume_compute_units(units: u64) {
+    COMPUTE_UNITS.with(|remaining| remaining.set(remaining.get().saturating_sub(units)));
+}
+
+/// 测试账户：字段与运行时传入的 AccountInfo 一一对应
+#[derive(Clone)]
+pub struct TestAccount {
+    pub key: Pubkey,
+    pub owner: Pubkey,
+    pub lamports: u64,
+    pub data: Vec<u8>,
+    pub is_signer: bool,
+    pub is_writable: bool,
+    pub executable: bool,
+}
+
+impl TestAccount {
+    /// 免租的账户（lamports 取 data 长度对应的免租下限）
+    pub fn new(key: Pubkey, owner: Pubkey, data: Vec<u8>) -> Self {
+        Self {
+            key,
+            owner,
+            lamports: Rent::default().minimum_balance(data.len()),
+            data,
+            is_signer: false,
+            is_writable: false,
+            executable: false,
+        }
+    }
+
+    /// 无数据的钱包账户（System Program 所有，也用于表示尚不存在的账户）
+    pub fn wallet(key: Pubkey, lamports: u64) -> Self {
+        Self {
+            lamports,
+            ..Self::new(key, anchor_lang::system_program::ID, vec![])
+        }
+    }
+
+    /// 程序账户（Program / Interface 要求 executable）
+    pub fn program(key: Pubkey) -> Self {
+        Self {
+            executable: true,
+            ..Self::new(key, anchor_lang::solana_program::bpf_loader_upgradeable::ID, vec![])
+        }
+    }
+
+    pub fn signer(mut self) -> Self {
+        self.is_signer = true;
+        self.is_writable = true;
+        self
+    }
+
+    pub fn writable(mut self) -> Self {
+        self.is_writable = true;
+        self
+    }
+
+    /// 转换为 AccountInfo（内存泄漏到 'static，仅用于测试）
+    ///
+    /// 内存布局与运行时序列化一致：key 前 4 字节为原始数据长度、数据前 8 字节为当前长度，
+    /// 数据后预留 MAX_PERMITTED_DATA_INCREASE，使 resize / close 在宿主机上可用
+    pub fn into_info(self) -> AccountInfo<'static> {
+        #[repr(C)]
+        struct KeyHeader {
+            _padding: u32,
+            original_data_len: u32,
+            key: Pubkey,
+        }
+
+        let len = self.data.len();
+        let header = Box::leak(Box::new(KeyHeader {
+            _padding: 0,
+            original_data_len: len as u32,
+            key: self.key,
+        }));
+
+        let words = (8 + len + MAX_PERMITTED_DATA_INCREASE).div_ceil(8);
+        let buffer: &'static mut [u64] = Box::leak(vec![0u64; words].into_boxed_slice());
+        buffer[0] = len as u64;
+        let bytes = unsafe {
+            std::slice::from_raw_parts_mut(buffer.as_mut_ptr() as *mut u8, words * 8)
+        };
+        bytes[8..8 + len].copy_from_slice(&self.data);
+
+        AccountInfo::new(
+            &header.key,
+            self.is_signer,
+            self.is_writable,
+            Box::leak(Box::new(self.lamports)),
+            &mut bytes[8..8 + len],
+            Box::leak(Box::new(self.owner)),
+            self.executable,
+            0,
+        )
+    }
+}
+
+/// 在子进程中重新执行当前测试，返回其标准输出（宿主机上 msg! 直接 println!，进程内无法捕获）
+///
+/// test 为不含 crate 名的测试路径；子进程内执行 f 后返回 None，调用方应直接返回，
+/// 父进程返回子进程的输出。f 中的 Pubkey 需用固定字节构造，父进程才能比对
+pub fn program_logs(test: &str, f: impl FnOnce()) -> Option<String> {
+    const CHILD: &str = "ESCROW_TEST_LOG_CAPTURE";
+
+    if std::env::var(CHILD).as_deref() == Ok(test) {
+        f();
+        return None;
+    }
+
+    let output = std::process::Command::new(std::env::current_exe().unwrap())
+        .args([test, "--exact", "--nocapture", "--test-threads=1"])
+        .env(CHILD, test)
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+
+    Some(String::from_utf8(output.stdout).unwrap())
+}
+
+/// 以 accounts 调用程序入口，返回指令结果与（执行后的）AccountInfo，便于检查账户状态
+pub fn run(data: &[u8], accounts: Vec<TestAccount>) -> (ProgramResult, Vec<AccountInfo<'static>>) {
+    let infos: &'static [AccountInfo<'static>] = Box::leak(
+        accounts
+            .into_iter()
+            .map(TestAccount::into_info)
+            .collect::<Vec<_>>()
+            .into_boxed_slice(),
+    );
+
+    (crate::entry(&crate::ID, infos, data), infos.to_vec())
+}
+
+/// 按账户约束校验并构造指令的账户结构（不执行 handler），返回账户与 bumps
+///
+/// 配合 Context::new 直接调用 handler，可检查通过 return data 返回的结果
+pub fn load_accounts<T>(accounts: Vec<TestAccount>, ix_data: &[u8]) -> Result<(T, T::Bumps)>
+where
+    T: Bumps + Accounts<'static, T::Bumps>,
+    T::Bumps: Default,
+{
+    let mut infos: &'static [AccountInfo<'static>] = Box::leak(
+        accounts
+            .into_iter()
+            .map(TestAccount::into_info)
+            .collect::<Vec<_>>()
+            .into_boxed_slice(),
+    );
+    let mut bumps = T::Bumps::default();
+    let accounts = T::try_accounts(
+        &crate::ID,
+        &mut infos,
+        ix_data,
+        &mut bumps,
+        &mut std::collections::BTreeSet::new(),
+    )?;
+
+    Ok((accounts, bumps))
+}
+
+/// 可选账户不传入时的占位（Anchor 约定传入本程序 ID）
+pub fn none() -> TestAccount {
+    TestAccount::program(crate::ID)
+}
+
+/// 指令数据：1 字节 discriminator + borsh 参数
+pub fn ix_data(discriminator: u8, args: impl AnchorSerialize) -> Vec<u8> {
+    let mut data = vec![discriminator];
+    args.serialize(&mut data).unwrap();
+
+    data
+}
+
+/// 期望指令以 error 失败
+pub fn assert_error(result: ProgramResult, error: impl Into<anchor_lang::error::Error>) {
+    let expected: ProgramError = error.into().into();
+    assert_eq!(result, Err(expected));
+}
+
+/// 期望返回 Result 的函数以 error 失败
+pub fn assert_err<T>(result: Result<T>, error: impl Into<anchor_lang::error::Error>) {
+    let expected: ProgramError = error.into().into();
+    match result {
+        Ok(_) => panic!("expected {expected:?}, got Ok"),
+        Err(err) => assert_eq!(ProgramError::from(err), expected),
+    }
+}
+
+/// 创建 Token A / Token B 两个 SPL Token mint（供应量为 0，无 mint / freeze authority）
+pub fn setup_mints(decimals_a: u8, decimals_b: u8) -> (TestAccount, TestAccount) {
+    (mint_account(decimals_a), mint_account(decimals_b))
+}
+
+/// 单个 SPL Token mint 账户
+pub fn mint_account(decimals: u8) -> TestAccount {
+    let mut data = vec![0u8; SplMint::LEN];
+    SplMint::pack(
+        SplMint {
+            mint_authority: COption::None,
+            supply: 0,
+            decimals,
+            is_initialized: true,
+            freeze_authority: COption::None,
+        },
+        &mut data,
+    )
+    .unwrap();
+
+    TestAccount::new(Pubkey::new_unique(), spl_token::ID, data)
+}
+
+/// 启用了 PermanentDelegate 扩展的 Token-2022 mint（delegate 可以转走任何持有人的余额）
+pub fn permanent_delegate_mint(delegate: &Pubkey) -> TestAccount {
+    let len = ExtensionType::try_calculate_account_len::<Token2022Mint>(&[
+        ExtensionType::PermanentDelegate,
+    ])
+    .unwrap();
+    let mut data = vec![0u8; len];
+    let mut state =
+        StateWithExtensionsMut::<Token2022Mint>::unpack_uninitialized(&mut data).unwrap();
+    state.init_extension::<PermanentDelegate>(true).unwrap().delegate =
+        Some(*delegate).try_into().unwrap();
+    state.base.decimals = 6;
+    state.base.is_initialized = true;
+    state.pack_base();
+    state.init_account_type().unwrap();
+
+    TestAccount::new(Pubkey::new_unique(), spl_token_2022::ID, data)
+}
+
+/// 配置了 transfer hook 程序 hook_program 的 Token-2022 mint
+pub fn transfer_hook_mint(hook_program: Pubkey) -> TestAccount {
+    let len =
+        ExtensionType::try_calculate_account_len::<Token2022Mint>(&[ExtensionType::TransferHook])
+            .unwrap();
+    let mut data = vec![0u8; len];
+    let mut state =
+        StateWithExtensionsMut::<Token2022Mint>::unpack_uninitialized(&mut data).unwrap();
+    let hook = state.init_extension::<TransferHook>(true).unwrap();
+    hook.program_id = Some(hook_program).try_into().unwrap();
+    state.base.decimals = 6;
+    state.base.is_initialized = true;
+    state.pack_base();
+    state.init_account_type().unwrap();
+
+    TestAccount::new(Pubkey::new_unique(), spl_token_2022::ID, data)
+}
+
+/// owner 持有 amount 个 mint 的规范 ATA
+pub fn fund_ata(owner: &Pubkey, mint: &TestAccount, amount: u64) -> TestAccount {
+    let mut data = vec![0u8; SplAccount::LEN];
+    SplAccount::pack(
+        SplAccount {
+            mint: mint.key,
+            owner: *owner,
+            amount,
+            delegate: COption::None,
+            state: AccountState::Initialized,
+            is_native: COption::None,
+            delegated_amount: 0,
+            close_authority: COption::None,
+        },
+        &mut data,
+    )
+    .unwrap();
+    let key = get_associated_token_address_with_program_id(owner, &mint.key, &mint.owner);
+
+    TestAccount::new(key, mint.owner, data)
+}
+
+/// 将 token 账户置为冻结状态（相当于 mint 的 freeze authority 执行了 freeze_account）
+pub fn freeze(mut token_account: TestAccount) -> TestAccount {
+    let mut account = SplAccount::unpack(&token_account.data).unwrap();
+    account.state = AccountState::Frozen;
+    SplAccount::pack(account, &mut token_account.data).unwrap();
+
+    token_account
+}
+
+/// 授权 delegate 动用 token 账户中的 amount 个代币（相当于持有人执行了 approve）
+pub fn approve(mut token_account: TestAccount, delegate: &Pubkey, amount: u64) -> TestAccount {
+    let mut account = SplAccount::unpack(&token_account.data).unwrap();
+    account.delegate = COption::Some(*delegate);
+    account.delegated_amount = amount;
+    SplAccount::pack(account, &mut token_account.data).unwrap();
+
+    token_account
+}
+
+/// 与 make 写入的条款相同的 escrow（固定价格、不限制 taker、使用全局费率）
+pub fn make_escrow(
+    maker: &Pubkey,
+    seed: u64,
+    mint_a: &TestAccount,
+    mint_b: &TestAccount,
+    deposit: u64,
+    receive: u64,
+) -> Escrow {
+    let (_, bump) = escrow_pda(maker, seed, &crate::ID);
+
+    Escrow {
+        maker: *maker,
+        seed,
+        mint_a: mint_a.key,
+        mint_b: mint_b.key,
+        receive,
+        deposit,
+        expiry: 0,
+        min_hold_until: 0,
+        allowed_taker: Pubkey::default(),
+        status: EscrowStatus::Active,
+        created_at: 0,
+        is_auction: false,
+        start_receive: 0,
+        end_receive: 0,
+        auction_start: 0,
+        auction_end: 0,
+        min_fill: 0,
+        max_fill: 0,
+        mint_b_alt: Pubkey::default(),
+        receive_alt: 0,
+        rent_collector: Pubkey::default(),
+        is_delegated: false,
+        mint_a_decimals: mint_decimals(mint_a),
+        mint_b_decimals: mint_decimals(mint_b),
+        memo: [0; 32],
+        creator: *maker,
+        mint_a2: Pubkey::default(),
+        deposit2: 0,
+        in_progress: false,
+        fee_bps_override: Escrow::FEE_BPS_USE_GLOBAL,
+        vault_rent_payer: Pubkey::default(),
+        proceeds_recipient: Pubkey::default(),
+        round_up_receive: false,
+        is_ratio: false,
+        price_num: 0,
+        price_den: 0,
+        maker_ata_b_ready: false,
+        reserved: 0,
+        gate_mint: Pubkey::default(),
+        gate_min_balance: 0,
+        version: Escrow::CURRENT_VERSION,
+        bump,
+    }
+}
+
+/// 100 Token A 换 50 Token B 的固定价格条款，其余参数均为默认（不启用）
+pub fn make_args() -> MakeArgs {
+    MakeArgs {
+        seed: 1,
+        receive: 50,
+        amount: 100,
+        expiry: 0,
+        min_hold_until: 0,
+        allowed_taker: Pubkey::default(),
+        is_auction: false,
+        start_receive: 0,
+        end_receive: 0,
+        auction_start: 0,
+        auction_end: 0,
+        min_fill: 0,
+        max_fill: 0,
+        mint_b_alt: Pubkey::default(),
+        receive_alt: 0,
+        rent_collector: Pubkey::default(),
+        memo: [0; 32],
+        fee_bps_override: Escrow::FEE_BPS_USE_GLOBAL,
+        vault_rent_payer: Pubkey::default(),
+        proceeds_recipient: Pubkey::default(),
+        round_up_receive: false,
+        is_ratio: false,
+        price_num: 0,
+        price_den: 0,
+        gate_mint: Pubkey::default(),
+        gate_min_balance: 0,
+    }
+}
+
+/// maker 以 make_args() 创建、在 created_at = 1_000 上链的 escrow（与 make 写入的状态逐字段相同）
+pub fn made_escrow(maker: &Pubkey, mint_a: &TestAccount, mint_b: &TestAccount) -> Escrow {
+    let (_, bump) = escrow_pda(maker, make_args().seed, &crate::ID);
+
+    make_args().to_escrow(
+        *maker,
+        &load_interface_account::<Mint>(mint_a.clone()),
+        &load_interface_account::<Mint>(mint_b.clone()),
+        1_000,
+        bump,
+    )
+}
+
+/// escrow 的 PDA 账户（由本程序所有）
+pub fn escrow_account(escrow: &Escrow) -> TestAccount {
+    let (key, _) = escrow_pda(&escrow.creator, escrow.seed, &crate::ID);
+
+    program_account(key, escrow)
+}
+
+/// 所有规则均关闭的全局配置（费率为 0、不暂停、不限制挂单）
+pub fn default_config() -> Config {
+    Config {
+        authority: Pubkey::new_unique(),
+        fee_bps: 0,
+        treasury: Pubkey::new_unique(),
+        paused: false,
+        cancel_fee_lamports: 0,
+        min_deposit: 0,
+        min_receive: 0,
+        referral_bps: 0,
+        restrict_pairs: false,
+        max_ttl_seconds: 0,
+        max_open_per_maker: 0,
+        discount_bps_per_day: 0,
+        max_discount_bps: 0,
+        cranker_bounty_lamports: 0,
+        require_take_memo: false,
+        bump: config_pda().1,
+    }
+}
+
+/// 全局 Config PDA 地址与 bump
+pub fn config_pda() -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"config"], &crate::ID)
+}
+
+/// 已初始化的 Config PDA 账户
+pub fn config_account(config: &Config) -> TestAccount {
+    program_account(config_pda().0, config)
+}
+
+/// 尚未初始化的 Config PDA（空账户）
+pub fn no_config() -> TestAccount {
+    TestAccount::wallet(config_pda().0, 0)
+}
+
+/// maker 的 MakerStats PDA 账户（open_escrows 个未关闭的 escrow）
+pub fn maker_stats_account(maker: &Pubkey, open_escrows: u64) -> TestAccount {
+    let (key, bump) =
+        Pubkey::find_program_address(&[MakerStats::SEED_PREFIX, maker.as_ref()], &crate::ID);
+    let stats = MakerStats {
+        maker: *maker,
+        total_created: open_escrows,
+        total_taken: 0,
+        total_refunded: 0,
+        open_escrows,
+        bump,
+    };
+
+    program_account(key, &stats)
+}
+
+/// 序列化 Anchor 账户（discriminator + borsh）并放在 key 地址上
+pub fn program_account<T: AccountSerialize>(key: Pubkey, account: &T) -> TestAccount {
+    let mut data = Vec::new();
+    account.try_serialize(&mut data).unwrap();
+
+    TestAccount::new(key, crate::ID, data)
+}
+
+/// taker 对 escrow 的预留 PDA，锁定 amount 个 Token A，expires_at 过期
+pub fn reservation_account(
+    escrow: &Escrow,
+    taker: &Pubkey,
+    amount: u64,
+    expires_at: i64,
+) -> TestAccount {
+    let escrow_key = escrow_account(escrow).key;
+    let (key, bump) = Pubkey::find_program_address(
+        &[Reservation::SEED_PREFIX, escrow_key.as_ref(), taker.as_ref()],
+        &crate::ID,
+    );
+    let reservation = Reservation {
+        escrow: escrow_key,
+        taker: *taker,
+        amount,
+        expires_at,
+        bump,
+    };
+
+    program_account(key, &reservation)
+}
+
+/// MakeChecked 的全部账户（按结构体字段顺序）：escrow 为已存在的账户
+/// （init_if_needed 复用已有账户，不触发 CPI），maker 持有 1_000 Token A
+pub fn make_checked_accounts(
+    maker: &Pubkey,
+    escrow: &Escrow,
+    mint_a: &TestAccount,
+    mint_b: &TestAccount,
+) -> Vec<TestAccount> {
+    let escrow_account = escrow_account(escrow);
+
+    vec![
+        TestAccount::wallet(*maker, 1_000_000_000).signer(),
+        escrow_account.clone().writable(),
+        maker_stats_account(maker, 1).writable(),
+        no_config(),
+        none(),
+        mint_a.clone(),
+        mint_b.clone(),
+        fund_ata(maker, mint_a, 1_000).writable(),
+        fund_ata(&escrow_account.key, mint_a, escrow.deposit).writable(),
+        TestAccount::program(anchor_spl::associated_token::ID),
+        TestAccount::program(mint_a.owner),
+        TestAccount::program(anchor_lang::system_program::ID),
+    ]
+}
+
+/// Take 的全部账户（字段与 Take 结构体一一对应），into_vec 按结构体字段顺序展开
+#[derive(Clone)]
+pub struct TakeAccounts {
+    pub taker: TestAccount,
+    pub maker: TestAccount,
+    pub escrow: TestAccount,
+    pub rent_collector: TestAccount,
+    pub vault_rent_payer: TestAccount,
+    pub proceeds_recipient: TestAccount,
+    pub config: TestAccount,
+    pub maker_stats: TestAccount,
+    pub treasury: TestAccount,
+    pub mint_a: TestAccount,
+    pub mint_b: TestAccount,
+    pub vault: TestAccount,
+    pub taker_ata_a: TestAccount,
+    pub taker_ata_b: TestAccount,
+    pub maker_ata_b: TestAccount,
+    pub treasury_ata_b: TestAccount,
+    pub referrer_ata_b: TestAccount,
+    pub gate_ata: TestAccount,
+    pub associated_token_program: TestAccount,
+    pub token_program: TestAccount,
+    pub system_program: TestAccount,
+}
+
+impl TakeAccounts {
+    pub fn into_vec(self) -> Vec<TestAccount> {
+        vec![
+            self.taker,
+            self.maker,
+            self.escrow,
+            self.rent_collector,
+            self.vault_rent_payer,
+            self.proceeds_recipient,
+            self.config,
+            self.maker_stats,
+            self.treasury,
+            self.mint_a,
+            self.mint_b,
+            self.vault,
+            self.taker_ata_a,
+            self.taker_ata_b,
+            self.maker_ata_b,
+            self.treasury_ata_b,
+            self.referrer_ata_b,
+            self.gate_ata,
+            self.associated_token_program,
+            self.token_program,
+            self.system_program,
+        ]
+    }
+}
+
+/// Take 的全部账户：vault 存有 escrow.deposit，taker 持有 taker_b 个 Token B，
+/// 所有 ATA 均已存在（ensure_atas 不触发 CPI），不传推荐人与门槛账户
+pub fn take_accounts(
+    taker: &Pubkey,
+    escrow: &Escrow,
+    config: &Config,
+    mint_a: &TestAccount,
+    mint_b: &TestAccount,
+    taker_b: u64,
+) -> TakeAccounts {
+    let escrow_key = escrow_account(escrow).key;
+    let proceeds = escrow.proceeds_destination();
+
+    TakeAccounts {
+        taker: TestAccount::wallet(*taker, 1_000_000_000).signer(),
+        maker: TestAccount::wallet(escrow.maker, 0).writable(),
+        escrow: escrow_account(escrow).writable(),
+        rent_collector: TestAccount::wallet(escrow.rent_destination(escrow.maker), 0).writable(),
+        vault_rent_payer: TestAccount::wallet(escrow.vault_rent_destination(escrow.maker), 0)
+            .writable(),
+        proceeds_recipient: TestAccount::wallet(proceeds, 0),
+        config: config_account(config),
+        maker_stats: none(),
+        treasury: TestAccount::wallet(config.treasury, 0),
+        mint_a: mint_a.clone(),
+        mint_b: mint_b.clone(),
+        vault: fund_ata(&escrow_key, mint_a, escrow.deposit).writable(),
+        taker_ata_a: fund_ata(taker, mint_a, 0).writable(),
+        taker_ata_b: fund_ata(taker, mint_b, taker_b).writable(),
+        maker_ata_b: fund_ata(&proceeds, mint_b, 0).writable(),
+        treasury_ata_b: fund_ata(&config.treasury, mint_b, 0).writable(),
+        referrer_ata_b: none(),
+        gate_ata: none(),
+        associated_token_program: TestAccount::program(anchor_spl::associated_token::ID),
+        token_program: TestAccount::program(mint_a.owner),
+        system_program: TestAccount::program(anchor_lang::system_program::ID),
+    }
+}
+
+/// 反序列化为 Account（用于直接调用以 Account 为参数的辅助函数）
+pub fn load_account<T>(account: TestAccount) -> Account<'static, T>
+where
+    T: AccountSerialize + AccountDeserialize + Owner + Clone,
+{
+    let info: &'static AccountInfo<'static> = Box::leak(Box::new(account.into_info()));
+
+    Account::try_from(info).unwrap()
+}
+
+/// 反序列化为 InterfaceAccount（token 账户 / mint）
+pub fn load_interface_account<T>(account: TestAccount) -> InterfaceAccount<'static, T>
+where
+    T: AccountSerialize + AccountDeserialize + CheckOwner + Clone,
+{
+    let info: &'static AccountInfo<'static> = Box::leak(Box::new(account.into_info()));
+
+    InterfaceAccount::try_from(info).unwrap()
+}
+
+fn mint_decimals(mint: &TestAccount) -> u8 {
+    // Token-2022 mint 的扩展数据位于基础布局之后
+    SplMint::unpack(&mint.data[..SplMint::LEN]).unwrap().decimals
+}
+
+#[cfg(test)]
+mod tests {
+    use anchor_spl::token_interface::TokenAccount;
+
+    use super::*;
+
+    #[test]
+    fn account_info_supports_resize() {
+        let info = TestAccount::new(Pubkey::new_unique(), crate::ID, vec![1; 10]).into_info();
+
+        info.resize(10 + MAX_PERMITTED_DATA_INCREASE).unwrap();
+        assert_eq!(info.data_len(), 10 + MAX_PERMITTED_DATA_INCREASE);
+        assert!(info.resize(11 + MAX_PERMITTED_DATA_INCREASE).is_err());
+    }
+
+    #[test]
+    fn make_escrow_loads_into_accounts() {
+        set_clock(1_000, 1);
+        let maker = Pubkey::new_unique();
+        let (mint_a, mint_b) = setup_mints(6, 9);
+        let escrow = make_escrow(&maker, 7, &mint_a, &mint_b, 100, 50);
+        let (accounts, _) = load_accounts::<crate::instructions::GetTerms>(
+            vec![escrow_account(&escrow)],
+            &[],
+        )
+        .unwrap();
+
+        assert_eq!(accounts.escrow.key(), escrow_pda(&maker, 7, &crate::ID).0);
+        assert_eq!((accounts.escrow.deposit, accounts.escrow.receive), (100, 50));
+    }
+
+    #[test]
+    fn none_is_the_program_placeholder() {
+        let none = none();
+
+        assert_eq!(none.key, crate::ID);
+        assert!(none.executable);
+    }
+
+    #[test]
+    fn fund_ata_creates_canonical_token_account() {
+        let owner = Pubkey::new_unique();
+        let (mint, _) = setup_mints(6, 6);
+        let info = Box::leak(Box::new(fund_ata(&owner, &mint, 42).into_info()));
+        let ata = InterfaceAccount::<TokenAccount>::try_from(&*info).unwrap();
+
+        assert_eq!(
+            info.key(),
+            get_associated_token_address_with_program_id(&owner, &mint.key, &spl_token::ID)
+        );
+        assert_eq!((ata.owner, ata.mint, ata.amount), (owner, mint.key, 42));
+    }
+
+    #[test]
+    fn run_rejects_unknown_instruction() {
+        let (result, _) = run(&ix_data(u8::MAX, ()), vec![]);
+
+        assert_error(result, ErrorCode::InstructionFallbackNotFound);
+    }
+}